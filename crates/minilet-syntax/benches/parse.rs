@@ -0,0 +1,97 @@
+//! Parser throughput over generated programs.
+//!
+//! Run with `cargo bench -p minilet-syntax`. Criterion reports throughput in bytes of source per
+//! second, and the run ends by checking the throughput on the largest input against
+//! [`TARGET_MIB_PER_SEC`].
+use criterion::{criterion_group, BenchmarkId, Criterion, Throughput};
+use minilet_syntax::{lexer::lex, parse_program};
+use std::{hint::black_box, time::Instant};
+
+/// Throughput the parser is expected to sustain on [`REPORTED_STMTS`] statements, in MiB of source
+/// per second.
+///
+/// Parsing operators and keywords a character at a time measured 0.81 MiB/s here, and scanning
+/// whole runs of them 1.21 MiB/s. The target sits halfway, so that losing the scanning shows up
+/// while ordinary noise between runs does not.
+const TARGET_MIB_PER_SEC: f64 = 1.0;
+
+/// Size of the program whose throughput is checked against [`TARGET_MIB_PER_SEC`].
+const REPORTED_STMTS: usize = 10_000;
+
+/// A program of `stmts` statements mixing the constructs found in real sources: comments,
+/// operator declarations, literals, calls, blocks and `let ... in` / `where` expressions.
+fn program(stmts: usize) -> String {
+    let mut source = String::from("// generated benchmark input\ninfixl 6 <+>;\n");
+    for i in 0..stmts {
+        let stmt = match i % 6 {
+            0 => format!("let value{i} = {i} + 0x{i:x} * (value - 1);\n"),
+            1 => format!("// comment number {i}, with some prose to skip over\nlet s{i} = \"text {i} \\\"quoted\\\"\";\n"),
+            2 => format!("let t{i} = f(a, b{i}, (1, 2, 3)) <+> g(x).field;\n"),
+            3 => format!("let b{i} = {{ let y = {i}; y * y }};\n"),
+            4 => format!("let l{i} = let a = {i} in a + b where b = 2;\n"),
+            _ => format!("let n{i} = -x + ~y - (((z)));\n"),
+        };
+        source.push_str(&stmt);
+    }
+    source
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_program");
+    group.sample_size(20);
+    for stmts in [100, 1_000, REPORTED_STMTS] {
+        let source = program(stmts);
+        assert!(
+            parse_program(&source).is_ok(),
+            "generated program should parse"
+        );
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(stmts), &source, |b, source| {
+            b.iter(|| parse_program(black_box(source)))
+        });
+    }
+    group.finish();
+}
+
+fn lexer(c: &mut Criterion) {
+    let source = program(1_000);
+    let mut group = c.benchmark_group("lex");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("1000", |b| b.iter(|| lex(black_box(&source))));
+    group.finish();
+}
+
+/// Prints the best throughput of a few parses of the largest program against the target.
+fn report_target() {
+    let source = program(REPORTED_STMTS);
+    let best = (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            let _ = black_box(parse_program(black_box(&source)));
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+
+    let mib_per_sec = source.len() as f64 / (1024.0 * 1024.0) / best.as_secs_f64();
+    let verdict = if mib_per_sec >= TARGET_MIB_PER_SEC {
+        "meets"
+    } else {
+        "is BELOW"
+    };
+    println!(
+        "parse_program/{REPORTED_STMTS}: {mib_per_sec:.2} MiB/s {verdict} the target of \
+         {TARGET_MIB_PER_SEC:.2} MiB/s"
+    );
+}
+
+criterion_group!(benches, parse, lexer);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    // `cargo test` runs each benchmark once, without `--bench` and usually unoptimised.
+    if std::env::args().any(|arg| arg == "--bench") {
+        report_target();
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{
+    op::{OpSymbol, ParseOpSymbolError},
+    term::ident::ParseIdentError,
+    token::{self, ParseTokenError},
+    Ident, InputStream, Parse, ParseContext, Span, Spanned,
+};
+use parcom::prelude::*;
+
+/// Name introduced by a binding: an identifier or a parenthesized operator such as `(<+>)`.
+#[derive(Debug)]
+pub enum Binder {
+    Ident(Ident),
+    Op(OpBinder),
+}
+
+impl Spanned for Binder {
+    fn span(&self) -> Span {
+        match self {
+            Binder::Ident(v) => v.span.clone(),
+            Binder::Op(v) => v.span(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct OpBinder {
+    pub lparen: token::LParen,
+    pub symbol: OpSymbol,
+    pub rparen: token::RParen,
+}
+
+impl Spanned for OpBinder {
+    fn span(&self) -> Span {
+        self.lparen.span.to(&self.rparen.span)
+    }
+}
+
+impl Binder {
+    pub fn name(&self) -> &str {
+        match self {
+            Binder::Ident(v) => &v.text,
+            Binder::Op(v) => &v.symbol.text,
+        }
+    }
+}
+
+impl Parse for Binder {
+    type Error = ParseBinderError;
+    type Fatal = ParseBinderError;
+
+    async fn parse<S: InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let anchor = input.anchor();
+        let (e0, input) = match Ident::parse(input, ctx).await {
+            Done(v, r) => return Done(Binder::Ident(v), r),
+            Fail(e, r) => (e, r.rewind(anchor)),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (lparen, rest) = match token::LParen::parse(input, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(_, r) => return Fail(ParseBinderError::Ident(e0), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (symbol, rest) = match OpSymbol::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fatal(ParseBinderError::Symbol(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (rparen, rest) = match token::RParen::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => {
+                return Fatal(
+                    ParseBinderError::MissingClosingParen {
+                        lparen: lparen.span,
+                        error: e,
+                    },
+                    r,
+                )
+            }
+            Fatal(e, _) => e.never(),
+        };
+
+        let me = OpBinder {
+            lparen,
+            symbol,
+            rparen,
+        };
+        Done(Binder::Op(me), rest)
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseBinderError {
+    Ident(ParseIdentError),
+    Symbol(ParseOpSymbolError),
+    MissingClosingParen {
+        lparen: Span,
+        error: ParseTokenError<token::RParen>,
+    },
+}
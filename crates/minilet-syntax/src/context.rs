@@ -0,0 +1,156 @@
+use crate::diagnostics::ToDiagnostic;
+use crate::precedence::{Fixity, PrecedenceTable};
+use crate::recovery::SyntaxError;
+use crate::stmts::StmtCache;
+use crate::{Position, Stmt};
+use std::cell::{Cell, Ref, RefCell};
+use std::rc::Rc;
+
+/// Nesting depth allowed by default. Parsing grows the stack onto the heap as terms nest, so the
+/// limit bounds the memory pathological input takes rather than protecting the thread's stack.
+/// One level of source nesting such as a block or a call takes several terms, so the limit is
+/// well above what written programs reach.
+pub const DEFAULT_MAX_DEPTH: usize = 1024;
+
+/// Configuration and state shared by every parser during a single parse.
+#[derive(Debug, Clone)]
+pub struct ParseContext {
+    precedence: RefCell<PrecedenceTable>,
+    /// Errors recovered from so far, or `None` outside of [`crate::parse_recovering`].
+    recovered: Rc<RefCell<Option<Vec<SyntaxError>>>>,
+    max_depth: usize,
+    /// Terms being parsed, counting the innermost.
+    depth: Cell<usize>,
+    /// Statements parsed before, taken instead of parsing them again while recovering.
+    stmt_cache: Option<Rc<RefCell<dyn StmtCache>>>,
+}
+
+impl Default for ParseContext {
+    fn default() -> Self {
+        Self {
+            precedence: Default::default(),
+            recovered: Default::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            depth: Cell::new(0),
+            stmt_cache: None,
+        }
+    }
+}
+
+impl ParseContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits how deeply terms may nest; deeper input fails with
+    /// [`ParseTermError::TooDeep`](crate::term::ParseTermError::TooDeep).
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Enters a nested term, or returns `None` if that would exceed the depth limit. The depth
+    /// is restored when the guard is dropped.
+    pub(crate) fn enter(&self) -> Option<DepthGuard<'_>> {
+        let depth = self.depth.get();
+        if depth >= self.max_depth {
+            return None;
+        }
+        self.depth.set(depth + 1);
+        Some(DepthGuard { ctx: self })
+    }
+
+    /// Number of terms being parsed, zero between top-level statements.
+    pub(crate) fn depth(&self) -> usize {
+        self.depth.get()
+    }
+
+    pub(crate) fn with_stmt_cache(mut self, cache: Rc<RefCell<dyn StmtCache>>) -> Self {
+        self.stmt_cache = Some(cache);
+        self
+    }
+
+    /// Takes the statement at `at` from the cache, if there is one that has it.
+    pub(crate) fn cached_stmt(&self, at: &Position) -> Option<Stmt> {
+        self.stmt_cache.as_ref()?.borrow_mut().take(at, self)
+    }
+
+    pub fn with_precedence(mut self, precedence: PrecedenceTable) -> Self {
+        self.precedence = RefCell::new(precedence);
+        self
+    }
+
+    pub fn precedence(&self) -> Ref<'_, PrecedenceTable> {
+        self.precedence.borrow()
+    }
+
+    /// Makes `symbol` usable as an infix operator for the rest of the enclosing `Stmts`.
+    pub fn declare_fixity(&self, symbol: impl Into<String>, fixity: Fixity) {
+        self.precedence.borrow_mut().insert(symbol, fixity);
+    }
+
+    pub(crate) fn replace_precedence(&self, precedence: PrecedenceTable) -> PrecedenceTable {
+        self.precedence.replace(precedence)
+    }
+
+    pub fn is_recovering(&self) -> bool {
+        self.recovered.borrow().is_some()
+    }
+
+    pub(crate) fn begin_recovery(&self) {
+        *self.recovered.borrow_mut() = Some(Vec::new());
+    }
+
+    pub(crate) fn end_recovery(&self) -> Vec<SyntaxError> {
+        self.recovered.borrow_mut().take().unwrap_or_default()
+    }
+
+    /// Records an error recovered from. Only meaningful while recovering.
+    pub(crate) fn report(&self, error: SyntaxError) {
+        if let Some(errors) = self.recovered.borrow_mut().as_mut() {
+            errors.push(error);
+        }
+    }
+
+    /// Number of errors reported so far, to pass to [`ParseContext::discard_reports`].
+    pub(crate) fn reports(&self) -> usize {
+        self.recovered.borrow().as_ref().map_or(0, Vec::len)
+    }
+
+    /// Whether one of the errors reported since [`ParseContext::reports`] returned `count` starts
+    /// at `at` or after it.
+    pub(crate) fn reported_since(&self, count: usize, at: &Position) -> bool {
+        let recovered = self.recovered.borrow();
+        let Some(errors) = recovered.as_ref() else {
+            return false;
+        };
+        errors[count..].iter().any(|e| {
+            e.to_diagnostic()
+                .primary_span()
+                .is_some_and(|span| span.start().cmp_offset(at).is_ge())
+        })
+    }
+
+    /// Discards the errors reported since [`ParseContext::reports`] returned `count`. Called when
+    /// input is rewound, as the errors were found in a branch that is then parsed again or
+    /// skipped.
+    pub(crate) fn discard_reports(&self, count: usize) {
+        if let Some(errors) = self.recovered.borrow_mut().as_mut() {
+            errors.truncate(count);
+        }
+    }
+}
+
+pub(crate) struct DepthGuard<'a> {
+    ctx: &'a ParseContext,
+}
+
+impl Drop for DepthGuard<'_> {
+    fn drop(&mut self) {
+        self.ctx.depth.set(self.ctx.depth.get() - 1);
+    }
+}
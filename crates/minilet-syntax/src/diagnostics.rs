@@ -0,0 +1,986 @@
+//! Human-readable reports of parse errors.
+//!
+//! Every parse error implements [`ToDiagnostic`], which flattens the nested error enums into a
+//! single [`Diagnostic`]. A [`Renderer`] prints it together with the offending source lines.
+
+use crate::{
+    binder::ParseBinderError,
+    expected::{Expected, Found},
+    expr::ParseExprError,
+    literal::{
+        integer::ParseIntegerLiteralError, string::ParseStringLiteralError, ParseLiteralError,
+    },
+    op::{ParseOpError, ParseOpSymbolError},
+    punctured::ParsePuncturedError,
+    recovery::SyntaxError,
+    spacing::ParseSpacingError,
+    stmt::{
+        ParseBindingError, ParseFixityKeywordError, ParseStmtError, ParseStmtFixityError,
+        ParseStmtImportError, ParseStmtLetError,
+    },
+    stmts::ParseStmtsError,
+    term::{
+        block::ParseBlockError, for_loop::ParseForError, ident::ParseIdentError,
+        let_in::ParseLetInError, tuple::ParseTupleError, unary::ParseUnaryError,
+        where_clause::ParseWhereError, ParseTermError,
+    },
+    token::{ParseTokenError, Token},
+    unary_op::ParseUnaryOpError,
+    Parse, Span,
+};
+use parcom::Never;
+use std::cmp::Ordering;
+use std::fmt::{Display, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+    /// Primary labels point at the error itself, secondary ones at related code.
+    pub primary: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: true,
+        });
+        self
+    }
+
+    pub fn with_secondary_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: false,
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    /// Span of the first primary label.
+    pub fn primary_span(&self) -> Option<&Span> {
+        self.labels.iter().find(|l| l.primary).map(|l| &l.span)
+    }
+
+    fn expected(what: impl Display, span: Span) -> Self {
+        Self::error(format!("expected {what}")).with_label(span, format!("expected {what}"))
+    }
+}
+
+/// Diagnostics reported for one source text, in the order they were found.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.0.push(diagnostic);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Renders every diagnostic, whose spans refer to `source` read from `path`.
+    pub fn render(&self, renderer: &Renderer, path: &str, source: &str) -> String {
+        self.iter()
+            .map(|d| renderer.render(d, path, source))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl From<Diagnostic> for Diagnostics {
+    fn from(value: Diagnostic) -> Self {
+        Self(vec![value])
+    }
+}
+
+impl FromIterator<Diagnostic> for Diagnostics {
+    fn from_iter<T: IntoIterator<Item = Diagnostic>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// One line per diagnostic, e.g. `1:5: error: expected identifier`.
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for diagnostic in self {
+            if let Some(span) = diagnostic.primary_span() {
+                let start = span.start();
+                write!(f, "{}:{}: ", start.line + 1, start.column + 1)?;
+            }
+            let severity = diagnostic.severity.as_str();
+            writeln!(f, "{severity}: {}", diagnostic.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+pub trait ToDiagnostic {
+    fn to_diagnostic(&self) -> Diagnostic;
+}
+
+impl ToDiagnostic for Never {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match *self {}
+    }
+}
+
+impl<T: ToDiagnostic + ?Sized> ToDiagnostic for Box<T> {
+    fn to_diagnostic(&self) -> Diagnostic {
+        (**self).to_diagnostic()
+    }
+}
+
+impl<T: Token> ToDiagnostic for ParseTokenError<T>
+where
+    T::Base: Display,
+{
+    fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::expected(format_args!("`{}`", T::TOKEN), self.span.clone())
+    }
+}
+
+impl ToDiagnostic for Expected {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let label = match self.found {
+            Some(Found::Char(c)) => format!("unexpected `{}`", c.escape_debug()),
+            Some(Found::EndOfInput) => "unexpected end of input".to_string(),
+            None => format!("expected {}", self.describe()),
+        };
+        Diagnostic::error(self.to_string()).with_label(self.span.clone(), label)
+    }
+}
+
+impl ToDiagnostic for ParseSpacingError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseSpacingError::MissingSpace { span } => {
+                Diagnostic::expected("whitespace", span.clone())
+            }
+        }
+    }
+}
+
+impl ToDiagnostic for ParseIdentError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseIdentError::Missing { span } => Diagnostic::expected("identifier", span.clone()),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseIntegerLiteralError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseIntegerLiteralError::Missing(span) => {
+                Diagnostic::expected("integer literal", span.clone())
+            }
+            ParseIntegerLiteralError::InvalidDigit { span, radix } => {
+                let digit = match radix {
+                    2 => "binary digit",
+                    8 => "octal digit",
+                    16 => "hexadecimal digit",
+                    _ => "decimal digit",
+                };
+                Diagnostic::expected(digit, span.clone())
+            }
+            ParseIntegerLiteralError::OutOfRange(span) => {
+                Diagnostic::error("integer literal out of range")
+                    .with_label(span.clone(), "does not fit in a 64-bit signed integer")
+            }
+        }
+    }
+}
+
+impl ToDiagnostic for ParseStringLiteralError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseStringLiteralError::Missing(span) => {
+                Diagnostic::expected("string literal", span.clone())
+            }
+            ParseStringLiteralError::Unterminated { start, end } => {
+                Diagnostic::error("unterminated string literal")
+                    .with_label(end.clone(), "expected `\"`")
+                    .with_secondary_label(start.clone(), "string literal starts here")
+            }
+            ParseStringLiteralError::InvalidEscape { start, escape } => {
+                Diagnostic::error("invalid escape in string literal")
+                    .with_label(escape.clone(), "unknown escape")
+                    .with_secondary_label(start.clone(), "string literal starts here")
+                    .with_note("only `\\\"` and `\\\\` are escapes")
+            }
+        }
+    }
+}
+
+impl ToDiagnostic for ParseLiteralError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        // the literal that got further says what is wrong with it, as in `Expected::merge`. Both
+        // failing at the same place means neither literal starts there, unless one is malformed
+        // from its first character on.
+        let (integer, string) = (self.integer.span(), self.string.span());
+        match integer.start().cmp_offset(string.start()) {
+            Ordering::Greater => self.integer.to_diagnostic(),
+            Ordering::Less => self.string.to_diagnostic(),
+            Ordering::Equal if !self.is_malformed() => Diagnostic::expected("literal", integer),
+            Ordering::Equal if matches!(self.integer, ParseIntegerLiteralError::Missing(_)) => {
+                self.string.to_diagnostic()
+            }
+            Ordering::Equal => self.integer.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseOpSymbolError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseOpSymbolError::Missing { span } => {
+                Diagnostic::expected("operator symbol", span.clone())
+            }
+        }
+    }
+}
+
+impl ToDiagnostic for ParseOpError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseOpError::MissingLeadingSpace(span) => {
+                Diagnostic::expected("whitespace before operator", span.clone())
+            }
+            ParseOpError::MissingTrailingSpace(span) => {
+                Diagnostic::expected("whitespace after operator", span.clone())
+            }
+            ParseOpError::NoSymbol(span) => Diagnostic::expected("operator", span.clone()),
+            ParseOpError::UnknownSymbol(span) => Diagnostic::error("unknown operator")
+                .with_label(span.clone(), "operator without fixity")
+                .with_help("declare it with `infixl <precedence> <symbol>` before use"),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseUnaryOpError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseUnaryOpError::Missing { span } => {
+                Diagnostic::expected("unary operator", span.clone())
+            }
+            ParseUnaryOpError::UnknownSymbol { span } => {
+                Diagnostic::error("unknown unary operator")
+                    .with_label(span.clone(), "not a unary operator")
+                    .with_note("unary operators are `+`, `-` and `~`")
+            }
+        }
+    }
+}
+
+impl ToDiagnostic for ParseBinderError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseBinderError::Ident(e) => e.to_diagnostic(),
+            ParseBinderError::Symbol(e) => e.to_diagnostic(),
+            ParseBinderError::MissingClosingParen { lparen, error } => unclosed(lparen, error),
+        }
+    }
+}
+
+/// Missing closing delimiter, pointing back at the opening one.
+fn unclosed<T: Token>(open: &Span, error: &ParseTokenError<T>) -> Diagnostic
+where
+    T::Base: Display,
+{
+    Diagnostic::error("unclosed delimiter")
+        .with_label(error.span.clone(), format!("expected `{}`", T::TOKEN))
+        .with_secondary_label(open.clone(), "unclosed delimiter opened here")
+}
+
+impl<T: Parse, P: Parse> ToDiagnostic for ParsePuncturedError<T, P>
+where
+    T::Fatal: ToDiagnostic,
+    P::Fatal: ToDiagnostic,
+{
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParsePuncturedError::Term(e) => e.to_diagnostic(),
+            ParsePuncturedError::Punct(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseExprError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseExprError::Term(e) => e.to_diagnostic(),
+            ParseExprError::NonAssociative { first, second } => {
+                Diagnostic::error("non-associative operators cannot be chained")
+                    .with_label(second.clone(), "chained here")
+                    .with_secondary_label(first.clone(), "first operator")
+                    .with_help("use parentheses to make the grouping explicit")
+            }
+            ParseExprError::Where(e) => e.to_diagnostic(),
+            ParseExprError::Op(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseTermError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseTermError::Expected(e) => e.to_diagnostic(),
+            ParseTermError::Unary(e) => e.to_diagnostic(),
+            ParseTermError::Tuple(e) => e.to_diagnostic(),
+            ParseTermError::Literal(e) => e.to_diagnostic(),
+            ParseTermError::Block(e) => e.to_diagnostic(),
+            ParseTermError::For(e) => e.to_diagnostic(),
+            ParseTermError::Field(e) => e.to_diagnostic().with_note("field names follow `.`"),
+            ParseTermError::LetIn(e) => e.to_diagnostic(),
+            ParseTermError::TooDeep { span } => Diagnostic::error("expression nested too deeply")
+                .with_label(span.clone(), "nesting limit reached here"),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseUnaryError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseUnaryError::Op(e) => e.to_diagnostic(),
+            ParseUnaryError::Term(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseTupleError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseTupleError::MissingOpeningParen(span) => Diagnostic::expected("`(`", span.clone()),
+            ParseTupleError::MissingClosingParen { lparen, error } => unclosed(lparen, error),
+            ParseTupleError::Unexpected {
+                span,
+                expected,
+                found,
+            } => Diagnostic::error(format!("expected {expected}, found {found}"))
+                .with_label(span.clone(), format!("expected {expected}")),
+            ParseTupleError::Punct(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseBlockError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseBlockError::MissingOpeningBrace(e) => e.to_diagnostic(),
+            ParseBlockError::Stmts(e) => e.to_diagnostic(),
+            ParseBlockError::MissingClosingBrace { lbrace, error } => unclosed(lbrace, error),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseForError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseForError::For(e) => e.to_diagnostic(),
+            ParseForError::Spacing(e) => e.to_diagnostic(),
+            ParseForError::Binder(e) => e.to_diagnostic(),
+            ParseForError::In(e) => e.to_diagnostic(),
+            ParseForError::Iter(e) => e.to_diagnostic(),
+            ParseForError::Body(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseLetInError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseLetInError::Let(e) => e.to_diagnostic(),
+            ParseLetInError::In(e) => e.to_diagnostic(),
+            ParseLetInError::Body(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseWhereError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseWhereError::Where(e) => e.to_diagnostic(),
+            ParseWhereError::Bindings(e) => e.to_diagnostic(),
+            ParseWhereError::MissingBinding { span } => {
+                Diagnostic::expected("binding after `where`", span.clone())
+            }
+        }
+    }
+}
+
+impl ToDiagnostic for ParseBindingError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseBindingError::Binder(e) => e.to_diagnostic(),
+            ParseBindingError::Eq(e) => e.to_diagnostic(),
+            ParseBindingError::Expr(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseStmtLetError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseStmtLetError::Let(e) => e.to_diagnostic(),
+            ParseStmtLetError::Spacing(e) => e.to_diagnostic(),
+            ParseStmtLetError::Binding(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseFixityKeywordError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseFixityKeywordError::Missing { span } => {
+                Diagnostic::expected("`infixl`, `infixr` or `infix`", span.clone())
+            }
+        }
+    }
+}
+
+impl ToDiagnostic for ParseStmtFixityError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseStmtFixityError::Keyword(e) => e.to_diagnostic(),
+            ParseStmtFixityError::Spacing(e) => e.to_diagnostic(),
+            ParseStmtFixityError::Precedence(e) => e
+                .to_diagnostic()
+                .with_note("fixity declarations look like `infixl 6 <+>`"),
+            ParseStmtFixityError::Symbol(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseStmtImportError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseStmtImportError::Import(e) => e.to_diagnostic(),
+            ParseStmtImportError::Spacing(e) => e.to_diagnostic(),
+            ParseStmtImportError::Path(e) => e.to_diagnostic(),
+            ParseStmtImportError::As(e) => e.to_diagnostic(),
+            ParseStmtImportError::Alias(e) => e.to_diagnostic(),
+        }
+        .with_note("imports look like `import \"path\" as name`")
+    }
+}
+
+impl ToDiagnostic for ParseStmtError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseStmtError::Expr(e) => e.to_diagnostic(),
+            ParseStmtError::Let(e) => e.to_diagnostic(),
+            ParseStmtError::LetIn(e) => e.to_diagnostic(),
+            ParseStmtError::Fixity(e) => e.to_diagnostic(),
+            ParseStmtError::Import(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for ParseStmtsError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            ParseStmtsError::Punctured(e) => e.to_diagnostic(),
+        }
+    }
+}
+
+impl ToDiagnostic for SyntaxError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            SyntaxError::Stmt(e) => e.to_diagnostic(),
+            SyntaxError::Expr(e) => e.to_diagnostic(),
+            SyntaxError::Term(e) => e.to_diagnostic(),
+            SyntaxError::UnexpectedInput { span } => {
+                Diagnostic::error("unexpected input").with_label(span.clone(), "expected `;`")
+            }
+        }
+    }
+}
+
+/// Prints diagnostics with the source lines they refer to.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Renderer {
+    color: bool,
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+
+impl Renderer {
+    pub fn plain() -> Self {
+        Self { color: false }
+    }
+
+    /// Renders with ANSI colour escapes, for terminals.
+    pub fn ansi() -> Self {
+        Self { color: true }
+    }
+
+    fn paint(&self, out: &mut String, style: &str, text: impl Display) {
+        if self.color {
+            let _ = write!(out, "{style}{text}{RESET}");
+        } else {
+            let _ = write!(out, "{text}");
+        }
+    }
+
+    /// Renders `diagnostic`, whose spans refer to `source` read from `path`.
+    pub fn render(&self, diagnostic: &Diagnostic, path: &str, source: &str) -> String {
+        let mut out = String::new();
+        let severity_style = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        self.paint(&mut out, severity_style, diagnostic.severity.as_str());
+        self.paint(&mut out, BOLD, format_args!(": {}", diagnostic.message));
+        out.push('\n');
+
+        let lines: Vec<&str> = source.split('\n').collect();
+        let mut labels: Vec<&Label> = diagnostic.labels.iter().collect();
+        labels.sort_by(|a, b| a.span.start().cmp_offset(b.span.start()));
+
+        let last_line = labels.iter().map(|l| l.span.start().line + 1).max();
+        let width = last_line.unwrap_or(0).to_string().len();
+        let pad = " ".repeat(width);
+
+        if let Some(span) = diagnostic
+            .primary_span()
+            .or(labels.first().map(|l| &l.span))
+        {
+            let _ = write!(out, "{pad}");
+            self.paint(&mut out, BLUE, "-->");
+            let start = span.start();
+            let _ = writeln!(out, " {path}:{}:{}", start.line + 1, start.column + 1);
+
+            let _ = write!(out, "{pad} ");
+            self.paint(&mut out, BLUE, "|");
+            out.push('\n');
+        }
+
+        let mut previous_line: Option<usize> = None;
+        for (i, label) in labels.iter().enumerate() {
+            let line = label.span.start().line;
+            let text = lines
+                .get(line)
+                .copied()
+                .unwrap_or("")
+                .trim_end_matches('\r');
+
+            if previous_line != Some(line) {
+                if previous_line.is_some_and(|p| line > p + 1) {
+                    self.paint(&mut out, BLUE, "...");
+                    out.push('\n');
+                }
+                self.paint(&mut out, BLUE, format_args!("{:>width$} |", line + 1));
+                let _ = writeln!(out, " {text}");
+                previous_line = Some(line);
+            }
+
+            let start = label.span.start().column;
+            let end = if label.span.end().line == line {
+                label.span.end().column
+            } else {
+                text.chars().count()
+            };
+            let indent: String = text
+                .chars()
+                .chain(std::iter::repeat(' '))
+                .take(start)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let (marker, style) = if label.primary {
+                ('^', severity_style)
+            } else {
+                ('-', BLUE)
+            };
+            let markers = marker.to_string().repeat(end.saturating_sub(start).max(1));
+
+            let _ = write!(out, "{pad} ");
+            self.paint(&mut out, BLUE, "|");
+            let _ = write!(out, " {indent}");
+            if label.message.is_empty() {
+                self.paint(&mut out, style, markers);
+            } else {
+                self.paint(&mut out, style, format_args!("{markers} {}", label.message));
+            }
+            out.push('\n');
+
+            let is_last = i + 1 == labels.len();
+            if is_last && !(diagnostic.notes.is_empty() && diagnostic.help.is_empty()) {
+                let _ = write!(out, "{pad} ");
+                self.paint(&mut out, BLUE, "|");
+                out.push('\n');
+            }
+        }
+
+        for (kind, texts) in [("note", &diagnostic.notes), ("help", &diagnostic.help)] {
+            for text in texts {
+                let _ = write!(out, "{pad} ");
+                self.paint(&mut out, BLUE, "=");
+                out.push(' ');
+                self.paint(&mut out, if kind == "help" { CYAN } else { BOLD }, kind);
+                let _ = writeln!(out, ": {text}");
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Position;
+
+    fn span(line: usize, start: usize, end: usize) -> Span {
+        let position = |column| Position {
+            line,
+            column,
+            ..Default::default()
+        };
+        Span::new(position(start), position(end))
+    }
+
+    #[test]
+    fn renders_primary_label() {
+        let diagnostic =
+            Diagnostic::error("expected `)`").with_label(span(0, 5, 5), "expected `)`");
+        let rendered = Renderer::plain().render(&diagnostic, "main.ml", "f(1, 2;\n");
+
+        assert_eq!(
+            rendered,
+            "\
+error: expected `)`
+ --> main.ml:1:6
+  |
+1 | f(1, 2;
+  |      ^ expected `)`
+"
+        );
+    }
+
+    #[test]
+    fn renders_secondary_labels_notes_and_help() {
+        let diagnostic = Diagnostic::error("non-associative operators cannot be chained")
+            .with_label(span(9, 6, 7), "chained here")
+            .with_secondary_label(span(9, 2, 3), "first operator")
+            .with_note("comparisons are non-associative")
+            .with_help("use parentheses to make the grouping explicit");
+        let source = "\n".repeat(9) + "a < b < c";
+        let rendered = Renderer::plain().render(&diagnostic, "main.ml", &source);
+
+        assert_eq!(
+            rendered,
+            "\
+error: non-associative operators cannot be chained
+  --> main.ml:10:7
+   |
+10 | a < b < c
+   |   - first operator
+   |       ^ chained here
+   |
+   = note: comparisons are non-associative
+   = help: use parentheses to make the grouping explicit
+"
+        );
+    }
+
+    #[test]
+    fn renders_labels_on_separate_lines() {
+        let diagnostic = Diagnostic::error("expected `)`")
+            .with_label(span(3, 1, 1), "expected `)`")
+            .with_secondary_label(span(0, 4, 5), "unclosed delimiter");
+        let rendered = Renderer::plain().render(&diagnostic, "main.ml", "let (+ = 1;\n\n\nx");
+
+        assert_eq!(
+            rendered,
+            "\
+error: expected `)`
+ --> main.ml:4:2
+  |
+1 | let (+ = 1;
+  |     - unclosed delimiter
+...
+4 | x
+  |  ^ expected `)`
+"
+        );
+    }
+
+    #[test]
+    fn renders_ansi_colours() {
+        let diagnostic = Diagnostic::error("unexpected input").with_label(span(0, 0, 1), "");
+        let rendered = Renderer::ansi().render(&diagnostic, "main.ml", "*");
+
+        assert_eq!(
+            rendered,
+            "\x1b[1;31merror\x1b[0m\x1b[1m: unexpected input\x1b[0m\n \
+             \x1b[1;34m-->\x1b[0m main.ml:1:1\n  \
+             \x1b[1;34m|\x1b[0m\n\
+             \x1b[1;34m1 |\x1b[0m *\n  \
+             \x1b[1;34m|\x1b[0m \x1b[1;31m^\x1b[0m\n"
+        );
+    }
+
+    /// Renders the errors of parsing `source` as a program.
+    fn render_errors(source: &str) -> String {
+        let diagnostics = crate::parse_program(source).unwrap_err();
+        diagnostics.render(&Renderer::plain(), "main.ml", source)
+    }
+
+    #[test]
+    fn renders_chained_comparisons() {
+        assert_eq!(
+            render_errors("let x = a < b < c;"),
+            "\
+error: non-associative operators cannot be chained
+ --> main.ml:1:15
+  |
+1 | let x = a < b < c;
+  |           - first operator
+  |               ^ chained here
+  |
+  = help: use parentheses to make the grouping explicit
+"
+        );
+    }
+
+    #[test]
+    fn renders_tuple_errors() {
+        assert_eq!(
+            render_errors("f(1,\n  2;\nlet y = 3"),
+            "\
+error: expected `,` or `)`, found `;`
+ --> main.ml:2:4
+  |
+2 |   2;
+  |    ^ expected `,` or `)`
+"
+        );
+        assert_eq!(
+            render_errors("{ a; b"),
+            "\
+error: unclosed delimiter
+ --> main.ml:1:7
+  |
+1 | { a; b
+  | - unclosed delimiter opened here
+  |       ^ expected `}`
+"
+        );
+    }
+
+    #[test]
+    fn renders_notes_and_every_error() {
+        assert_eq!(
+            render_errors("lib.;"),
+            "\
+error: expected identifier
+ --> main.ml:1:5
+  |
+1 | lib.;
+  |     ^ expected identifier
+  |
+  = note: field names follow `.`
+"
+        );
+        assert_eq!(
+            render_errors("let = 1; g(x) h"),
+            "\
+error: expected identifier
+ --> main.ml:1:5
+  |
+1 | let = 1; g(x) h
+  |     ^ expected identifier
+
+error: unexpected input
+ --> main.ml:1:15
+  |
+1 | let = 1; g(x) h
+  |               ^ expected `;`
+"
+        );
+    }
+
+    #[test]
+    fn renders_columns_in_characters() {
+        assert_eq!(
+            render_errors("let s = \"é\" + *;"),
+            "\
+error: expected `(`, `+`, `-`, `{`, `~`, identifier or literal, found `*`
+ --> main.ml:1:15
+  |
+1 | let s = \"é\" + *;
+  |               ^ unexpected `*`
+"
+        );
+    }
+
+    #[test]
+    fn reports_the_operand_missing_after_an_operator() {
+        let expected = "expected `(`, `+`, `-`, `{`, `~`, identifier or literal";
+        for (source, position, found) in [
+            ("1 + *", "1:5", '*'),
+            ("let x = 1 + ;", "1:13", ';'),
+            ("f(1 + ;", "1:7", ';'),
+            ("f(1, 2 * );", "1:10", ')'),
+        ] {
+            let diagnostics = crate::parse_program(source).unwrap_err();
+            assert_eq!(
+                diagnostics.to_string().lines().next().unwrap(),
+                format!("{position}: error: {expected}, found `{found}`"),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn renders_unknown_operators() {
+        assert_eq!(
+            render_errors("1 <+> 2;"),
+            "\
+error: unknown operator
+ --> main.ml:1:3
+  |
+1 | 1 <+> 2;
+  |   ^^^ operator without fixity
+  |
+  = help: declare it with `infixl <precedence> <symbol>` before use
+"
+        );
+        assert!(crate::parse_program("infixl 6 <+>; 1 <+> 2; x // <+>").is_ok());
+    }
+
+    #[test]
+    fn renders_malformed_literals() {
+        assert_eq!(
+            render_errors("let s = \"abc"),
+            "\
+error: unterminated string literal
+ --> main.ml:1:13
+  |
+1 | let s = \"abc
+  |         - string literal starts here
+  |             ^ expected `\"`
+"
+        );
+        assert_eq!(
+            render_errors("let n = 0x;"),
+            "\
+error: expected hexadecimal digit
+ --> main.ml:1:11
+  |
+1 | let n = 0x;
+  |           ^ expected hexadecimal digit
+"
+        );
+        for (source, error) in [
+            ("f(0b102);", "1:7: error: expected binary digit"),
+            ("1 + 0o8;", "1:7: error: expected octal digit"),
+            (
+                "99999999999999999999;",
+                "1:1: error: integer literal out of range",
+            ),
+            (
+                "import \"a\\q\" as m;",
+                "1:10: error: invalid escape in string literal",
+            ),
+        ] {
+            let diagnostics = crate::parse_program(source).unwrap_err();
+            assert_eq!(
+                diagnostics.to_string().lines().next(),
+                Some(error),
+                "{source}"
+            );
+        }
+    }
+}
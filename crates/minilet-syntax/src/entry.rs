@@ -0,0 +1,173 @@
+//! Blocking entry points parsing a whole string.
+//!
+//! These drive the parser futures to completion internally, so callers need neither an async
+//! runtime nor a hand-built input stream.
+use crate::{
+    diagnostics::{Diagnostic, Diagnostics, ToDiagnostic},
+    recovery::parse_recovering,
+    util::any_char,
+    Expr, InputStream, Parse, ParseContext, Position, PositionMeter, Span, Stmts, Trivia,
+};
+use minilet_utils::stream::StrStream;
+use parcom::{prelude::*, IntoMeasured};
+
+/// Parses a whole program, reporting every syntax error in it.
+pub fn parse_program(source: &str) -> Result<Stmts, Diagnostics> {
+    let ctx = ParseContext::new();
+    let recovered = pollster::block_on(parse_recovering(measure(source), &ctx));
+    if recovered.is_ok() {
+        Ok(recovered.value.stmts)
+    } else {
+        Err(recovered.errors.iter().map(|e| e.to_diagnostic()).collect())
+    }
+}
+
+/// Parses a single expression, optionally surrounded by whitespace and comments.
+pub fn parse_expr(source: &str) -> Result<Expr, Diagnostics> {
+    let ctx = ParseContext::new();
+    pollster::block_on(parse_all(measure(source), &ctx)).map_err(Diagnostics::from)
+}
+
+fn measure(source: &str) -> impl InputStream + '_ {
+    IntoMeasured::<Position>::into_measured_with(StrStream::new(source), PositionMeter::default())
+}
+
+/// Parses a `T` that must span the whole input, apart from surrounding trivia.
+async fn parse_all<T, S>(input: S, ctx: &ParseContext) -> Result<T, Diagnostic>
+where
+    T: Parse,
+    T::Error: ToDiagnostic,
+    T::Fatal: ToDiagnostic,
+    S: InputStream,
+{
+    let rest = match Trivia::parse(input, ctx).await {
+        Done(_, r) => r,
+        Fail(e, _) | Fatal(e, _) => e.never(),
+    };
+
+    let (value, rest) = match T::parse(rest, ctx).await {
+        Done(v, r) => (v, r),
+        Fail(e, _) => return Err(e.to_diagnostic()),
+        Fatal(e, _) => return Err(e.to_diagnostic()),
+    };
+
+    let rest = match Trivia::parse(rest, ctx).await {
+        Done(_, r) => r,
+        Fail(e, _) | Fatal(e, _) => e.never(),
+    };
+
+    let start = rest.position();
+    match any_char().parse(rest).await {
+        Done(_, r) => {
+            let span = Span::new(start, r.position());
+            Err(Diagnostic::error("unexpected input").with_label(span, "expected end of input"))
+        }
+        Fail(_, _) => Ok(value),
+        Fatal(e, _) => e.never(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_expr, parse_program};
+    use crate::diagnostics::Renderer;
+
+    #[test]
+    fn parses_whole_inputs() {
+        let stmts = parse_program("let x = 1;\nx + 2 // sum\n").unwrap();
+        assert_eq!(stmts.stmts.iter().count(), 2);
+        assert!(parse_expr("  f(1, 2) + 3 ").is_ok());
+    }
+
+    #[test]
+    fn parses_identifiers_starting_with_keywords() {
+        let stmts = parse_program("letter; format + index; important.asx").unwrap();
+        assert_eq!(stmts.stmts.iter().count(), 3);
+    }
+
+    #[test]
+    fn parses_ranges_after_terms() {
+        for source in ["a..b", "a..=b", "f(x)..g(y)", "lib.start..lib.end"] {
+            assert!(parse_expr(source).is_ok(), "{source}");
+        }
+        assert!(parse_program("for i in 0..n { i };").is_ok());
+        assert!(parse_expr("a.").is_err());
+        assert!(parse_expr("a+b").is_err());
+    }
+
+    #[test]
+    fn allows_comments_around_in_and_where() {
+        for source in [
+            "let x = 1 // c\nin x",
+            "let x = 1 in // c\nx",
+            "y // c\nwhere y = 1",
+            "y where // c\ny = 1",
+            "let x = 1\nin x",
+        ] {
+            assert!(parse_expr(source).is_ok(), "{source:?}");
+        }
+    }
+
+    #[test]
+    fn reports_trailing_input() {
+        let diagnostics = parse_expr("1 + 2 )").unwrap_err();
+        assert_eq!(diagnostics.to_string(), "1:7: error: unexpected input\n");
+    }
+
+    #[test]
+    fn discards_errors_of_rewound_branches() {
+        let cases = [
+            (
+                "f(let x = ; 1)",
+                "1:11: error: expected `in`\n1:14: error: unexpected input\n",
+            ),
+            ("f(let x = ) + 1", "1:11: error: expected `in`\n"),
+            ("f(-) g; h", "1:6: error: unexpected input\n"),
+        ];
+        for (source, expected) in cases {
+            let diagnostics = parse_program(source).unwrap_err();
+            assert_eq!(diagnostics.to_string(), expected, "{source:?}");
+        }
+    }
+
+    #[test]
+    fn reports_unexpected_input_in_tuples() {
+        let cases = [
+            (
+                "f(1 +); g(2",
+                "1:5: error: expected `,` or `)`, found `+`\n1:12: error: unclosed delimiter\n",
+            ),
+            (
+                "a + (b c) + d; e",
+                "1:8: error: expected `,` or `)`, found `c`\n",
+            ),
+            ("f(1;", "1:4: error: expected `,` or `)`, found `;`\n"),
+            ("{ f(1 }", "1:7: error: unclosed delimiter\n"),
+        ];
+        for (source, expected) in cases {
+            let diagnostics = parse_program(source).unwrap_err();
+            assert_eq!(diagnostics.to_string(), expected, "{source:?}");
+        }
+    }
+
+    #[test]
+    fn reports_unclosed_parens_at_the_opening_paren() {
+        let diagnostics = parse_expr("f(1").unwrap_err();
+        let rendered = diagnostics.render(&Renderer::plain(), "main.ml", "f(1");
+        let expected = "\
+error: unclosed delimiter
+ --> main.ml:1:4
+  |
+1 | f(1
+  |  - unclosed delimiter opened here
+  |    ^ expected `)`
+";
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn reports_every_error_in_a_program() {
+        let diagnostics = parse_program("let = 1; f(;\nlet y = 2 }").unwrap_err();
+        assert_eq!(diagnostics.len(), 3);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{
+    term::ident::ParseIdentError,
+    token::{ParseTokenError, Token},
+    Span,
+};
+use std::fmt::Display;
+
+/// Something a parser would have accepted at a position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expectation {
+    Token(String),
+    /// A class of input such as identifiers or literals.
+    Construct(&'static str),
+}
+
+impl Display for Expectation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expectation::Token(t) => write!(f, "`{t}`"),
+            Expectation::Construct(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+/// What was found instead of the expected input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Found {
+    Char(char),
+    EndOfInput,
+}
+
+impl Display for Found {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Found::Char(c) => write!(f, "`{}`", c.escape_debug()),
+            Found::EndOfInput => write!(f, "end of input"),
+        }
+    }
+}
+
+/// Alternatives that failed at the furthest position reached by any of them.
+#[derive(Debug, Clone)]
+pub struct Expected {
+    pub span: Span,
+    pub items: Vec<Expectation>,
+    /// Input at `span`, if known.
+    pub found: Option<Found>,
+}
+
+impl Expected {
+    pub fn new(span: Span, item: Expectation) -> Self {
+        Self {
+            span,
+            items: vec![item],
+            found: None,
+        }
+    }
+
+    pub fn token<T: Token>(span: Span) -> Self
+    where
+        T::Base: Display,
+    {
+        Self::new(span, Expectation::Token(T::TOKEN.to_string()))
+    }
+
+    pub fn construct(span: Span, name: &'static str) -> Self {
+        Self::new(span, Expectation::Construct(name))
+    }
+
+    /// Keeps whichever of `self` and `other` got further, or both sets of items if they failed
+    /// at the same position.
+    pub fn merge(mut self, other: Expected) -> Self {
+        match self.span.start().cmp_offset(other.span.start()) {
+            std::cmp::Ordering::Less => other,
+            std::cmp::Ordering::Greater => self,
+            std::cmp::Ordering::Equal => {
+                for item in other.items {
+                    if !self.items.contains(&item) {
+                        self.items.push(item);
+                    }
+                }
+                self.found = self.found.or(other.found);
+                self
+            }
+        }
+    }
+
+    pub fn with_found(mut self, found: Found) -> Self {
+        self.found = Some(found);
+        self
+    }
+
+    /// Lists the expected items, tokens first, e.g. "`(`, `{`, identifier or literal".
+    pub fn describe(&self) -> String {
+        let mut items: Vec<&Expectation> = self.items.iter().collect();
+        items.sort_by(|a, b| match (a, b) {
+            (Expectation::Token(a), Expectation::Token(b)) => a.cmp(b),
+            (Expectation::Token(_), Expectation::Construct(_)) => std::cmp::Ordering::Less,
+            (Expectation::Construct(_), Expectation::Token(_)) => std::cmp::Ordering::Greater,
+            (Expectation::Construct(_), Expectation::Construct(_)) => std::cmp::Ordering::Equal,
+        });
+
+        let mut text = String::new();
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                text.push_str(if i + 1 == items.len() { " or " } else { ", " });
+            }
+            text.push_str(&item.to_string());
+        }
+        text
+    }
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {}", self.describe())?;
+        match &self.found {
+            Some(found) => write!(f, ", found {found}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: Token> From<&ParseTokenError<T>> for Expected
+where
+    T::Base: Display,
+{
+    fn from(value: &ParseTokenError<T>) -> Self {
+        Expected::token::<T>(value.span.clone())
+    }
+}
+
+impl From<&ParseIdentError> for Expected {
+    fn from(value: &ParseIdentError) -> Self {
+        match value {
+            ParseIdentError::Missing { span } => Expected::construct(span.clone(), "identifier"),
+        }
+    }
+}
@@ -1,14 +1,28 @@
+use crate::precedence::Associativity;
+use crate::recovery::ErrorNode;
+use crate::term::where_clause::{ParseWhereError, Where};
 use crate::term::ParseTermError;
-use crate::{term::Term, Parse};
-use parcom::parsers::binary_expr::BinaryExprParser;
+use crate::{term::Term, Parse, ParseContext, Span, Spanned};
 use parcom::prelude::*;
 
-use crate::op::Op;
+use crate::op::{Op, ParseOpError};
 
 #[derive(Debug)]
 pub enum Expr {
     Term(Box<Term>),
     Bin(Box<BinOp>),
+    /// Expression skipped by the recovering parser.
+    Error(ErrorNode),
+}
+
+impl Spanned for Expr {
+    fn span(&self) -> Span {
+        match self {
+            Expr::Term(v) => v.span(),
+            Expr::Bin(v) => v.span(),
+            Expr::Error(v) => v.span.clone(),
+        }
+    }
 }
 
 impl Parse for Expr {
@@ -17,24 +31,115 @@ impl Parse for Expr {
 
     async fn parse<S: crate::InputStream>(
         input: S,
-    ) -> parcom::ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let result = BinaryExprParser::new(Term::parse, Op::parse)
-            .parse(input)
-            .await;
+        ctx: &ParseContext,
+    ) -> parcom::ParseResult<S, Self, <Self as Parse>::Error, <Self as Parse>::Fatal> {
+        let (first, mut rest) = match Term::parse(input, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseExprError::Term(Box::new(e)), r),
+            Fatal(e, r) => return Fatal(ParseExprError::Term(Box::new(e)), r),
+        };
+
+        let mut operands = vec![Expr::from(first)];
+        let mut ops = Vec::new();
+        loop {
+            let anchor = rest.anchor();
+            let (op, r) = match Op::parse(rest, ctx).await {
+                Done(v, r) => (v, r),
+                // a symbol without fixity cannot end the expression either, as nothing else
+                // starts with one.
+                Fail(e @ ParseOpError::UnknownSymbol(_), r) => {
+                    return Fail(ParseExprError::Op(e), r)
+                }
+                Fail(_, r) => {
+                    rest = r.rewind(anchor);
+                    break;
+                }
+                Fatal(e, _) => e.never(),
+            };
+
+            // an operator must be followed by its right operand, so the operand's error is the
+            // furthest the expression got.
+            let (term, r) = match Term::parse(r, ctx).await {
+                Done(v, r) => (v, r),
+                Fail(e, r) => return Fail(ParseExprError::Term(Box::new(e)), r),
+                Fatal(e, r) => return Fatal(ParseExprError::Term(Box::new(e)), r),
+            };
+
+            rest = r;
+
+            while ops.last().is_some_and(|top| binds_before(top, &op)) {
+                reduce(&mut operands, &mut ops);
+            }
+            ops.push(op);
+            operands.push(Expr::from(term));
+        }
+        while !ops.is_empty() {
+            reduce(&mut operands, &mut ops);
+        }
+        let expr = operands.pop().expect("an operand is left after reducing");
+
+        if let Err((first, second)) = check_associativity(&expr) {
+            let error = ParseExprError::NonAssociative {
+                first: first.kind.span(),
+                second: second.kind.span(),
+            };
+            return Fatal(error, rest.into());
+        }
 
-        match result {
-            Done((v, _), r) => Done(v, r),
-            Fail(e, r) => Fail(ParseExprError { term: Box::new(e) }, r),
-            Fatal(e, r) => Fatal(
-                ParseExprError {
-                    term: Box::new(e.always_last()),
-                },
-                r,
-            ),
+        let anchor = rest.anchor();
+        match Where::parse_clause(expr, rest, ctx).await {
+            Done(v, r) => Done(Term::Where(Box::new(v)).into(), r),
+            Fail((expr, _), r) => Done(expr, r.rewind(anchor)),
+            Fatal(e, r) => Fatal(ParseExprError::Where(Box::new(e)), r),
         }
     }
 }
 
+/// Whether `top`, the operator before `next`, takes the operand between them. Non-associative
+/// chains are grouped to the left here and rejected by [`check_associativity`].
+fn binds_before(top: &Op, next: &Op) -> bool {
+    top.fixity.precedence > next.fixity.precedence
+        || (top.fixity.precedence == next.fixity.precedence
+            && next.fixity.associativity != Associativity::Right)
+}
+
+/// Replaces the last two operands by the last operator applied to them.
+fn reduce(operands: &mut Vec<Expr>, ops: &mut Vec<Op>) {
+    let (Some(op), Some(rhs), Some(lhs)) = (ops.pop(), operands.pop(), operands.pop()) else {
+        unreachable!("every operator has two operands");
+    };
+    operands.push(Expr::from((lhs, op, rhs)));
+}
+
+/// Rejects chains of non-associative operators of the same precedence such as `a < b < c`,
+/// returning the first two chained operators.
+fn check_associativity(expr: &Expr) -> Result<(), (&Op, &Op)> {
+    let Expr::Bin(bin) = expr else {
+        return Ok(());
+    };
+
+    let chained = |inner: &Op| {
+        inner.fixity.precedence == bin.op.fixity.precedence
+            && (inner.fixity.associativity == Associativity::None
+                || bin.op.fixity.associativity == Associativity::None)
+    };
+
+    if let Expr::Bin(lhs) = &bin.lhs {
+        if chained(&lhs.op) {
+            return Err((&lhs.op, &bin.op));
+        }
+    }
+
+    if let Expr::Bin(rhs) = &bin.rhs {
+        if chained(&rhs.op) {
+            return Err((&bin.op, &rhs.op));
+        }
+    }
+
+    check_associativity(&bin.lhs)?;
+    check_associativity(&bin.rhs)
+}
+
 impl From<(Expr, Op, Expr)> for Expr {
     fn from((lhs, op, rhs): (Expr, Op, Expr)) -> Self {
         Expr::Bin(Box::new(BinOp { lhs, op, rhs }))
@@ -54,7 +159,119 @@ pub struct BinOp {
     pub rhs: Expr,
 }
 
+impl Spanned for BinOp {
+    fn span(&self) -> Span {
+        self.lhs.span().to(&self.rhs.span())
+    }
+}
+
 #[derive(Debug)]
-pub struct ParseExprError {
-    pub term: Box<ParseTermError>,
+pub enum ParseExprError {
+    Term(Box<ParseTermError>),
+    /// Two non-associative operators of the same precedence were chained.
+    NonAssociative {
+        first: Span,
+        second: Span,
+    },
+    Where(Box<ParseWhereError>),
+    /// An operator missing from the precedence table.
+    Op(ParseOpError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Expr, ParseExprError};
+    use crate::{
+        diagnostics::ToDiagnostic,
+        op::ParseOpError,
+        precedence::{Fixity, PrecedenceTable, ADDITIVE},
+        util::grouping,
+        Parse, ParseContext, Position,
+    };
+    use minilet_utils::stream::StrStream;
+    use parcom::{prelude::*, IntoMeasured};
+
+    /// Parses `source` with `ctx`, showing the grouping of binary operators with parentheses.
+    fn group(source: &str, ctx: &ParseContext) -> Result<String, Box<ParseExprError>> {
+        let input = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            Default::default(),
+        );
+        match pollster::block_on(Expr::parse(input, ctx)) {
+            Done(expr, _) => Ok(grouping(&expr)),
+            Fail(e, _) | Fatal(e, _) => Err(Box::new(e)),
+        }
+    }
+
+    #[test]
+    fn groups_builtin_operators_by_precedence() {
+        let cases = [
+            ("1 + 2 * 3 ** 2 ** 3", "(1 + (2 * (3 ** (2 ** 3))))"),
+            ("a - b - c", "((a - b) - c)"),
+            ("a / b * c % d", "(((a / b) * c) % d)"),
+            ("a ** b * c", "((a ** b) * c)"),
+            ("x << 1 + 2 >> y", "((x << (1 + 2)) >> y)"),
+            ("a | b ^ c & d", "(a | (b ^ (c & d)))"),
+            ("a & b == c | d", "((a & b) == (c | d))"),
+            ("a < b + 1", "(a < (b + 1))"),
+            ("0 .. n - 1", "(0 .. (n - 1))"),
+        ];
+        let ctx = ParseContext::new();
+        for (source, grouped) in cases {
+            assert_eq!(group(source, &ctx).unwrap(), grouped, "{source}");
+        }
+    }
+
+    #[test]
+    fn rejects_chained_comparisons() {
+        let ctx = ParseContext::new();
+        for source in ["a < b < c", "a == b != c", "a <= b > c", "0 .. 1 .. 2"] {
+            let error = group(source, &ctx).unwrap_err();
+            assert!(
+                matches!(*error, ParseExprError::NonAssociative { .. }),
+                "{source}: {error:?}"
+            );
+            assert_eq!(
+                error.to_diagnostic().message,
+                "non-associative operators cannot be chained"
+            );
+        }
+        assert_eq!(group("(a < b) < c", &ctx).unwrap(), "((a < b) < c)");
+    }
+
+    #[test]
+    fn groups_by_a_custom_table() {
+        let table = PrecedenceTable::empty()
+            .with("+.", Fixity::left(9))
+            .with("*.", Fixity::left(1))
+            .with("^.", Fixity::right(5));
+        let ctx = ParseContext::new().with_precedence(table);
+        assert_eq!(group("1 +. 2 *. 3", &ctx).unwrap(), "((1 +. 2) *. 3)");
+        assert_eq!(group("a ^. b ^. c", &ctx).unwrap(), "(a ^. (b ^. c))");
+        // operators missing from the table are rejected.
+        let error = group("a +. b + c", &ctx).unwrap_err();
+        assert!(matches!(
+            *error,
+            ParseExprError::Op(ParseOpError::UnknownSymbol(_))
+        ));
+    }
+
+    #[test]
+    fn reports_the_chained_operators() {
+        let table = PrecedenceTable::default().with("<>", Fixity::non(ADDITIVE));
+        let ctx = ParseContext::new().with_precedence(table);
+        let columns = |source| match *group(source, &ctx).unwrap_err() {
+            ParseExprError::NonAssociative { first, second } => {
+                (first.start().column, second.start().column)
+            }
+            error => panic!("{source}: {error:?}"),
+        };
+
+        assert_eq!(columns("a < b < c"), (2, 6));
+        // non-associative operators cannot be chained with others of their level either way.
+        assert_eq!(columns("a + b <> c"), (2, 6));
+        assert_eq!(columns("a <> b - c"), (2, 7));
+        assert_eq!(columns("1 + a <> b * c"), (2, 6));
+        assert_eq!(group("a <> b * c", &ctx).unwrap(), "(a <> (b * c))");
+    }
 }
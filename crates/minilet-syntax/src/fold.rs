@@ -0,0 +1,461 @@
+//! Rebuilding syntax trees by value.
+//!
+//! Every method of [`Fold`] defaults to the matching `walk_*` function, which folds the children
+//! of the node in source order and reassembles it. Leaves are returned unchanged by default.
+use crate::{
+    binder::{Binder, OpBinder},
+    expr::BinOp,
+    literal::{IntegerLiteral, StringLiteral},
+    op::{Op, OpKind, OpSymbol},
+    punctured::Punctured,
+    recovery::ErrorNode,
+    relaxed::Relaxed,
+    stmt::{Binding, FixityKeyword, StmtFixity, StmtImport, StmtLet},
+    term::{app::App, block::Block, field::Field, for_loop::For, let_in::LetIn, Where},
+    token::{AnyToken, Token},
+    unary_op::UnaryOp,
+    Expr, Ident, Literal, Parse, SourceFile, Spacing, Stmt, Stmts, Term, Trivia, Tuple, Unary,
+};
+
+pub trait Fold {
+    fn fold_source_file(&mut self, node: SourceFile) -> SourceFile {
+        walk_source_file(self, node)
+    }
+
+    fn fold_stmts(&mut self, node: Stmts) -> Stmts {
+        walk_stmts(self, node)
+    }
+
+    fn fold_stmt(&mut self, node: Stmt) -> Stmt {
+        walk_stmt(self, node)
+    }
+
+    fn fold_stmt_let(&mut self, node: StmtLet) -> StmtLet {
+        walk_stmt_let(self, node)
+    }
+
+    fn fold_binding(&mut self, node: Binding) -> Binding {
+        walk_binding(self, node)
+    }
+
+    fn fold_stmt_fixity(&mut self, node: StmtFixity) -> StmtFixity {
+        walk_stmt_fixity(self, node)
+    }
+
+    fn fold_fixity_keyword(&mut self, node: FixityKeyword) -> FixityKeyword {
+        walk_fixity_keyword(self, node)
+    }
+
+    fn fold_stmt_import(&mut self, node: StmtImport) -> StmtImport {
+        walk_stmt_import(self, node)
+    }
+
+    fn fold_binder(&mut self, node: Binder) -> Binder {
+        walk_binder(self, node)
+    }
+
+    fn fold_op_binder(&mut self, node: OpBinder) -> OpBinder {
+        walk_op_binder(self, node)
+    }
+
+    fn fold_expr(&mut self, node: Expr) -> Expr {
+        walk_expr(self, node)
+    }
+
+    fn fold_bin_op(&mut self, node: BinOp) -> BinOp {
+        walk_bin_op(self, node)
+    }
+
+    fn fold_op(&mut self, node: Op) -> Op {
+        walk_op(self, node)
+    }
+
+    fn fold_op_kind(&mut self, node: OpKind) -> OpKind {
+        walk_op_kind(self, node)
+    }
+
+    fn fold_term(&mut self, node: Term) -> Term {
+        walk_term(self, node)
+    }
+
+    fn fold_tuple(&mut self, node: Tuple) -> Tuple {
+        walk_tuple(self, node)
+    }
+
+    fn fold_block(&mut self, node: Block) -> Block {
+        walk_block(self, node)
+    }
+
+    fn fold_unary(&mut self, node: Unary) -> Unary {
+        walk_unary(self, node)
+    }
+
+    fn fold_app(&mut self, node: App) -> App {
+        walk_app(self, node)
+    }
+
+    fn fold_for(&mut self, node: For) -> For {
+        walk_for(self, node)
+    }
+
+    fn fold_field(&mut self, node: Field) -> Field {
+        walk_field(self, node)
+    }
+
+    fn fold_let_in(&mut self, node: LetIn) -> LetIn {
+        walk_let_in(self, node)
+    }
+
+    fn fold_where(&mut self, node: Where) -> Where {
+        walk_where(self, node)
+    }
+
+    fn fold_literal(&mut self, node: Literal) -> Literal {
+        walk_literal(self, node)
+    }
+
+    fn fold_integer_literal(&mut self, node: IntegerLiteral) -> IntegerLiteral {
+        node
+    }
+
+    fn fold_string_literal(&mut self, node: StringLiteral) -> StringLiteral {
+        node
+    }
+
+    fn fold_ident(&mut self, node: Ident) -> Ident {
+        node
+    }
+
+    fn fold_op_symbol(&mut self, node: OpSymbol) -> OpSymbol {
+        node
+    }
+
+    fn fold_unary_op(&mut self, node: UnaryOp) -> UnaryOp {
+        node
+    }
+
+    fn fold_error_node(&mut self, node: ErrorNode) -> ErrorNode {
+        node
+    }
+
+    fn fold_trivia(&mut self, node: Trivia) -> Trivia {
+        node
+    }
+
+    fn fold_spacing(&mut self, node: Spacing) -> Spacing {
+        node
+    }
+
+    /// Tokens hold nothing but their span, so they are folded in place.
+    fn fold_token(&mut self, _node: &mut dyn AnyToken) {}
+}
+
+pub fn walk_source_file<F: Fold + ?Sized>(f: &mut F, node: SourceFile) -> SourceFile {
+    SourceFile {
+        leading_trivia: f.fold_trivia(node.leading_trivia),
+        stmts: f.fold_stmts(node.stmts),
+        trailing_trivia: f.fold_trivia(node.trailing_trivia),
+    }
+}
+
+pub fn walk_stmts<F: Fold + ?Sized>(f: &mut F, node: Stmts) -> Stmts {
+    Stmts {
+        stmts: fold_punctured(f, node.stmts, F::fold_stmt),
+        trailing_semi: node
+            .trailing_semi
+            .map(|(trivia, semi)| (f.fold_trivia(trivia), walk_token(f, semi))),
+        span: node.span,
+    }
+}
+
+pub fn walk_stmt<F: Fold + ?Sized>(f: &mut F, node: Stmt) -> Stmt {
+    match node {
+        Stmt::Let(n) => Stmt::Let(f.fold_stmt_let(n)),
+        Stmt::Fixity(n) => Stmt::Fixity(f.fold_stmt_fixity(n)),
+        Stmt::Import(n) => Stmt::Import(f.fold_stmt_import(n)),
+        Stmt::Expr(n) => Stmt::Expr(f.fold_expr(n)),
+        Stmt::Error(n) => Stmt::Error(f.fold_error_node(n)),
+    }
+}
+
+pub fn walk_stmt_let<F: Fold + ?Sized>(f: &mut F, node: StmtLet) -> StmtLet {
+    StmtLet {
+        let_token: walk_token(f, node.let_token),
+        let_spacing: f.fold_spacing(node.let_spacing),
+        binding: f.fold_binding(node.binding),
+    }
+}
+
+pub fn walk_binding<F: Fold + ?Sized>(f: &mut F, node: Binding) -> Binding {
+    Binding {
+        binder: f.fold_binder(node.binder),
+        binder_trivia: f.fold_trivia(node.binder_trivia),
+        eq: walk_token(f, node.eq),
+        eq_trivia: f.fold_trivia(node.eq_trivia),
+        expr: f.fold_expr(node.expr),
+    }
+}
+
+pub fn walk_stmt_fixity<F: Fold + ?Sized>(f: &mut F, node: StmtFixity) -> StmtFixity {
+    StmtFixity {
+        keyword: f.fold_fixity_keyword(node.keyword),
+        keyword_spacing: f.fold_spacing(node.keyword_spacing),
+        precedence: f.fold_integer_literal(node.precedence),
+        precedence_spacing: f.fold_spacing(node.precedence_spacing),
+        symbol: f.fold_op_symbol(node.symbol),
+    }
+}
+
+pub fn walk_fixity_keyword<F: Fold + ?Sized>(f: &mut F, node: FixityKeyword) -> FixityKeyword {
+    match node {
+        FixityKeyword::Infixl(t) => FixityKeyword::Infixl(walk_token(f, t)),
+        FixityKeyword::Infixr(t) => FixityKeyword::Infixr(walk_token(f, t)),
+        FixityKeyword::Infix(t) => FixityKeyword::Infix(walk_token(f, t)),
+    }
+}
+
+pub fn walk_stmt_import<F: Fold + ?Sized>(f: &mut F, node: StmtImport) -> StmtImport {
+    StmtImport {
+        import_token: walk_token(f, node.import_token),
+        import_spacing: f.fold_spacing(node.import_spacing),
+        path: f.fold_string_literal(node.path),
+        path_spacing: f.fold_spacing(node.path_spacing),
+        as_token: walk_token(f, node.as_token),
+        as_spacing: f.fold_spacing(node.as_spacing),
+        alias: f.fold_ident(node.alias),
+    }
+}
+
+pub fn walk_binder<F: Fold + ?Sized>(f: &mut F, node: Binder) -> Binder {
+    match node {
+        Binder::Ident(n) => Binder::Ident(f.fold_ident(n)),
+        Binder::Op(n) => Binder::Op(f.fold_op_binder(n)),
+    }
+}
+
+pub fn walk_op_binder<F: Fold + ?Sized>(f: &mut F, node: OpBinder) -> OpBinder {
+    OpBinder {
+        lparen: walk_token(f, node.lparen),
+        symbol: f.fold_op_symbol(node.symbol),
+        rparen: walk_token(f, node.rparen),
+    }
+}
+
+pub fn walk_expr<F: Fold + ?Sized>(f: &mut F, node: Expr) -> Expr {
+    match node {
+        Expr::Term(n) => Expr::Term(Box::new(f.fold_term(*n))),
+        Expr::Bin(n) => Expr::Bin(Box::new(f.fold_bin_op(*n))),
+        Expr::Error(n) => Expr::Error(f.fold_error_node(n)),
+    }
+}
+
+pub fn walk_bin_op<F: Fold + ?Sized>(f: &mut F, node: BinOp) -> BinOp {
+    BinOp {
+        lhs: f.fold_expr(node.lhs),
+        op: f.fold_op(node.op),
+        rhs: f.fold_expr(node.rhs),
+    }
+}
+
+pub fn walk_op<F: Fold + ?Sized>(f: &mut F, node: Op) -> Op {
+    Op {
+        leading_spacing: f.fold_spacing(node.leading_spacing),
+        kind: f.fold_op_kind(node.kind),
+        trailing_spacing: f.fold_spacing(node.trailing_spacing),
+        fixity: node.fixity,
+    }
+}
+
+pub fn walk_op_kind<F: Fold + ?Sized>(f: &mut F, node: OpKind) -> OpKind {
+    match node {
+        OpKind::Add(t) => OpKind::Add(walk_token(f, t)),
+        OpKind::Sub(t) => OpKind::Sub(walk_token(f, t)),
+        OpKind::Mul(t) => OpKind::Mul(walk_token(f, t)),
+        OpKind::Div(t) => OpKind::Div(walk_token(f, t)),
+        OpKind::Rem(t) => OpKind::Rem(walk_token(f, t)),
+        OpKind::Pow(t) => OpKind::Pow(walk_token(f, t)),
+        OpKind::BitAnd(t) => OpKind::BitAnd(walk_token(f, t)),
+        OpKind::BitOr(t) => OpKind::BitOr(walk_token(f, t)),
+        OpKind::BitXor(t) => OpKind::BitXor(walk_token(f, t)),
+        OpKind::Shl(t) => OpKind::Shl(walk_token(f, t)),
+        OpKind::Shr(t) => OpKind::Shr(walk_token(f, t)),
+        OpKind::Eq(t) => OpKind::Eq(walk_token(f, t)),
+        OpKind::Ne(t) => OpKind::Ne(walk_token(f, t)),
+        OpKind::Lt(t) => OpKind::Lt(walk_token(f, t)),
+        OpKind::Gt(t) => OpKind::Gt(walk_token(f, t)),
+        OpKind::Le(t) => OpKind::Le(walk_token(f, t)),
+        OpKind::Ge(t) => OpKind::Ge(walk_token(f, t)),
+        OpKind::Range(t) => OpKind::Range(walk_token(f, t)),
+        OpKind::RangeInclusive(t) => OpKind::RangeInclusive(walk_token(f, t)),
+        OpKind::Custom(s) => OpKind::Custom(f.fold_op_symbol(s)),
+    }
+}
+
+pub fn walk_term<F: Fold + ?Sized>(f: &mut F, node: Term) -> Term {
+    match node {
+        Term::Tuple(n) => Term::Tuple(f.fold_tuple(n)),
+        Term::Literal(n) => Term::Literal(f.fold_literal(n)),
+        Term::Ident(n) => Term::Ident(f.fold_ident(n)),
+        Term::Unary(n) => Term::Unary(Box::new(f.fold_unary(*n))),
+        Term::Block(n) => Term::Block(f.fold_block(n)),
+        Term::App(n) => Term::App(Box::new(f.fold_app(*n))),
+        Term::For(n) => Term::For(Box::new(f.fold_for(*n))),
+        Term::Field(n) => Term::Field(Box::new(f.fold_field(*n))),
+        Term::LetIn(n) => Term::LetIn(Box::new(f.fold_let_in(*n))),
+        Term::Where(n) => Term::Where(Box::new(f.fold_where(*n))),
+        Term::Error(n) => Term::Error(f.fold_error_node(n)),
+    }
+}
+
+pub fn walk_tuple<F: Fold + ?Sized>(f: &mut F, node: Tuple) -> Tuple {
+    Tuple {
+        lparen: walk_token(f, node.lparen),
+        ltrivia: f.fold_trivia(node.ltrivia),
+        items: fold_punctured(f, node.items, F::fold_expr),
+        rtrivia: f.fold_trivia(node.rtrivia),
+        rparen: walk_token(f, node.rparen),
+    }
+}
+
+pub fn walk_block<F: Fold + ?Sized>(f: &mut F, node: Block) -> Block {
+    Block {
+        lbrace: walk_token(f, node.lbrace),
+        ltrivia: f.fold_trivia(node.ltrivia),
+        stmts: f.fold_stmts(node.stmts),
+        rtrivia: f.fold_trivia(node.rtrivia),
+        rbrace: walk_token(f, node.rbrace),
+    }
+}
+
+pub fn walk_unary<F: Fold + ?Sized>(f: &mut F, node: Unary) -> Unary {
+    Unary {
+        op: f.fold_unary_op(node.op),
+        term: f.fold_term(node.term),
+    }
+}
+
+pub fn walk_app<F: Fold + ?Sized>(f: &mut F, node: App) -> App {
+    App {
+        receiver: f.fold_term(node.receiver),
+        arg: f.fold_tuple(node.arg),
+    }
+}
+
+pub fn walk_for<F: Fold + ?Sized>(f: &mut F, node: For) -> For {
+    For {
+        for_token: walk_token(f, node.for_token),
+        for_spacing: f.fold_spacing(node.for_spacing),
+        binder: f.fold_binder(node.binder),
+        binder_spacing: f.fold_spacing(node.binder_spacing),
+        in_token: walk_token(f, node.in_token),
+        in_spacing: f.fold_spacing(node.in_spacing),
+        iter: f.fold_expr(node.iter),
+        iter_trivia: f.fold_trivia(node.iter_trivia),
+        body: f.fold_block(node.body),
+    }
+}
+
+pub fn walk_field<F: Fold + ?Sized>(f: &mut F, node: Field) -> Field {
+    Field {
+        receiver: f.fold_term(node.receiver),
+        dot: walk_token(f, node.dot),
+        name: f.fold_ident(node.name),
+    }
+}
+
+pub fn walk_let_in<F: Fold + ?Sized>(f: &mut F, node: LetIn) -> LetIn {
+    LetIn {
+        binding: f.fold_stmt_let(node.binding),
+        in_trivia: f.fold_trivia(node.in_trivia),
+        in_token: walk_token(f, node.in_token),
+        body_trivia: f.fold_trivia(node.body_trivia),
+        body: f.fold_expr(node.body),
+    }
+}
+
+pub fn walk_where<F: Fold + ?Sized>(f: &mut F, node: Where) -> Where {
+    Where {
+        expr: f.fold_expr(node.expr),
+        where_trivia: f.fold_trivia(node.where_trivia),
+        where_token: walk_token(f, node.where_token),
+        bindings_trivia: f.fold_trivia(node.bindings_trivia),
+        bindings: fold_punctured(f, node.bindings, F::fold_binding),
+    }
+}
+
+pub fn walk_literal<F: Fold + ?Sized>(f: &mut F, node: Literal) -> Literal {
+    match node {
+        Literal::Integer(n) => Literal::Integer(f.fold_integer_literal(n)),
+        Literal::String(n) => Literal::String(f.fold_string_literal(n)),
+    }
+}
+
+fn fold_punctured<F: Fold + ?Sized, T: Parse, P: Token + Parse>(
+    f: &mut F,
+    node: Punctured<T, Relaxed<P>>,
+    mut fold_item: impl FnMut(&mut F, T) -> T,
+) -> Punctured<T, Relaxed<P>> {
+    let (first, lasts) = node.into_parts();
+    let first = first.map(|item| fold_item(f, item));
+    let lasts = lasts
+        .into_iter()
+        .map(|(punct, item)| {
+            let punct = Relaxed {
+                leading_trivia: f.fold_trivia(punct.leading_trivia),
+                item: walk_token(f, punct.item),
+                trailing_trivia: f.fold_trivia(punct.trailing_trivia),
+            };
+            (punct, fold_item(f, item))
+        })
+        .collect();
+    Punctured::from_parts(first, lasts)
+}
+
+fn walk_token<F: Fold + ?Sized, T: AnyToken>(f: &mut F, mut node: T) -> T {
+    f.fold_token(&mut node);
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fold;
+    use crate::{literal::IntegerLiteral, parse_program, token::AnyToken, ToSource};
+
+    /// Doubles integer literals written in decimal and counts the tokens it passes.
+    struct Double {
+        tokens: usize,
+    }
+
+    impl Fold for Double {
+        fn fold_integer_literal(&mut self, node: IntegerLiteral) -> IntegerLiteral {
+            if node.prefix.is_some() {
+                return node;
+            }
+            let number = node.number * 2;
+            IntegerLiteral {
+                raw_text: number.to_string(),
+                digits: number.to_string(),
+                number,
+                ..node
+            }
+        }
+
+        fn fold_token(&mut self, _node: &mut dyn AnyToken) {
+            self.tokens += 1;
+        }
+    }
+
+    #[test]
+    fn rebuilds_a_parsed_program() {
+        let stmts = parse_program("let x = (1, 0x10);\n{ x + 21 } where y = 4").unwrap();
+        let mut double = Double { tokens: 0 };
+        let folder: &mut dyn Fold = &mut double;
+        let stmts = folder.fold_stmts(stmts);
+
+        assert_eq!(
+            stmts.to_source(),
+            "let x = (2, 0x10);\n{ x + 42 } where y = 8"
+        );
+        assert_eq!(double.tokens, 11);
+    }
+}
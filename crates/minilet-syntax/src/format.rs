@@ -0,0 +1,518 @@
+//! Opinionated source formatting.
+//!
+//! The formatter reprints a parsed tree with normalised layout: one statement per line, blocks
+//! indented, single spaces around operators and after keywords, and tuple or argument lists
+//! broken one item per line when they do not fit in the width budget. Comments are kept where
+//! they were, as trailing comments or on lines of their own. Formatting formatted output gives
+//! the same output again.
+use crate::{
+    binder::Binder,
+    op::Op,
+    relaxed::Relaxed,
+    stmt::{Binding, FixityKeyword, StmtFixity, StmtImport, StmtLet},
+    term::{block::Block, for_loop::For, let_in::LetIn, Where},
+    token,
+    unary_op::UnaryOp,
+    Expr, Literal, SourceFile, Stmt, Stmts, Term, ToSource, Trivia, Tuple,
+};
+
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// Line width that lists are kept within where possible.
+    pub width: usize,
+    /// Spaces per indentation level.
+    pub indent: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            indent: 4,
+        }
+    }
+}
+
+pub fn format(file: &SourceFile, options: &FormatOptions) -> String {
+    let mut f = Formatter::new(options, 0, false);
+    f.stmts(&file.leading_trivia, &file.stmts, &file.trailing_trivia);
+
+    let mut out = f.out;
+    out.truncate(out.trim_end().len());
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// A line comment and the number of line breaks between it and whatever precedes it.
+struct Comment<'t> {
+    text: &'t str,
+    newlines_before: usize,
+}
+
+/// Splits trivia into its comments and the number of line breaks after the last of them.
+fn comments(trivia: &Trivia) -> (Vec<Comment<'_>>, usize) {
+    let mut comments = Vec::new();
+    let mut newlines = 0;
+    for (i, line) in trivia.text.split('\n').enumerate() {
+        if i > 0 {
+            newlines += 1;
+        }
+        if let Some(at) = line.find("//") {
+            comments.push(Comment {
+                text: &line[at..],
+                newlines_before: newlines,
+            });
+            newlines = 0;
+        }
+    }
+    (comments, newlines)
+}
+
+fn has_comments(trivia: &Trivia) -> bool {
+    trivia.text.contains("//")
+}
+
+struct Formatter<'a> {
+    options: &'a FormatOptions,
+    out: String,
+    level: usize,
+    /// Set while measuring; lists are then only broken when they contain comments.
+    flat: bool,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(options: &'a FormatOptions, level: usize, flat: bool) -> Self {
+        Self {
+            options,
+            out: String::new(),
+            level,
+            flat,
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    fn current_line(&self) -> &str {
+        match self.out.rfind('\n') {
+            Some(i) => &self.out[i + 1..],
+            None => &self.out,
+        }
+    }
+
+    fn column(&self) -> usize {
+        self.current_line().chars().count()
+    }
+
+    /// Starts a new line at the current indentation, leaving an empty line before it if `blank`.
+    fn line_break(&mut self, blank: bool) {
+        self.out.truncate(self.out.trim_end_matches(' ').len());
+        if !self.out.is_empty() {
+            let wanted = if blank { 2 } else { 1 };
+            let present = self.out.len() - self.out.trim_end_matches('\n').len();
+            for _ in present..wanted {
+                self.out.push('\n');
+            }
+        }
+        let indent = self.level * self.options.indent;
+        self.out.extend(std::iter::repeat_n(' ', indent));
+    }
+
+    fn continuation_break(&mut self) {
+        self.level += 1;
+        self.line_break(false);
+        self.level -= 1;
+    }
+
+    /// Writes the comments of trivia between statements or list items. Comments on the line of
+    /// the preceding item stay there; the others go on their own lines. Returns whether the next
+    /// item should be preceded by an empty line.
+    fn comment_lines(&mut self, trivia: &Trivia, allow_blank: bool) -> bool {
+        let (comments, newlines_after) = comments(trivia);
+        let any = !comments.is_empty();
+        for (i, comment) in comments.into_iter().enumerate() {
+            let trailing = comment.newlines_before == 0 && !self.current_line().trim().is_empty();
+            if trailing {
+                self.write(" ");
+            } else {
+                self.line_break((allow_blank || i > 0) && comment.newlines_before >= 2);
+            }
+            self.write(comment.text);
+        }
+        (allow_blank || any) && newlines_after >= 2
+    }
+
+    /// Writes trivia inside a line as a single space, or as its comments followed by a
+    /// continuation line.
+    fn gap(&mut self, trivia: &Trivia) {
+        if !has_comments(trivia) {
+            self.write(" ");
+            return;
+        }
+        self.tight(trivia);
+    }
+
+    /// Writes trivia inside a line as nothing, or as its comments followed by a continuation
+    /// line.
+    fn tight(&mut self, trivia: &Trivia) {
+        let (comments, _) = comments(trivia);
+        if comments.is_empty() {
+            return;
+        }
+        for comment in comments {
+            if !self.current_line().trim().is_empty() {
+                self.write(" ");
+            }
+            self.write(comment.text);
+            self.continuation_break();
+        }
+    }
+
+    fn stmts(&mut self, leading: &Trivia, stmts: &Stmts, closing: &Trivia) {
+        let (first, lasts) = stmts.stmts.parts();
+        let blank = self.comment_lines(leading, false);
+        if let Some(first) = first {
+            self.line_break(blank);
+            self.stmt(first);
+        }
+        for (sep, stmt) in lasts {
+            self.write(";");
+            self.comment_lines(&sep.leading_trivia, true);
+            let blank = self.comment_lines(&sep.trailing_trivia, true);
+            self.line_break(blank);
+            self.stmt(stmt);
+        }
+        if let Some((trivia, _)) = &stmts.trailing_semi {
+            self.write(";");
+            self.comment_lines(trivia, true);
+        }
+        self.comment_lines(closing, true);
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Let(v) => self.stmt_let(v),
+            Stmt::Fixity(v) => self.stmt_fixity(v),
+            Stmt::Import(v) => self.stmt_import(v),
+            Stmt::Expr(v) => self.expr(v),
+            Stmt::Error(v) => self.write(&v.text),
+        }
+    }
+
+    fn stmt_let(&mut self, stmt: &StmtLet) {
+        self.write("let ");
+        self.binding(&stmt.binding);
+    }
+
+    fn binding(&mut self, binding: &Binding) {
+        self.binder(&binding.binder);
+        self.gap(&binding.binder_trivia);
+        self.write("=");
+        self.gap(&binding.eq_trivia);
+        self.expr(&binding.expr);
+    }
+
+    fn binder(&mut self, binder: &Binder) {
+        match binder {
+            Binder::Ident(v) => self.write(&v.text),
+            Binder::Op(v) => {
+                self.write("(");
+                self.write(&v.symbol.text);
+                self.write(")");
+            }
+        }
+    }
+
+    fn stmt_fixity(&mut self, stmt: &StmtFixity) {
+        self.write(match stmt.keyword {
+            FixityKeyword::Infixl(_) => "infixl ",
+            FixityKeyword::Infixr(_) => "infixr ",
+            FixityKeyword::Infix(_) => "infix ",
+        });
+        self.write(&stmt.precedence.raw_text);
+        self.write(" ");
+        self.write(&stmt.symbol.text);
+    }
+
+    fn stmt_import(&mut self, stmt: &StmtImport) {
+        self.write("import ");
+        self.write(&stmt.path.raw_text);
+        self.write(" as ");
+        self.write(&stmt.alias.text);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Term(v) => self.term(v),
+            Expr::Bin(v) => {
+                self.expr(&v.lhs);
+                self.op(&v.op);
+                self.expr(&v.rhs);
+            }
+            Expr::Error(v) => self.write(&v.text),
+        }
+    }
+
+    fn op(&mut self, op: &Op) {
+        self.write(" ");
+        self.write(&op.kind.to_source());
+        self.write(" ");
+    }
+
+    fn term(&mut self, term: &Term) {
+        match term {
+            Term::Tuple(v) => self.tuple(v),
+            Term::Literal(Literal::Integer(v)) => self.write(&v.raw_text),
+            Term::Literal(Literal::String(v)) => self.write(&v.raw_text),
+            Term::Ident(v) => self.write(&v.text),
+            Term::Unary(v) => {
+                self.write(match v.op {
+                    UnaryOp::Plus { .. } => "+",
+                    UnaryOp::Sub { .. } => "-",
+                    UnaryOp::BitNot { .. } => "~",
+                });
+                self.term(&v.term);
+            }
+            Term::Block(v) => self.block(v),
+            Term::App(v) => {
+                self.term(&v.receiver);
+                self.tuple(&v.arg);
+            }
+            Term::For(v) => self.for_loop(v),
+            Term::Field(v) => {
+                self.term(&v.receiver);
+                self.write(".");
+                self.write(&v.name.text);
+            }
+            Term::LetIn(v) => self.let_in(v),
+            Term::Where(v) => self.where_clause(v),
+            Term::Error(v) => self.write(&v.text),
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        self.write("{");
+        let empty = block.stmts.stmts.first().is_none()
+            && block.stmts.trailing_semi.is_none()
+            && !has_comments(&block.ltrivia)
+            && !has_comments(&block.rtrivia);
+        if !empty {
+            self.level += 1;
+            self.stmts(&block.ltrivia, &block.stmts, &block.rtrivia);
+            self.level -= 1;
+            self.line_break(false);
+        }
+        self.write("}");
+    }
+
+    fn tuple(&mut self, tuple: &Tuple) {
+        let (first, lasts) = tuple.items.parts();
+        let Some(first) = first else {
+            self.write("(");
+            self.tight(&tuple.ltrivia);
+            self.tight(&tuple.rtrivia);
+            self.write(")");
+            return;
+        };
+
+        let commented = has_comments(&tuple.ltrivia)
+            || has_comments(&tuple.rtrivia)
+            || lasts.iter().any(|(sep, _)| {
+                has_comments(&sep.leading_trivia) || has_comments(&sep.trailing_trivia)
+            });
+        if !commented && (self.flat || self.fits(|f| f.tuple_inline(tuple))) {
+            self.tuple_inline(tuple);
+            return;
+        }
+
+        self.write("(");
+        self.level += 1;
+        let blank = self.comment_lines(&tuple.ltrivia, false);
+        self.line_break(blank);
+        self.expr(first);
+        for (sep, item) in lasts {
+            self.write(",");
+            self.comment_lines(&sep.leading_trivia, true);
+            let blank = self.comment_lines(&sep.trailing_trivia, true);
+            self.line_break(blank);
+            self.expr(item);
+        }
+        self.comment_lines(&tuple.rtrivia, true);
+        self.level -= 1;
+        self.line_break(false);
+        self.write(")");
+    }
+
+    fn tuple_inline(&mut self, tuple: &Tuple) {
+        self.write("(");
+        for (i, item) in tuple.items.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.expr(item);
+        }
+        self.write(")");
+    }
+
+    /// Whether the first line of what `print` writes fits on the current line.
+    fn fits(&self, print: impl FnOnce(&mut Formatter)) -> bool {
+        let mut f = Formatter::new(self.options, self.level, true);
+        print(&mut f);
+        let first_line = f.out.lines().next().unwrap_or("");
+        self.column() + first_line.chars().count() <= self.options.width
+    }
+
+    fn for_loop(&mut self, for_loop: &For) {
+        self.write("for ");
+        self.binder(&for_loop.binder);
+        self.write(" in ");
+        self.expr(&for_loop.iter);
+        self.gap(&for_loop.iter_trivia);
+        self.block(&for_loop.body);
+    }
+
+    fn let_in(&mut self, let_in: &LetIn) {
+        self.stmt_let(&let_in.binding);
+        self.gap(&let_in.in_trivia);
+        self.write("in");
+        self.gap(&let_in.body_trivia);
+        self.expr(&let_in.body);
+    }
+
+    fn where_clause(&mut self, clause: &Where) {
+        self.expr(&clause.expr);
+        self.gap(&clause.where_trivia);
+        self.write("where");
+        self.gap(&clause.bindings_trivia);
+        let (first, lasts) = clause.bindings.parts();
+        if let Some(first) = first {
+            self.binding(first);
+        }
+        for (sep, binding) in lasts {
+            self.separator(sep);
+            self.binding(binding);
+        }
+    }
+
+    fn separator(&mut self, sep: &Relaxed<token::Comma>) {
+        self.tight(&sep.leading_trivia);
+        self.write(",");
+        self.gap(&sep.trailing_trivia);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, FormatOptions};
+    use crate::{parse_recovering, ParseContext, Position};
+    use minilet_utils::stream::StrStream;
+    use parcom::IntoMeasured;
+
+    fn format_source(source: &str, options: &FormatOptions) -> String {
+        let ctx = ParseContext::new();
+        let input = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            Default::default(),
+        );
+        let recovered = pollster::block_on(parse_recovering(input, &ctx));
+        assert!(recovered.is_ok(), "{source:?} has syntax errors");
+        format(&recovered.value, options)
+    }
+
+    #[test]
+    fn normalises_layout() {
+        let source = "let x=1;  let y =  x  +\n  2 ;{a;{ b ; c}};for  i in 0 ..  10{print(i)}";
+        let expected = "\
+let x = 1;
+let y = x + 2;
+{
+    a;
+    {
+        b;
+        c
+    }
+};
+for i in 0 .. 10 {
+    print(i)
+}
+";
+        assert_eq!(format_source(source, &FormatOptions::default()), expected);
+    }
+
+    #[test]
+    fn breaks_long_argument_lists() {
+        let options = FormatOptions {
+            width: 20,
+            indent: 2,
+        };
+        let source = "f(alpha, beta, (gamma, delta), epsilon); g(a, b)";
+        let expected = "\
+f(
+  alpha,
+  beta,
+  (gamma, delta),
+  epsilon
+);
+g(a, b)
+";
+        assert_eq!(format_source(source, &options), expected);
+    }
+
+    #[test]
+    fn keeps_comments() {
+        let source = "// header\n\nlet x = 1; // one\n// before y\nlet y = f(x, // first\n  2);\n{ a // last\n}";
+        let expected = "\
+// header
+
+let x = 1; // one
+// before y
+let y = f(
+    x, // first
+    2
+);
+{
+    a // last
+}
+";
+        assert_eq!(format_source(source, &FormatOptions::default()), expected);
+    }
+
+    #[test]
+    fn keeps_comments_around_in_and_where() {
+        let source = "let x = 1 // one\nin x; y where // y\ny = 2";
+        let expected = "\
+let x = 1 // one
+    in x;
+y where // y
+    y = 2
+";
+        assert_eq!(format_source(source, &FormatOptions::default()), expected);
+    }
+
+    #[test]
+    fn is_idempotent() {
+        let samples = [
+            "",
+            "  let x = 0x1F + 07;\n  x ",
+            "infixl 6 <+>; let (<+>) = add; 1 <+> 2",
+            "import \"lib.ml\" as lib; lib.f(1, (2 , 3))",
+            "for i in 0 .. 10 { print(i); };",
+            "let y = a * b where a = 1, // a\n b = 2 ; let z = 1 in z",
+            "{ a;\n\n\n b; { c // c\n ; d }; }",
+            "let x = // why\n 1;\n\n// trailing\n",
+            "f(g(aaaaaaaaaa, bbbbbbbbbb, cccccccccc), h(dddddddddd, eeeeeeeeee, ffffffffff, gggg))",
+            "{ let r = f(x)(y).z; -r }; ()",
+            "( // open\n a, b // b\n )",
+            "let x = 1 // one\nin x where // x\n x = 2",
+        ];
+        let options = FormatOptions::default();
+        for sample in samples {
+            let once = format_source(sample, &options);
+            let twice = format_source(&once, &options);
+            assert_eq!(once, twice, "formatting {sample:?} is not idempotent");
+        }
+    }
+}
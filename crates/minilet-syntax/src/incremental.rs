@@ -0,0 +1,608 @@
+//! Incremental reparsing for editors.
+//!
+//! A [`Document`] keeps a source text together with its recovered syntax tree. When the text is
+//! edited, statements the edit cannot have changed are moved from the old tree into the new one
+//! instead of being parsed again, at top level and within the blocks around the edit. The tree
+//! and errors after an edit are the same as those of a fresh [`crate::parse_recovering`].
+use crate::{
+    literal::{IntegerLiteral, StringLiteral},
+    op::OpSymbol,
+    precedence::PrecedenceTable,
+    punctured::Punctured,
+    recovery::{parse_recovering, ErrorNode},
+    spacing::Spacing,
+    stmt::Stmt,
+    stmts::StmtCache,
+    term::block::Block,
+    token::AnyToken,
+    unary_op::UnaryOp,
+    visit::Visit,
+    visit_mut::{self, VisitMut},
+    FileId, Ident, ParseContext, Position, PositionMeter, Recovered, SourceFile, Span, Spanned,
+    Stmts, SyntaxError, Trivia,
+};
+use minilet_utils::stream::StrStream;
+use parcom::IntoMeasured;
+use std::{cell::RefCell, collections::BTreeMap, iter::Peekable, ops::Range, rc::Rc, vec};
+
+/// Replacement of the bytes in `range` by `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, text: impl Into<String>) -> Self {
+        Self {
+            range,
+            text: text.into(),
+        }
+    }
+
+    pub fn insert(at: usize, text: impl Into<String>) -> Self {
+        Self::new(at..at, text)
+    }
+
+    pub fn delete(range: Range<usize>) -> Self {
+        Self::new(range, "")
+    }
+
+    /// Panics if the range is out of bounds or does not lie on `char` boundaries.
+    pub fn apply(&self, text: &mut String) {
+        text.replace_range(self.range.clone(), &self.text);
+    }
+}
+
+/// A source file kept parsed across edits.
+#[derive(Debug)]
+pub struct Document {
+    file: FileId,
+    text: String,
+    ctx: ParseContext,
+    tree: Recovered<SourceFile>,
+    /// Context of every statement of the tree, by its byte offset.
+    before: BTreeMap<usize, Before>,
+    /// Statements the last edit took from the old tree.
+    reused: usize,
+}
+
+impl Document {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self::with_context(FileId::default(), text, ParseContext::new())
+    }
+
+    /// Parses `text` as `file`, starting from the precedence of `ctx`.
+    pub fn with_context(file: FileId, text: impl Into<String>, ctx: ParseContext) -> Self {
+        let text = text.into();
+        let (tree, reuse) = parse(file, &text, &ctx, Reuse::default());
+        Self {
+            file,
+            text,
+            ctx,
+            tree,
+            before: reuse.before,
+            reused: 0,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn tree(&self) -> &SourceFile {
+        &self.tree.value
+    }
+
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.tree.errors
+    }
+
+    pub fn into_tree(self) -> Recovered<SourceFile> {
+        self.tree
+    }
+
+    /// Number of statements, at any depth, that the last edit moved from the old tree instead of
+    /// parsing them again.
+    pub fn reused_stmts(&self) -> usize {
+        self.reused
+    }
+
+    /// Applies `edit` to the text and reparses it, reusing the statements it leaves untouched.
+    ///
+    /// Panics if the range of `edit` is out of bounds or does not lie on `char` boundaries.
+    pub fn edit(&mut self, edit: &TextEdit) {
+        let old_end = position_at(self.file, &self.text, edit.range.end);
+        edit.apply(&mut self.text);
+        let new_end = position_at(self.file, &self.text, edit.range.start + edit.text.len());
+
+        let reuse = Reuse::new(
+            &mut self.tree.value.stmts,
+            std::mem::take(&mut self.before),
+            edit.range.start,
+            Shift {
+                old: old_end,
+                new: new_end,
+            },
+        );
+        let (tree, reuse) = parse(self.file, &self.text, &self.ctx, reuse);
+        self.tree = tree;
+        self.before = reuse.before;
+        self.reused = reuse.reused;
+    }
+}
+
+fn parse(
+    file: FileId,
+    text: &str,
+    ctx: &ParseContext,
+    reuse: Reuse,
+) -> (Recovered<SourceFile>, Reuse) {
+    let input = IntoMeasured::<Position>::into_measured_with(
+        StrStream::new(text),
+        PositionMeter::new(file),
+    );
+    let reuse = Rc::new(RefCell::new(reuse));
+    let ctx = ctx.clone().with_stmt_cache(reuse.clone());
+    let tree = pollster::block_on(parse_recovering(input, &ctx));
+    (tree, reuse.take())
+}
+
+fn position_at(file: FileId, text: &str, byte: usize) -> Position {
+    let before = &text[..byte];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        file,
+        byte,
+        char: before.chars().count(),
+        line: before.matches('\n').count(),
+        column: before[line_start..].chars().count(),
+        utf16_column: before[line_start..].encode_utf16().count(),
+    }
+}
+
+/// State of the parser at the start of a statement, which parsing the statement depends on.
+#[derive(Debug, Clone)]
+struct Before {
+    precedence: Rc<PrecedenceTable>,
+    /// Terms being parsed, zero for top-level statements.
+    depth: usize,
+}
+
+/// A statement of the old tree that may reappear in the new one.
+#[derive(Debug)]
+struct Candidate {
+    stmt: Stmt,
+    /// Byte offset of the statement in the new text.
+    start: usize,
+    /// Whether the statement follows the edit and has to be moved.
+    shifted: bool,
+    before: Before,
+    /// Context of the statements nested in it, by their byte offset in the old text.
+    nested: BTreeMap<usize, Before>,
+}
+
+/// Hands out statements of the old tree to the parser of the edited text.
+///
+/// A statement depends on its own text, the `;` after it, or the rest of the input if there is
+/// none, and on the precedence and depth it is parsed at. It is reused when the edit touches
+/// none of these and the parser arrives exactly at its start. A statement the edit does touch
+/// offers the statements of the blocks within it instead.
+#[derive(Debug)]
+struct Reuse {
+    candidates: Peekable<vec::IntoIter<Candidate>>,
+    shift: Shift,
+    /// Context of every statement of the new tree, by its byte offset.
+    before: BTreeMap<usize, Before>,
+    /// Precedence recorded last, shared with the statements after it while it is unchanged.
+    precedence: Option<Rc<PrecedenceTable>>,
+    /// Statements handed out.
+    reused: usize,
+}
+
+impl Default for Reuse {
+    fn default() -> Self {
+        Self {
+            candidates: Vec::new().into_iter().peekable(),
+            shift: Shift {
+                old: Position::default(),
+                new: Position::default(),
+            },
+            before: BTreeMap::new(),
+            precedence: None,
+            reused: 0,
+        }
+    }
+}
+
+impl Reuse {
+    /// Takes the reusable statements out of `old`, which is left empty.
+    fn new(
+        old: &mut Stmts,
+        before: BTreeMap<usize, Before>,
+        edit_start: usize,
+        shift: Shift,
+    ) -> Self {
+        let mut collect = Collect {
+            before,
+            edit_start,
+            shift,
+            candidates: Vec::new(),
+        };
+        collect.stmts(old);
+
+        Self {
+            candidates: collect.candidates.into_iter().peekable(),
+            shift: collect.shift,
+            before: BTreeMap::new(),
+            precedence: None,
+            reused: 0,
+        }
+    }
+
+    fn record(&mut self, at: &Position, ctx: &ParseContext) -> Before {
+        let current = ctx.precedence();
+        let precedence = match &self.precedence {
+            Some(last) if **last == *current => last.clone(),
+            _ => Rc::new(current.clone()),
+        };
+        self.precedence = Some(precedence.clone());
+        let before = Before {
+            precedence,
+            depth: ctx.depth(),
+        };
+        self.before.insert(at.byte, before.clone());
+        before
+    }
+}
+
+impl StmtCache for Reuse {
+    fn take(&mut self, at: &Position, ctx: &ParseContext) -> Option<Stmt> {
+        let before = self.record(at, ctx);
+        while self.candidates.next_if(|c| c.start < at.byte).is_some() {}
+
+        let mut candidate = self.candidates.next_if(|c| {
+            c.start == at.byte
+                && c.before.depth == before.depth
+                && *c.before.precedence == *before.precedence
+        })?;
+        for (byte, before) in candidate.nested {
+            let byte = if candidate.shifted {
+                self.shift.byte(byte)
+            } else {
+                byte
+            };
+            self.before.insert(byte, before);
+        }
+        if candidate.shifted {
+            self.shift.visit_stmt_mut(&mut candidate.stmt);
+        }
+        if let Stmt::Fixity(fixity) = &candidate.stmt {
+            ctx.declare_fixity(fixity.symbol.text.clone(), fixity.fixity());
+        }
+        self.reused += 1;
+        Some(candidate.stmt)
+    }
+}
+
+/// Gathers the candidates of [`Reuse`] in source order.
+struct Collect {
+    before: BTreeMap<usize, Before>,
+    edit_start: usize,
+    shift: Shift,
+    candidates: Vec<Candidate>,
+}
+
+impl Collect {
+    fn stmts(&mut self, old: &mut Stmts) {
+        let trailing_semi_end = old
+            .trailing_semi
+            .as_ref()
+            .map(|(_, semi)| semi.span.end().byte);
+        let stmts = std::mem::replace(&mut old.stmts, Punctured::from_parts(None, Vec::new()));
+        let (first, lasts) = stmts.into_parts();
+
+        // pair every statement with the end of the `;` following it.
+        let mut stmts = Vec::new();
+        let mut current = first;
+        for (semi, stmt) in lasts {
+            if let Some(prev) = current.replace(stmt) {
+                stmts.push((prev, Some(semi.item.span.end().byte)));
+            }
+        }
+        stmts.extend(current.map(|stmt| (stmt, trailing_semi_end)));
+
+        for (stmt, depends_until) in stmts {
+            self.stmt(stmt, depends_until);
+        }
+    }
+
+    fn stmt(&mut self, mut stmt: Stmt, depends_until: Option<usize>) {
+        let start = stmt.span().start().byte;
+        let shifted = if depends_until.is_some_and(|end| end <= self.edit_start) {
+            Some(false)
+        } else if start >= self.shift.old.byte {
+            Some(true)
+        } else {
+            None
+        };
+
+        match (shifted, self.before.remove(&start)) {
+            (Some(shifted), Some(before)) if !has_errors(&stmt) => {
+                let mut nested = self.before.split_off(&start);
+                let mut after = nested.split_off(&stmt.span().end().byte);
+                self.before.append(&mut after);
+                self.candidates.push(Candidate {
+                    start: if shifted {
+                        self.shift.byte(start)
+                    } else {
+                        start
+                    },
+                    stmt,
+                    shifted,
+                    before,
+                    nested,
+                });
+            }
+            _ => self.visit_stmt_mut(&mut stmt),
+        }
+    }
+}
+
+impl VisitMut for Collect {
+    fn visit_block_mut(&mut self, node: &mut Block) {
+        self.stmts(&mut node.stmts);
+    }
+}
+
+fn has_errors(stmt: &Stmt) -> bool {
+    struct Errors(bool);
+
+    impl Visit for Errors {
+        fn visit_error_node(&mut self, _node: &ErrorNode) {
+            self.0 = true;
+        }
+    }
+
+    let mut errors = Errors(false);
+    errors.visit_stmt(stmt);
+    errors.0
+}
+
+/// Moves positions after the end of an edit to where they are in the edited text.
+#[derive(Debug)]
+struct Shift {
+    /// End of the edit in the old text.
+    old: Position,
+    /// End of the edit in the new text.
+    new: Position,
+}
+
+impl Shift {
+    fn byte(&self, byte: usize) -> usize {
+        byte - self.old.byte + self.new.byte
+    }
+
+    fn position(&self, position: &mut Position) {
+        if position.line == self.old.line {
+            position.column = position.column - self.old.column + self.new.column;
+            position.utf16_column =
+                position.utf16_column - self.old.utf16_column + self.new.utf16_column;
+        }
+        position.line = position.line - self.old.line + self.new.line;
+        position.char = position.char - self.old.char + self.new.char;
+        position.byte = self.byte(position.byte);
+    }
+
+    fn span(&self, span: &mut Span) {
+        let mut start = span.start().clone();
+        let mut end = span.end().clone();
+        self.position(&mut start);
+        self.position(&mut end);
+        *span = Span::new(start, end);
+    }
+}
+
+impl VisitMut for Shift {
+    fn visit_stmts_mut(&mut self, node: &mut Stmts) {
+        self.span(&mut node.span);
+        visit_mut::walk_stmts_mut(self, node);
+    }
+
+    fn visit_integer_literal_mut(&mut self, node: &mut IntegerLiteral) {
+        self.span(&mut node.span);
+    }
+
+    fn visit_string_literal_mut(&mut self, node: &mut StringLiteral) {
+        self.span(&mut node.span);
+    }
+
+    fn visit_ident_mut(&mut self, node: &mut Ident) {
+        self.span(&mut node.span);
+    }
+
+    fn visit_op_symbol_mut(&mut self, node: &mut OpSymbol) {
+        self.span(&mut node.span);
+    }
+
+    fn visit_unary_op_mut(&mut self, node: &mut UnaryOp) {
+        match node {
+            UnaryOp::Plus { span } | UnaryOp::Sub { span } | UnaryOp::BitNot { span } => {
+                self.span(span)
+            }
+        }
+    }
+
+    fn visit_error_node_mut(&mut self, node: &mut ErrorNode) {
+        self.span(&mut node.span);
+    }
+
+    fn visit_trivia_mut(&mut self, node: &mut Trivia) {
+        self.span(&mut node.span);
+    }
+
+    fn visit_spacing_mut(&mut self, node: &mut Spacing) {
+        self.span(&mut node.span);
+    }
+
+    fn visit_token_mut(&mut self, node: &mut dyn AnyToken) {
+        self.span(node.span_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Document, TextEdit};
+    use crate::{parse_recovering, util::Rng, Expr, FileId, ParseContext, Position, Stmt, Term};
+    use minilet_utils::stream::StrStream;
+    use parcom::IntoMeasured;
+
+    fn check(document: &Document) {
+        let ctx = document.ctx.clone();
+        let input = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(document.text()),
+            Default::default(),
+        );
+        let fresh = pollster::block_on(parse_recovering(input, &ctx));
+        assert_eq!(
+            format!("{:?}", document.tree()),
+            format!("{:?}", fresh.value),
+            "{:?}",
+            document.text()
+        );
+        assert_eq!(
+            format!("{:?}", document.errors()),
+            format!("{:?}", fresh.errors),
+            "{:?}",
+            document.text()
+        );
+    }
+
+    fn term_at(document: &Document, index: usize) -> *const Term {
+        match document.tree().stmts.stmts.iter().nth(index) {
+            Some(Stmt::Expr(Expr::Term(term))) => &**term,
+            _ => panic!("expected a term statement"),
+        }
+    }
+
+    #[test]
+    fn reuses_statements_around_the_edit() {
+        let mut document = Document::new("{ a; b };\nlet x = 1;\ng(x)");
+        let first = term_at(&document, 0);
+        let last = term_at(&document, 2);
+
+        document.edit(&TextEdit::new(14..15, "é"));
+        check(&document);
+        assert_eq!(document.text(), "{ a; b };\nlet é = 1;\ng(x)");
+        assert_eq!(term_at(&document, 0), first);
+        assert_eq!(term_at(&document, 2), last);
+
+        document.edit(&TextEdit::insert(0, "infixl 6 <+>; 1 <+> 2;\n"));
+        check(&document);
+        document.edit(&TextEdit::delete(0..14));
+        check(&document);
+    }
+
+    #[test]
+    fn reuses_statements_within_the_edited_block() {
+        let mut document = Document::new("{ a; b };\nlet x = 1;\nfor i in n { c; { d; e }; f(i) }");
+
+        // `c` and `f(i)` are reused from the loop body, `e` from the block within it.
+        document.edit(&TextEdit::new(39..40, "g(x)"));
+        check(&document);
+        assert_eq!(
+            document.text(),
+            "{ a; b };\nlet x = 1;\nfor i in n { c; { g(x); e }; f(i) }"
+        );
+        assert_eq!(document.reused_stmts(), 5);
+
+        // a fixity declared in the loop body changes the precedence of the statements after it.
+        document.edit(&TextEdit::insert(37, "infixl 6 <+>; "));
+        check(&document);
+        assert_eq!(document.reused_stmts(), 3);
+
+        // the statements of a block put in a tuple are parsed at a greater depth.
+        document.edit(&TextEdit::new(0..1, "({"));
+        check(&document);
+        assert_eq!(document.reused_stmts(), 2);
+    }
+
+    const STMTS: &[&str] = &[
+        "let x = 1",
+        "let (<+>) = add",
+        "infixl 6 <+>",
+        "infixr 2 ++",
+        "1 <+> 2 ++ 3",
+        "f(x, \"s;\") // c;",
+        "{ a; b }",
+        "for i in 0 .. n { g(i); }",
+        "let y = a where a = 2",
+        "let z = 1 in z",
+        "import \"lib.ml\" as lib",
+        "lib.f(é)",
+        "(1,\n 2)",
+    ];
+
+    const INSERTS: &[&str] = &[
+        "",
+        " ",
+        "\n",
+        ";",
+        "(",
+        ")",
+        "{",
+        "}",
+        "\"",
+        "+",
+        "<+>",
+        "x",
+        "1",
+        "let",
+        "in",
+        "// ",
+        "; y;",
+        "infixl 1 <+>;",
+    ];
+
+    /// Random edits of random programs give the same tree as parsing the edited text.
+    #[test]
+    fn matches_fresh_parse_after_random_edits() {
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..300 {
+            let text = (0..rng.below(8))
+                .map(|_| *rng.pick(STMTS))
+                .collect::<Vec<_>>()
+                .join(";\n");
+            // a low limit on nesting makes the depth statements are parsed at matter.
+            let ctx = match rng.below(2) {
+                0 => ParseContext::new(),
+                _ => ParseContext::new().with_max_depth(3),
+            };
+            let mut document = Document::with_context(FileId::default(), text, ctx);
+            check(&document);
+
+            for _ in 0..8 {
+                let text = document.text();
+                let boundaries = (0..=text.len())
+                    .filter(|i| text.is_char_boundary(*i))
+                    .collect::<Vec<_>>();
+                let start = *rng.pick(&boundaries);
+                let end = start
+                    + text[start..]
+                        .chars()
+                        .take(rng.below(4))
+                        .map(char::len_utf8)
+                        .sum::<usize>();
+                let insert = if rng.below(3) == 0 {
+                    *rng.pick(STMTS)
+                } else {
+                    *rng.pick(INSERTS)
+                };
+
+                document.edit(&TextEdit::new(start..end, insert));
+                check(&document);
+            }
+        }
+    }
+}
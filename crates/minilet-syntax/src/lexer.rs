@@ -0,0 +1,295 @@
+//! Splitting source text into tokens.
+//!
+//! The lexer classifies input the same way the parsers do: identifiers are ASCII alphanumeric
+//! words, operators are maximal runs of [symbol characters](crate::op), and everything between
+//! tokens is [`Trivia`]. Keywords, identifiers, literals and binary operators
+//! ([`crate::token`], [`crate::op::OpSymbol`]) are read as tokens through [`scan`], so that they
+//! match whole tokens: `let` does not match the start of `letter`. Tokens are scanned from the
+//! input stream as the parsers reach them rather than collected by [`lex`] beforehand, so
+//! backtracking still rewinds the stream. Prefix operators and single-character punctuation read
+//! one character, so that prefix operators stack (`-~x`) and `x=-1` binds `-1`.
+use crate::{
+    op::is_symbol_char,
+    util::{any_char, take_while},
+    InputStream, Parse, ParseContext, Position, PositionMeter, Span, Spanned, Trivia,
+};
+use minilet_utils::stream::StrStream;
+use parcom::{prelude::*, IntoMeasured};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Ident,
+    Let,
+    In,
+    Where,
+    For,
+    Import,
+    As,
+    Infixl,
+    Infixr,
+    Infix,
+    /// Digits, possibly with a radix prefix. The value is checked by the literal parser.
+    Integer,
+    String,
+    /// A string literal missing its closing `"`.
+    UnterminatedString,
+    /// A run of operator characters, such as `+`, `..=` or `<+>`.
+    Symbol,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    Comma,
+    Semi,
+    /// A character that starts no token.
+    Unknown,
+    /// End of input. Its leading trivia is the trivia at the end of the source.
+    Eof,
+}
+
+impl TokenKind {
+    /// Whether the token is an identifier or a keyword, which the parsers also read as names.
+    pub fn is_word(self) -> bool {
+        matches!(
+            self,
+            TokenKind::Ident
+                | TokenKind::Let
+                | TokenKind::In
+                | TokenKind::Where
+                | TokenKind::For
+                | TokenKind::Import
+                | TokenKind::As
+                | TokenKind::Infixl
+                | TokenKind::Infixr
+                | TokenKind::Infix
+        )
+    }
+
+    fn keyword(text: &str) -> Option<Self> {
+        let kind = match text {
+            "let" => TokenKind::Let,
+            "in" => TokenKind::In,
+            "where" => TokenKind::Where,
+            "for" => TokenKind::For,
+            "import" => TokenKind::Import,
+            "as" => TokenKind::As,
+            "infixl" => TokenKind::Infixl,
+            "infixr" => TokenKind::Infixr,
+            "infix" => TokenKind::Infix,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+#[derive(Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub span: Span,
+    pub leading_trivia: Trivia,
+}
+
+impl Spanned for Token {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
+/// Splits `source` into tokens. The last token is always [`TokenKind::Eof`].
+pub fn lex(source: &str) -> Vec<Token> {
+    let ctx = ParseContext::new();
+    let mut rest = IntoMeasured::<Position>::into_measured_with(
+        StrStream::new(source),
+        PositionMeter::default(),
+    );
+    let mut tokens = Vec::new();
+    loop {
+        let (token, r) = pollster::block_on(next_token(rest, &ctx));
+        let eof = token.kind == TokenKind::Eof;
+        tokens.push(token);
+        if eof {
+            return tokens;
+        }
+        rest = r;
+    }
+}
+
+/// Reads the trivia and the token following it.
+pub async fn next_token<S: InputStream>(input: S, ctx: &ParseContext) -> (Token, S) {
+    let (leading_trivia, rest) = match Trivia::parse(input, ctx).await {
+        Done(v, r) => (v, r),
+        Fail(e, _) | Fatal(e, _) => e.never(),
+    };
+
+    let start = rest.position();
+    let (kind, text, rest) = scan(rest).await;
+    let token = Token {
+        kind,
+        text,
+        span: Span::new(start, rest.position()),
+        leading_trivia,
+    };
+    (token, rest)
+}
+
+/// Reads the token starting right at `input`, without skipping trivia.
+pub(crate) async fn scan<S: InputStream>(input: S) -> (TokenKind, String, S) {
+    let anchor = input.anchor();
+    let (first, rest) = match any_char().parse(input).await {
+        Done(c, r) => (c, r),
+        Fail(_, r) => return (TokenKind::Eof, String::new(), r.rewind(anchor)),
+        Fatal(e, _) => e.never(),
+    };
+
+    let mut text = String::from(first);
+    let kind = match first {
+        '(' => TokenKind::LParen,
+        ')' => TokenKind::RParen,
+        '{' => TokenKind::LBrace,
+        '}' => TokenKind::RBrace,
+        ',' => TokenKind::Comma,
+        ';' => TokenKind::Semi,
+        '"' => {
+            let (terminated, rest) = scan_string(rest, &mut text).await;
+            let kind = if terminated {
+                TokenKind::String
+            } else {
+                TokenKind::UnterminatedString
+            };
+            return (kind, text, rest);
+        }
+        c if c.is_ascii_alphabetic() => {
+            let rest = scan_while(rest, &mut text, |c| c.is_ascii_alphanumeric()).await;
+            let kind = TokenKind::keyword(&text).unwrap_or(TokenKind::Ident);
+            return (kind, text, rest);
+        }
+        c if c.is_ascii_digit() => {
+            let rest = scan_while(rest, &mut text, |c| c.is_ascii_alphanumeric()).await;
+            return (TokenKind::Integer, text, rest);
+        }
+        c if is_symbol_char(c) => {
+            let rest = scan_while(rest, &mut text, is_symbol_char).await;
+            return (TokenKind::Symbol, text, rest);
+        }
+        _ => TokenKind::Unknown,
+    };
+    (kind, text, rest)
+}
+
+async fn scan_while<S: InputStream>(
+    input: S,
+    text: &mut String,
+    accept: impl Fn(char) -> bool,
+) -> S {
+    let (more, rest) = take_while(input, accept).await;
+    text.push_str(&more);
+    rest
+}
+
+/// Reads the rest of a string literal after its opening `"`, returning whether it was closed.
+async fn scan_string<S: InputStream>(mut input: S, text: &mut String) -> (bool, S) {
+    let mut escaped = false;
+    loop {
+        let anchor = input.anchor();
+        let (c, rest) = match any_char().parse(input).await {
+            Done(c, r) => (c, r),
+            Fail(_, r) => return (false, r.rewind(anchor)),
+            Fatal(e, _) => e.never(),
+        };
+        text.push(c);
+        input = rest;
+
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return (true, input),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lex, TokenKind};
+    use crate::{
+        literal::{IntegerLiteral, StringLiteral},
+        term::ident::Ident,
+        Parse, ParseContext, Position,
+    };
+    use minilet_utils::stream::StrStream;
+    use parcom::{prelude::*, IntoMeasured};
+
+    /// Parses the start of `source` as `T`, giving back the text it read.
+    fn read<T: Parse>(source: &str, text: impl Fn(&T) -> &str) -> Option<String> {
+        let input = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            Default::default(),
+        );
+        match pollster::block_on(T::parse(input, &ParseContext::new())) {
+            Done(v, _) => Some(text(&v).to_string()),
+            Fail(_, _) | Fatal(_, _) => None,
+        }
+    }
+
+    #[test]
+    fn splits_source_into_tokens() {
+        let source = "let x = 0x1F <+> f(\"a\\\"b\") // note\n{ y; } é \"open";
+        let tokens = lex(source);
+
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Let,
+                TokenKind::Ident,
+                TokenKind::Symbol,
+                TokenKind::Integer,
+                TokenKind::Symbol,
+                TokenKind::Ident,
+                TokenKind::LParen,
+                TokenKind::String,
+                TokenKind::RParen,
+                TokenKind::LBrace,
+                TokenKind::Ident,
+                TokenKind::Semi,
+                TokenKind::RBrace,
+                TokenKind::Unknown,
+                TokenKind::UnterminatedString,
+                TokenKind::Eof
+            ]
+        );
+        assert_eq!(tokens[9].leading_trivia.text, " // note\n");
+
+        let printed: String = tokens
+            .iter()
+            .map(|t| format!("{}{}", t.leading_trivia.text, t.text))
+            .collect();
+        assert_eq!(printed, source);
+        for token in &tokens {
+            assert_eq!(&source[token.span.bytes()], token.text);
+        }
+    }
+
+    #[test]
+    fn parsers_read_whole_tokens() {
+        let source = "letter 0x1F 0x1Fg 12ab \"a\\\"b\" \"open";
+        for token in lex(source) {
+            let rest = &source[token.span.bytes().start..];
+            let ident = read::<Ident>(rest, |v| &v.text);
+            let integer = read::<IntegerLiteral>(rest, |v| &v.raw_text);
+            let string = read::<StringLiteral>(rest, |v| &v.raw_text);
+            let expected = Some(token.text.clone());
+            match token.kind {
+                TokenKind::Ident => assert_eq!(ident, expected),
+                // the token is read whole and rejected if it is not a valid number.
+                TokenKind::Integer if matches!(token.text.as_str(), "0x1Fg" | "12ab") => {
+                    assert_eq!(integer, None)
+                }
+                TokenKind::Integer => assert_eq!(integer, expected),
+                TokenKind::String => assert_eq!(string, expected),
+                _ => assert!(ident.is_none() && integer.is_none() && string.is_none()),
+            }
+        }
+    }
+}
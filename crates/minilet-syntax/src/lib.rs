@@ -1,28 +1,53 @@
+mod entry;
 mod span;
 mod util;
 
+pub mod binder;
+pub mod context;
+pub mod diagnostics;
+pub mod expected;
 pub mod expr;
+pub mod fold;
+pub mod format;
+pub mod incremental;
+pub mod lexer;
 pub mod literal;
+pub mod metrics;
+pub mod module;
 pub mod op;
+pub mod precedence;
 pub mod punctured;
+pub mod recovery;
 pub mod relaxed;
+pub mod source_file;
+pub mod source_map;
 pub mod spacing;
 pub mod stmt;
 pub mod stmts;
+pub mod syntax_tree;
 pub mod term;
+pub mod to_source;
 pub mod token;
 pub mod trivia;
 pub mod unary_op;
+pub mod visit;
+pub mod visit_mut;
 
-use parcom::{metrics::LineColumn, ParseResult, ParseStream};
+use parcom::{ParseResult, ParseStream};
 
+pub use context::{ParseContext, DEFAULT_MAX_DEPTH};
+pub use entry::{parse_expr, parse_program};
 pub use expr::Expr;
 pub use literal::Literal;
+pub use recovery::{parse_recovering, Recovered, SyntaxError};
+pub use source_file::SourceFile;
+pub use source_map::{FileId, SourceMap};
 pub use spacing::Spacing;
-pub use span::Span;
+pub use span::{Position, PositionMeter, Span, Spanned};
 pub use stmt::Stmt;
 pub use stmts::Stmts;
 pub use term::{Ident, Term, Tuple, Unary};
+pub use to_source::ToSource;
 pub use trivia::Trivia;
 
 pub trait Parse: Sized {
@@ -30,8 +55,16 @@ pub trait Parse: Sized {
     type Fatal;
     fn parse<S: InputStream>(
         input: S,
+        ctx: &ParseContext,
     ) -> impl std::future::Future<Output = ParseResult<S, Self, Self::Error, Self::Fatal>>;
 }
 
-pub trait InputStream: ParseStream<Segment = str, Metrics = LineColumn> {}
-impl<S: ParseStream<Segment = str, Metrics = LineColumn>> InputStream for S {}
+/// Streams the parsers accept. Spans are always made of [`Position`]s; a stream measured with
+/// coarser [metrics](crate::metrics) gives positions with the fields it does not measure left at
+/// zero.
+pub trait InputStream: ParseStream<Segment = str, Metrics: Into<Position>> {
+    fn position(&self) -> Position {
+        self.metrics().into()
+    }
+}
+impl<S: ParseStream<Segment = str, Metrics: Into<Position>>> InputStream for S {}
@@ -7,8 +7,8 @@ use parcom::ParseResult;
 use string::ParseStringLiteralError;
 pub use string::StringLiteral;
 
-use crate::{Parse, Span};
-use parcom::prelude::*;
+use crate::{Parse, ParseContext, Span, Spanned};
+use parcom::{prelude::*, RewindStream};
 
 #[derive(Debug)]
 pub enum Literal {
@@ -22,16 +22,17 @@ impl Parse for Literal {
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        ctx: &ParseContext,
     ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
         let anchor = input.anchor();
 
-        let (e0, input) = match IntegerLiteral::parse(input).await {
+        let (e0, input) = match IntegerLiteral::parse(input, ctx).await {
             Done(v, r) => return Done(Literal::Integer(v), r),
             Fail(e, r) => (e, r.rewind(anchor)),
             Fatal(e, _) => e.never(),
         };
 
-        match StringLiteral::parse(input).await {
+        match StringLiteral::parse(input, ctx).await {
             Done(v, r) => return Done(Literal::String(v), r),
             Fail(e, r) => Fail(
                 ParseLiteralError {
@@ -51,8 +52,8 @@ impl Parse for Literal {
     }
 }
 
-impl Literal {
-    pub fn span(&self) -> Span {
+impl Spanned for Literal {
+    fn span(&self) -> Span {
         match self {
             Literal::Integer(v) => v.span.clone(),
             Literal::String(v) => v.span.clone(),
@@ -65,3 +66,106 @@ pub struct ParseLiteralError {
     pub integer: ParseIntegerLiteralError,
     pub string: ParseStringLiteralError,
 }
+
+impl ParseLiteralError {
+    /// Whether a literal starts here but is malformed, rather than no literal starting here.
+    pub fn is_malformed(&self) -> bool {
+        !matches!(
+            (&self.integer, &self.string),
+            (
+                ParseIntegerLiteralError::Missing(_),
+                ParseStringLiteralError::Missing(_)
+            )
+        )
+    }
+}
+
+/// Span of the `len` bytes `offset` bytes into the token read from `anchor`, with the input
+/// after them.
+async fn span_in_token<S: crate::InputStream>(
+    input: S,
+    anchor: <S as RewindStream>::Anchor,
+    offset: usize,
+    len: usize,
+) -> (Span, S) {
+    let input = input.rewind(anchor).advance(offset).await;
+    let start = input.position();
+    let input = input.advance(len).await;
+    let end = input.position();
+    (Span::new(start, end), input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IntegerLiteral, StringLiteral};
+    use crate::{util::splits, InputStream, Parse, ParseContext, Position, PositionMeter};
+    use minilet_utils::stream::{RopeStream, StrStream};
+    use parcom::{prelude::*, IntoMeasured, ParcomSegmentIterator};
+    use std::fmt::Debug;
+
+    async fn rest<S: InputStream>(input: S) -> String {
+        let mut segments = input.segments();
+        let mut text = String::new();
+        while let Some(segment) = segments.next(0).await {
+            text.push_str(&segment);
+        }
+        text
+    }
+
+    async fn outcome<T, S>(input: S) -> String
+    where
+        T: Parse + Debug,
+        T::Error: Debug,
+        T::Fatal: Debug,
+        S: InputStream,
+    {
+        match T::parse(input, &ParseContext::new()).await {
+            Done(v, r) => format!("{v:?} {:?} {:?}", r.position(), rest(r).await),
+            Fail(e, _) => format!("fail {e:?}"),
+            Fatal(e, _) => format!("fatal {e:?}"),
+        }
+    }
+
+    /// Parses `source` split at every boundary, expecting the result of parsing it whole.
+    fn assert_split_invariant<T>(source: &str)
+    where
+        T: Parse + Debug,
+        T::Error: Debug,
+        T::Fatal: Debug,
+    {
+        let whole = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            PositionMeter::default(),
+        );
+        let expected = pollster::block_on(outcome::<T, _>(whole));
+        for pieces in splits(source) {
+            let input = IntoMeasured::<Position>::into_measured_with(
+                RopeStream::new(&pieces),
+                PositionMeter::default(),
+            );
+            let actual = pollster::block_on(outcome::<T, _>(input));
+            assert_eq!(actual, expected, "{pieces:?}");
+        }
+    }
+
+    #[test]
+    fn integer_literals_parse_across_segments() {
+        for source in ["0", "07;", "0x1Fg", "0b102", "0o", "12é", "x"] {
+            assert_split_invariant::<IntegerLiteral>(source);
+        }
+    }
+
+    #[test]
+    fn string_literals_parse_across_segments() {
+        for source in [
+            "\"\"",
+            "\"a\\\"é🙂\" x",
+            "\"a\\\\\"\n",
+            "\"open",
+            "\"bad\\q\"",
+            "x",
+        ] {
+            assert_split_invariant::<StringLiteral>(source);
+        }
+    }
+}
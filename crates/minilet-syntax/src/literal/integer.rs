@@ -1,115 +1,83 @@
-use parcom::{
-    Never, ParcomSegmentIterator,
-    ParseResult::{Done, Fail},
-};
+use parcom::prelude::*;
 
-use crate::{InputStream, Parse, Span};
+use super::span_in_token;
+use crate::{
+    lexer::{scan, TokenKind},
+    InputStream, Parse, ParseContext, Span, Spanned,
+};
 #[derive(Debug)]
 pub struct IntegerLiteral {
+    /// Source text including the prefix.
+    pub raw_text: String,
     pub prefix: Option<IntegerLiteralPrefix>,
     pub digits: String,
     pub number: i64,
     pub span: Span,
 }
 
+impl Spanned for IntegerLiteral {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
 impl Parse for IntegerLiteral {
     type Error = ParseIntegerLiteralError;
     type Fatal = Never;
 
     async fn parse<S: InputStream>(
         input: S,
+        _ctx: &ParseContext,
     ) -> parcom::ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let start = input.metrics();
-        let mut segments = input.segments();
-        let mut digits = String::new();
-        let mut state = IntegerParserState::Initial;
-
-        let mut prefix = None;
-
-        'outer: loop {
-            let Some(segment) = segments.next(0).await else {
-                break;
-            };
-
-            for c in segment.chars() {
-                match state {
-                    IntegerParserState::Initial => match c {
-                        '0' => {
-                            state = IntegerParserState::MaybePrefixZero;
-                        }
-                        '0'..='9' => {
-                            state = IntegerParserState::Digits;
-                            digits.push(c);
-                        }
-                        _ => {
-                            return Fail(
-                                ParseIntegerLiteralError(Span::points(start)),
-                                input.into(),
-                            );
-                        }
-                    },
-                    IntegerParserState::MaybePrefixZero => match c {
-                        'b' => {
-                            prefix = Some(IntegerLiteralPrefix::Bin);
-                            state = IntegerParserState::Digits;
-                        }
-                        'o' => {
-                            prefix = Some(IntegerLiteralPrefix::Oct);
-                            state = IntegerParserState::Digits;
-                        }
-                        'x' => {
-                            prefix = Some(IntegerLiteralPrefix::Hex);
-                            state = IntegerParserState::Digits;
-                        }
-                        '0'..='9' => {
-                            state = IntegerParserState::Digits;
-                        }
-                        _ => {
-                            digits.push('0');
-                            break 'outer;
-                        }
-                    },
-                    IntegerParserState::Digits => match prefix {
-                        Some(IntegerLiteralPrefix::Bin) if ('0'..='1').contains(&c) => {
-                            digits.push(c);
-                        }
-                        Some(IntegerLiteralPrefix::Oct) if ('0'..='7').contains(&c) => {
-                            digits.push(c);
-                        }
-                        Some(IntegerLiteralPrefix::Hex) if c.is_ascii_hexdigit() => {
-                            digits.push(c);
-                        }
-                        None if c.is_ascii_digit() => {
-                            digits.push(c);
-                        }
-                        _ => break 'outer,
-                    },
-                }
-            }
+        let start = input.position();
+        let anchor = input.anchor();
+        let (kind, raw_text, rest) = Box::pin(scan(input)).await;
+        if kind != TokenKind::Integer {
+            return Fail(
+                ParseIntegerLiteralError::Missing(Span::points(start)),
+                rest.rewind(anchor).into(),
+            );
         }
 
+        let (prefix, digits) = match raw_text.get(..2) {
+            Some("0b") => (Some(IntegerLiteralPrefix::Bin), &raw_text[2..]),
+            Some("0o") => (Some(IntegerLiteralPrefix::Oct), &raw_text[2..]),
+            Some("0x") => (Some(IntegerLiteralPrefix::Hex), &raw_text[2..]),
+            _ => (None, raw_text.as_str()),
+        };
         let radix = match prefix {
             Some(IntegerLiteralPrefix::Bin) => 2,
             Some(IntegerLiteralPrefix::Oct) => 8,
             Some(IntegerLiteralPrefix::Hex) => 16,
             None => 10,
         };
-        let Ok(num) = i64::from_str_radix(&digits, radix) else {
-            return Fail(ParseIntegerLiteralError(Span::points(start)), input.into());
-        };
 
-        let consumed = match prefix {
-            Some(_) => digits.len() + 2,
-            None => digits.len(),
+        // the first character that is not a digit, or the end of a prefix without digits.
+        let prefix_len = raw_text.len() - digits.len();
+        let invalid = match digits.char_indices().find(|(_, c)| !c.is_digit(radix)) {
+            Some((i, c)) => Some((prefix_len + i, c.len_utf8())),
+            None if digits.is_empty() => Some((prefix_len, 0)),
+            None => None,
         };
+        if let Some((offset, len)) = invalid {
+            let (span, rest) = span_in_token(rest, anchor, offset, len).await;
+            return Fail(
+                ParseIntegerLiteralError::InvalidDigit { span, radix },
+                rest.into(),
+            );
+        }
 
-        let rest = input.advance(consumed).await;
-        let end = rest.metrics();
+        let end = rest.position();
         let span = Span::new(start, end);
+        // the digits are valid, so only a value too large for `i64` is rejected.
+        let Ok(num) = i64::from_str_radix(digits, radix) else {
+            return Fail(ParseIntegerLiteralError::OutOfRange(span), rest.into());
+        };
 
         let literal = IntegerLiteral {
+            digits: digits.to_string(),
+            raw_text,
             prefix,
-            digits,
             number: num,
             span,
         };
@@ -124,18 +92,23 @@ pub enum IntegerLiteralPrefix {
     Hex,
 }
 
-enum IntegerParserState {
-    Initial,
-    /// Starting `0` that may be prefix
-    MaybePrefixZero,
-    Digits,
-}
-
 #[derive(Debug)]
-pub struct ParseIntegerLiteralError(Span);
+pub enum ParseIntegerLiteralError {
+    /// No integer literal starts here.
+    Missing(Span),
+    /// A character that is not a digit of the literal's radix, or the end of a prefix without
+    /// digits.
+    InvalidDigit { span: Span, radix: u32 },
+    /// A value too large for `i64`.
+    OutOfRange(Span),
+}
 
 impl ParseIntegerLiteralError {
     pub fn span(&self) -> Span {
-        self.0.clone()
+        match self {
+            ParseIntegerLiteralError::Missing(span)
+            | ParseIntegerLiteralError::InvalidDigit { span, .. }
+            | ParseIntegerLiteralError::OutOfRange(span) => span.clone(),
+        }
     }
 }
@@ -1,9 +1,10 @@
-use parcom::{
-    ParcomSegmentIterator,
-    ParseResult::{Done, Fail, Fatal},
-};
+use parcom::prelude::*;
 
-use crate::{Parse, Span};
+use super::span_in_token;
+use crate::{
+    lexer::{scan, TokenKind},
+    Parse, ParseContext, Span, Spanned,
+};
 
 #[derive(Debug)]
 pub struct StringLiteral {
@@ -12,81 +13,62 @@ pub struct StringLiteral {
     pub span: Span,
 }
 
+impl Spanned for StringLiteral {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
 impl Parse for StringLiteral {
     type Error = ParseStringLiteralError;
     type Fatal = ParseStringLiteralError;
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        _ctx: &ParseContext,
     ) -> parcom::ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let mut state = StringParserState::Initial;
-
-        let start = input.metrics();
-        let mut segments = input.segments();
+        let start = input.position();
+        let anchor = input.anchor();
+        let (kind, raw_text, rest) = Box::pin(scan(input)).await;
+        match kind {
+            TokenKind::String => (),
+            TokenKind::UnterminatedString => {
+                let error = ParseStringLiteralError::Unterminated {
+                    start: Span::points(start),
+                    end: Span::points(rest.position()),
+                };
+                return Fatal(error, rest.into());
+            }
+            _ => {
+                return Fail(
+                    ParseStringLiteralError::Missing(Span::points(start)),
+                    rest.rewind(anchor).into(),
+                )
+            }
+        }
 
-        let mut raw_text = String::new();
         let mut text = String::new();
-
-        'outer: loop {
-            let Some(segment) = segments.next(0).await else {
-                match state {
-                    StringParserState::Initial => {
-                        return Fail(ParseStringLiteralError(Span::points(start)), input.into())
-                    }
-                    _ => return Fatal(ParseStringLiteralError(Span::points(start)), input.into()),
-                }
-            };
-
-            for c in segment.chars() {
-                match state {
-                    StringParserState::Initial => match c {
-                        '"' => {
-                            state = StringParserState::Text;
-                            raw_text.push(c);
-                        }
-                        _ => {
-                            return Fail(ParseStringLiteralError(Span::points(start)), input.into())
-                        }
-                    },
-                    StringParserState::Text => match c {
-                        '\\' => {
-                            state = StringParserState::Escape;
-                            raw_text.push(c);
-                        }
-                        '"' => {
-                            raw_text.push(c);
-                            break 'outer;
-                        }
-                        _ => {
-                            text.push(c);
-                            raw_text.push(c);
-                        }
-                    },
-                    StringParserState::Escape => match c {
-                        '"' => {
-                            state = StringParserState::Text;
-                            raw_text.push(c);
-                            text.push(c);
-                        }
-                        '\\' => {
-                            state = StringParserState::Text;
-                            raw_text.push(c);
-                            text.push(c);
-                        }
-                        _ => {
-                            return Fatal(
-                                ParseStringLiteralError(Span::points(start)),
-                                input.into(),
-                            )
-                        }
-                    },
+        let mut chars = raw_text[..raw_text.len() - 1].char_indices().skip(1);
+        while let Some((i, c)) = chars.next() {
+            if c != '\\' {
+                text.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => text.push(c),
+                escaped => {
+                    let len = 1 + escaped.map_or(0, |(_, c)| c.len_utf8());
+                    let (escape, rest) = span_in_token(rest, anchor, i, len).await;
+                    let error = ParseStringLiteralError::InvalidEscape {
+                        start: Span::points(start),
+                        escape,
+                    };
+                    return Fatal(error, rest.into());
                 }
             }
         }
 
-        let rest = input.advance(raw_text.len()).await;
-        let end = rest.metrics();
-
+        let end = rest.position();
         let literal = StringLiteral {
             raw_text,
             text,
@@ -97,17 +79,22 @@ impl Parse for StringLiteral {
     }
 }
 
-enum StringParserState {
-    Initial,
-    Text,
-    Escape,
-}
-
 #[derive(Debug)]
-pub struct ParseStringLiteralError(Span);
+pub enum ParseStringLiteralError {
+    /// No string literal starts here.
+    Missing(Span),
+    /// The input ended before the closing `"` of the literal starting at `start`.
+    Unterminated { start: Span, end: Span },
+    /// A `\` followed by something other than `"` or `\`.
+    InvalidEscape { start: Span, escape: Span },
+}
 
 impl ParseStringLiteralError {
     pub fn span(&self) -> Span {
-        self.0.clone()
+        match self {
+            ParseStringLiteralError::Missing(span) => span.clone(),
+            ParseStringLiteralError::Unterminated { end, .. } => end.clone(),
+            ParseStringLiteralError::InvalidEscape { escape, .. } => escape.clone(),
+        }
     }
 }
@@ -0,0 +1,243 @@
+//! Metrics for measuring input in units other than a full [`Position`].
+//!
+//! [`Position`] measures everything at once. The metrics here each measure one thing, for
+//! callers that only need byte offsets or LSP coordinates; parsers accept streams measured with
+//! any of them, as well as parcom's [`LineColumn`], and fill the rest of their spans' positions
+//! with zeros.
+use crate::{source_map::Utf16Position, Position};
+use parcom::{metrics::LineColumn, Meter, Metrics};
+
+/// Offset in bytes from the start of the input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteOffset(pub usize);
+
+impl Metrics<str> for ByteOffset {
+    type Meter = Self;
+}
+
+impl Meter<str> for ByteOffset {
+    type Metrics = Self;
+
+    fn advance(self, segment: &str) -> Self {
+        Self(self.0 + segment.len())
+    }
+
+    fn metrics(&self) -> Self {
+        *self
+    }
+}
+
+impl From<ByteOffset> for Position {
+    fn from(offset: ByteOffset) -> Self {
+        Position {
+            byte: offset.0,
+            ..Default::default()
+        }
+    }
+}
+
+/// Offset in `char`s from the start of the input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CharOffset(pub usize);
+
+impl Metrics<str> for CharOffset {
+    type Meter = Self;
+}
+
+impl Meter<str> for CharOffset {
+    type Metrics = Self;
+
+    fn advance(self, segment: &str) -> Self {
+        Self(self.0 + segment.chars().count())
+    }
+
+    fn metrics(&self) -> Self {
+        *self
+    }
+}
+
+impl From<CharOffset> for Position {
+    fn from(offset: CharOffset) -> Self {
+        Position {
+            char: offset.0,
+            ..Default::default()
+        }
+    }
+}
+
+impl Metrics<str> for Utf16Position {
+    type Meter = Self;
+}
+
+impl Meter<str> for Utf16Position {
+    type Metrics = Self;
+
+    fn advance(mut self, segment: &str) -> Self {
+        for c in segment.chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.character = 0;
+            } else {
+                self.character += c.len_utf16();
+            }
+        }
+        self
+    }
+
+    fn metrics(&self) -> Self {
+        *self
+    }
+}
+
+impl From<Utf16Position> for Position {
+    fn from(position: Utf16Position) -> Self {
+        Position {
+            line: position.line,
+            utf16_column: position.character,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<LineColumn> for Position {
+    fn from(position: LineColumn) -> Self {
+        Position {
+            line: position.line,
+            column: position.column,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ByteOffset, CharOffset};
+    use crate::{
+        diagnostics::ToDiagnostic,
+        expected::{Expectation, Expected},
+        literal::StringLiteral,
+        parse_recovering,
+        source_map::Utf16Position,
+        InputStream, Parse, ParseContext, Position, PositionMeter, Recovered, SourceFile, Span,
+        Spanned, ToSource,
+    };
+    use minilet_utils::stream::{ChunkedStream, StrStream};
+    use parcom::{metrics::LineColumn, prelude::*, IntoMeasured, Metrics};
+
+    const SOURCE: &str = "\"a\n\u{e9}\u{1F600}\" rest";
+
+    fn end_of_literal<M>(meter: M::Meter) -> Position
+    where
+        M: Metrics<str> + Into<Position>,
+        M::Meter: Clone,
+    {
+        let input = IntoMeasured::<M>::into_measured_with(StrStream::new(SOURCE), meter);
+        let (literal, rest) =
+            match pollster::block_on(StringLiteral::parse(input, &ParseContext::new())) {
+                Done(v, r) => (v, r),
+                _ => panic!("string literal should parse"),
+            };
+        assert_eq!(literal.span().end(), &rest.position());
+        rest.position()
+    }
+
+    #[test]
+    fn measures_in_each_unit() {
+        let full = end_of_literal::<Position>(PositionMeter::default());
+        assert_eq!(
+            full,
+            Position {
+                byte: 10,
+                char: 6,
+                line: 1,
+                column: 3,
+                utf16_column: 4,
+                ..Default::default()
+            }
+        );
+
+        let byte = end_of_literal::<ByteOffset>(ByteOffset::default());
+        assert_eq!((byte.byte, byte.char, byte.line), (10, 0, 0));
+
+        let char = end_of_literal::<CharOffset>(CharOffset::default());
+        assert_eq!((char.byte, char.char, char.line), (0, 6, 0));
+
+        let utf16 = end_of_literal::<Utf16Position>(Utf16Position::default());
+        assert_eq!((utf16.byte, utf16.line, utf16.utf16_column), (0, 1, 4));
+
+        let line_column = end_of_literal::<LineColumn>(Default::default());
+        assert_eq!(
+            (line_column.byte, line_column.line, line_column.column),
+            (0, 1, 3)
+        );
+    }
+
+    #[test]
+    fn merges_by_offset_in_any_unit() {
+        let expected = |at: Position, name| Expected::construct(Span::points(at), name);
+        let bytes = |byte| Position {
+            byte,
+            ..Default::default()
+        };
+        let chars = |char| Position {
+            char,
+            ..Default::default()
+        };
+
+        let merged = expected(bytes(3), "a").merge(expected(bytes(7), "b"));
+        assert_eq!(merged.items, [Expectation::Construct("b")]);
+        let merged = expected(chars(7), "a").merge(expected(chars(3), "b"));
+        assert_eq!(merged.items, [Expectation::Construct("a")]);
+        let merged = expected(chars(3), "a").merge(expected(chars(3), "b"));
+        assert_eq!(merged.items.len(), 2);
+    }
+
+    fn parse_program<S: InputStream>(input: S) -> Recovered<SourceFile> {
+        pollster::block_on(parse_recovering(input, &ParseContext::new()))
+    }
+
+    /// Reading a few bytes at a time splits `é`, `😀` and `あ` between reads, yet the text is
+    /// measured and parsed as if it were read at once.
+    #[test]
+    fn measures_chunked_input_like_whole_input() {
+        let source = "let s = \"\u{e9}\u{1F600}\"; // \u{3042}\nf(\"\u{1F600}\", 1 + ;\n{ x; y }";
+        let whole = parse_program(IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            PositionMeter::default(),
+        ));
+
+        for chunk_size in 1..=8 {
+            let input = IntoMeasured::<Position>::into_measured_with(
+                ChunkedStream::with_chunk_size(source.as_bytes(), chunk_size),
+                PositionMeter::default(),
+            );
+            let chunked = parse_program(input);
+            assert_eq!(chunked.value.to_source(), source);
+            assert_eq!(
+                format!("{:?}", chunked.value),
+                format!("{:?}", whole.value),
+                "{chunk_size}"
+            );
+            assert_eq!(
+                format!("{:?}", chunked.errors),
+                format!("{:?}", whole.errors),
+                "{chunk_size}"
+            );
+
+            // the `;` missing the operand of `+` after `"😀", 1`, where `😀` is two UTF-16 units
+            // but one character.
+            let diagnostic = chunked.errors[0].to_diagnostic();
+            assert_eq!(
+                diagnostic.primary_span().unwrap().start(),
+                &Position {
+                    byte: 39,
+                    char: 30,
+                    line: 1,
+                    column: 11,
+                    utf16_column: 12,
+                    ..Default::default()
+                }
+            );
+        }
+    }
+}
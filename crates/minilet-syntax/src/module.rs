@@ -0,0 +1,489 @@
+use crate::{
+    stmt::StmtImport, stmts::ParseStmtsError, util::any_char, visit::Visit, FileId, Parse,
+    ParseContext, Position, PositionMeter, SourceFile, SourceMap, Span, Spanned, Stmts,
+};
+use minilet_utils::stream::StrStream;
+use parcom::{prelude::*, IntoMeasured, MeasuredStream};
+use std::{collections::HashMap, path::PathBuf};
+
+/// Normalized, `/`-separated name of a module.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModulePath(String);
+
+impl ModulePath {
+    pub fn new(path: &str) -> Self {
+        Self::join(None, path)
+    }
+
+    /// Resolves `path` relative to the directory of `base`. Paths starting with `/` are absolute.
+    pub fn join(base: Option<&ModulePath>, path: &str) -> Self {
+        let mut segments: Vec<&str> = match base {
+            Some(base) if !path.starts_with('/') => {
+                let mut segments: Vec<&str> = base.0.split('/').collect();
+                segments.pop();
+                segments
+            }
+            _ => Vec::new(),
+        };
+
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => (),
+                ".." => {
+                    segments.pop();
+                }
+                _ => segments.push(segment),
+            }
+        }
+
+        Self(segments.join("/"))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Source of module contents, implemented by hosts.
+pub trait ModuleLoader {
+    /// Resolves `path`, as written in an `import` of `importer`, to the module it refers to.
+    fn resolve(&self, importer: Option<&ModulePath>, path: &str) -> Result<ModulePath, LoadError> {
+        Ok(ModulePath::join(importer, path))
+    }
+
+    fn load(&self, path: &ModulePath) -> Result<String, LoadError>;
+}
+
+/// Loads modules from files below `root`.
+#[derive(Debug, Clone)]
+pub struct FsLoader {
+    root: PathBuf,
+}
+
+impl FsLoader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl ModuleLoader for FsLoader {
+    fn load(&self, path: &ModulePath) -> Result<String, LoadError> {
+        std::fs::read_to_string(self.root.join(path.as_str())).map_err(|error| match error.kind() {
+            std::io::ErrorKind::NotFound => LoadError::NotFound(path.clone()),
+            _ => LoadError::Io(error),
+        })
+    }
+}
+
+/// Loads modules from an in-memory map of paths to sources.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLoader {
+    files: HashMap<ModulePath, String>,
+}
+
+impl MemoryLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, path: &str, source: impl Into<String>) {
+        self.files.insert(ModulePath::new(path), source.into());
+    }
+
+    pub fn with(mut self, path: &str, source: impl Into<String>) -> Self {
+        self.insert(path, source);
+        self
+    }
+}
+
+impl ModuleLoader for MemoryLoader {
+    fn load(&self, path: &ModulePath) -> Result<String, LoadError> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| LoadError::NotFound(path.clone()))
+    }
+}
+
+/// Loads modules embedded in the binary, e.g. `&[("std.ml", include_str!("std.ml"))]`.
+#[derive(Debug, Clone, Copy)]
+pub struct BundleLoader {
+    files: &'static [(&'static str, &'static str)],
+}
+
+impl BundleLoader {
+    pub fn new(files: &'static [(&'static str, &'static str)]) -> Self {
+        Self { files }
+    }
+}
+
+impl ModuleLoader for BundleLoader {
+    fn load(&self, path: &ModulePath) -> Result<String, LoadError> {
+        self.files
+            .iter()
+            .find(|(p, _)| ModulePath::new(p) == *path)
+            .map(|(_, source)| source.to_string())
+            .ok_or_else(|| LoadError::NotFound(path.clone()))
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    NotFound(ModulePath),
+    Io(std::io::Error),
+}
+
+#[derive(Debug)]
+pub struct Module {
+    pub path: ModulePath,
+    /// File of the module's source in [`ModuleGraph::source_map`].
+    pub file: FileId,
+    pub stmts: Stmts,
+    pub imports: Vec<Import>,
+}
+
+/// `import` of a module, resolved by the loader. Imports nested in blocks are loaded along with
+/// those at the top level.
+#[derive(Debug)]
+pub struct Import {
+    pub alias: String,
+    pub path: ModulePath,
+    pub span: Span,
+}
+
+/// An entry module and every module it transitively imports.
+#[derive(Debug)]
+pub struct ModuleGraph {
+    /// Modules in the order they were loaded; the entry module comes first.
+    modules: Vec<Module>,
+    source_map: SourceMap,
+}
+
+impl ModuleGraph {
+    pub async fn load<L: ModuleLoader>(
+        entry: &str,
+        loader: &L,
+        ctx: &ParseContext,
+    ) -> Result<Self, ModuleError> {
+        let mut modules: Vec<Module> = Vec::new();
+        let mut indices: HashMap<ModulePath, usize> = HashMap::new();
+        // modules whose imports are being loaded, with the index of the next import to visit.
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+        let mut source_map = SourceMap::new();
+
+        let path = loader
+            .resolve(None, entry)
+            .map_err(|error| ModuleError::Load {
+                path: ModulePath::new(entry),
+                import: None,
+                error,
+            })?;
+        let module = load_module(path, None, loader, ctx, &mut source_map).await?;
+        indices.insert(module.path.clone(), 0);
+        modules.push(module);
+        stack.push((0, 0));
+
+        while let Some(&(index, next)) = stack.last() {
+            let module = &modules[index];
+            let Some(import) = module.imports.get(next) else {
+                stack.pop();
+                continue;
+            };
+            let top = stack.len() - 1;
+            stack[top].1 += 1;
+
+            if let Some(&target) = indices.get(&import.path) {
+                if let Some(position) = stack.iter().position(|(i, _)| *i == target) {
+                    let cycle = stack[position..]
+                        .iter()
+                        .map(|&(i, next)| {
+                            let module = &modules[i];
+                            let import = &module.imports[next - 1];
+                            CycleStep {
+                                importer: module.path.clone(),
+                                import: import.path.clone(),
+                                span: import.span.clone(),
+                            }
+                        })
+                        .collect();
+                    return Err(ModuleError::Cycle(cycle));
+                }
+                continue;
+            }
+
+            let importer = (module.path.clone(), import.span.clone());
+            let module = load_module(
+                import.path.clone(),
+                Some(importer),
+                loader,
+                ctx,
+                &mut source_map,
+            )
+            .await?;
+            let index = modules.len();
+            indices.insert(module.path.clone(), index);
+            modules.push(module);
+            stack.push((index, 0));
+        }
+
+        Ok(Self {
+            modules,
+            source_map,
+        })
+    }
+
+    pub fn entry(&self) -> &Module {
+        &self.modules[0]
+    }
+
+    pub fn get(&self, path: &ModulePath) -> Option<&Module> {
+        self.modules.iter().find(|m| m.path == *path)
+    }
+
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    /// Sources of all modules, for resolving spans in them.
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+}
+
+async fn load_module<L: ModuleLoader>(
+    path: ModulePath,
+    importer: Option<(ModulePath, Span)>,
+    loader: &L,
+    ctx: &ParseContext,
+    source_map: &mut SourceMap,
+) -> Result<Module, ModuleError> {
+    let source = match loader.load(&path) {
+        Ok(v) => v,
+        Err(error) => {
+            return Err(ModuleError::Load {
+                path,
+                import: importer,
+                error,
+            })
+        }
+    };
+
+    let file = source_map.add(path.as_str(), source);
+    let stmts = parse_module(&path, source_map.text(file), file, ctx).await?;
+
+    let mut found = FindImports::default();
+    found.visit_stmts(&stmts);
+
+    let mut imports = Vec::new();
+    for (import_path, alias, span) in found.0 {
+        let resolved =
+            loader
+                .resolve(Some(&path), &import_path)
+                .map_err(|error| ModuleError::Load {
+                    path: ModulePath::join(Some(&path), &import_path),
+                    import: Some((path.clone(), span.clone())),
+                    error,
+                })?;
+
+        imports.push(Import {
+            alias,
+            path: resolved,
+            span,
+        });
+    }
+
+    Ok(Module {
+        path,
+        file,
+        stmts,
+        imports,
+    })
+}
+
+/// Collects the path, alias and span of every `import` in a module, including those nested in
+/// blocks.
+#[derive(Default)]
+struct FindImports(Vec<(String, String, Span)>);
+
+impl Visit for FindImports {
+    fn visit_stmt_import(&mut self, node: &StmtImport) {
+        let import = (node.path.text.clone(), node.alias.text.clone(), node.span());
+        self.0.push(import);
+    }
+}
+
+async fn parse_module(
+    path: &ModulePath,
+    source: &str,
+    file: FileId,
+    ctx: &ParseContext,
+) -> Result<Stmts, ModuleError> {
+    let input = IntoMeasured::<Position>::into_measured_with(
+        StrStream::new(source),
+        PositionMeter::new(file),
+    );
+
+    let (file, rest) = match SourceFile::parse(input, ctx).await {
+        Done(v, r) => (v, r),
+        Fail(e, _) => e.never(),
+        Fatal(error, _) => {
+            return Err(ModuleError::Parse {
+                path: path.clone(),
+                error,
+            })
+        }
+    };
+
+    let start = rest.metrics();
+    match any_char().parse(rest).await {
+        Fail(_, _) => Ok(file.stmts),
+        Done(_, r) => Err(ModuleError::UnexpectedInput {
+            path: path.clone(),
+            span: Span::new(start, r.metrics()),
+        }),
+        Fatal(e, _) => e.never(),
+    }
+}
+
+/// One `import` statement taking part in an import cycle.
+#[derive(Debug)]
+pub struct CycleStep {
+    pub importer: ModulePath,
+    pub import: ModulePath,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum ModuleError {
+    Load {
+        path: ModulePath,
+        /// The importing module and the span of its `import` statement.
+        import: Option<(ModulePath, Span)>,
+        error: LoadError,
+    },
+    Parse {
+        path: ModulePath,
+        error: ParseStmtsError,
+    },
+    UnexpectedInput {
+        path: ModulePath,
+        span: Span,
+    },
+    Cycle(Vec<CycleStep>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BundleLoader, FsLoader, LoadError, MemoryLoader, ModuleError, ModuleGraph, ModuleLoader,
+        ModulePath,
+    };
+    use crate::ParseContext;
+
+    fn load(entry: &str, loader: &impl ModuleLoader) -> ModuleGraph {
+        pollster::block_on(ModuleGraph::load(entry, loader, &ParseContext::new())).unwrap()
+    }
+
+    fn load_error(entry: &str, loader: &impl ModuleLoader) -> ModuleError {
+        pollster::block_on(ModuleGraph::load(entry, loader, &ParseContext::new())).unwrap_err()
+    }
+
+    fn paths(graph: &ModuleGraph) -> Vec<&str> {
+        graph.modules().iter().map(|m| m.path.as_str()).collect()
+    }
+
+    #[test]
+    fn joins_paths_relative_to_the_importer() {
+        let base = ModulePath::new("lib/list.ml");
+        assert_eq!(
+            ModulePath::join(Some(&base), "map.ml").as_str(),
+            "lib/map.ml"
+        );
+        assert_eq!(
+            ModulePath::join(Some(&base), "../main.ml").as_str(),
+            "main.ml"
+        );
+        assert_eq!(
+            ModulePath::join(Some(&base), "/std/io.ml").as_str(),
+            "std/io.ml"
+        );
+        assert_eq!(ModulePath::new("./a//b.ml").as_str(), "a/b.ml");
+    }
+
+    #[test]
+    fn loads_imports_from_memory() {
+        let loader = MemoryLoader::new()
+            .with("main.ml", "import \"lib/list.ml\" as list;\nlist.map")
+            .with("lib/list.ml", "import \"map.ml\" as map;\nmap.f")
+            .with("lib/map.ml", "let f = 1;");
+
+        let graph = load("main.ml", &loader);
+        assert_eq!(paths(&graph), ["main.ml", "lib/list.ml", "lib/map.ml"]);
+        let import = &graph.entry().imports[0];
+        assert_eq!(import.alias, "list");
+        let source = graph.source_map().text(graph.entry().file);
+        assert_eq!(
+            &source[import.span.bytes()],
+            "import \"lib/list.ml\" as list"
+        );
+    }
+
+    #[test]
+    fn loads_imports_nested_in_blocks() {
+        let loader = MemoryLoader::new()
+            .with("main.ml", "let x = { import \"a.ml\" as a; a.x };")
+            .with("a.ml", "f({ import \"b.ml\" as b; b.y });")
+            .with("b.ml", "let y = 2;");
+
+        let graph = load("main.ml", &loader);
+        assert_eq!(paths(&graph), ["main.ml", "a.ml", "b.ml"]);
+    }
+
+    #[test]
+    fn loads_imports_from_a_bundle() {
+        static FILES: &[(&str, &str)] = &[("main.ml", "import \"std.ml\" as std;"), ("std.ml", "")];
+        let graph = load("main.ml", &BundleLoader::new(FILES));
+        assert_eq!(paths(&graph), ["main.ml", "std.ml"]);
+    }
+
+    #[test]
+    fn loads_imports_from_files() {
+        let root = std::env::temp_dir().join(format!("minilet-fs-loader-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::write(root.join("main.ml"), "import \"lib/a.ml\" as a;").unwrap();
+        std::fs::write(root.join("lib/a.ml"), "import \"missing.ml\" as m;").unwrap();
+
+        let error = load_error("main.ml", &FsLoader::new(&root));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let ModuleError::Load {
+            path,
+            import: Some((importer, _)),
+            error: LoadError::NotFound(_),
+        } = error
+        else {
+            panic!("expected a missing module, got {error:?}");
+        };
+        assert_eq!(path.as_str(), "lib/missing.ml");
+        assert_eq!(importer.as_str(), "lib/a.ml");
+    }
+
+    #[test]
+    fn reports_import_cycles_with_spans() {
+        let loader = MemoryLoader::new()
+            .with("main.ml", "import \"a.ml\" as a;")
+            .with("a.ml", "import \"b.ml\" as b;")
+            .with("b.ml", "let x = 1;\n{ import \"a.ml\" as a; a };");
+
+        let ModuleError::Cycle(cycle) = load_error("main.ml", &loader) else {
+            panic!("expected an import cycle");
+        };
+        let steps: Vec<_> = cycle
+            .iter()
+            .map(|s| (s.importer.as_str(), s.import.as_str()))
+            .collect();
+        assert_eq!(steps, [("a.ml", "b.ml"), ("b.ml", "a.ml")]);
+        let start = cycle[1].span.start();
+        assert_eq!((start.line, start.column), (1, 2));
+    }
+}
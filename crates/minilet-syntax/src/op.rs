@@ -1,46 +1,173 @@
+use crate::lexer::{scan, TokenKind};
+use crate::precedence::Fixity;
 use crate::spacing::Spacing;
 use crate::token::Token;
-use crate::util::any_char;
-use crate::{token, Parse, Span};
-use parcom::parsers::binary_expr::Operator;
+use crate::{token, Parse, ParseContext, Span, Spanned};
 use parcom::prelude::*;
 
 #[derive(Debug)]
-pub enum Op {
-    Add {
-        leading_spacing: Spacing,
-        token: token::Plus,
-        trailing_spacing: Spacing,
-    },
-    Sub {
-        leading_spacing: Spacing,
-        token: token::Minus,
-        trailing_spacing: Spacing,
-    },
-    Mul {
-        leading_spacing: Spacing,
-        token: token::Asterisk,
-        trailing_spacing: Spacing,
-    },
-    Div {
-        leading_spacing: Spacing,
-        token: token::Slash,
-        trailing_spacing: Spacing,
-    },
+pub struct Op {
+    /// Whitespace before the symbol, [empty](Spacing::empty) for an unspaced range such as `0..n`.
+    pub leading_spacing: Spacing,
+    pub kind: OpKind,
+    pub trailing_spacing: Spacing,
+    /// Fixity looked up from the [`crate::precedence::PrecedenceTable`] at parse time.
+    pub fixity: Fixity,
 }
 
-impl Operator for Op {
-    fn precedence(&self) -> usize {
+#[derive(Debug)]
+pub enum OpKind {
+    Add(token::Plus),
+    Sub(token::Minus),
+    Mul(token::Asterisk),
+    Div(token::Slash),
+    Rem(token::Percent),
+    Pow(token::StarStar),
+    BitAnd(token::Amp),
+    BitOr(token::Pipe),
+    BitXor(token::Caret),
+    Shl(token::Shl),
+    Shr(token::Shr),
+    Eq(token::EqEq),
+    Ne(token::Ne),
+    Lt(token::Lt),
+    Gt(token::Gt),
+    Le(token::Le),
+    Ge(token::Ge),
+    Range(token::DotDot),
+    RangeInclusive(token::DotDotEq),
+    /// Operator without builtin meaning, declared in the precedence table.
+    Custom(OpSymbol),
+}
+
+#[derive(Debug)]
+pub struct OpSymbol {
+    pub text: String,
+    pub span: Span,
+}
+
+impl Spanned for OpSymbol {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
+impl Spanned for Op {
+    fn span(&self) -> Span {
+        self.leading_spacing
+            .span()
+            .to(&self.trailing_spacing.span())
+    }
+}
+
+impl Spanned for OpKind {
+    fn span(&self) -> Span {
         match self {
-            Op::Add { .. } => 1,
-            Op::Sub { .. } => 1,
-            Op::Mul { .. } => 2,
-            Op::Div { .. } => 2,
+            OpKind::Add(t) => t.span(),
+            OpKind::Sub(t) => t.span(),
+            OpKind::Mul(t) => t.span(),
+            OpKind::Div(t) => t.span(),
+            OpKind::Rem(t) => t.span(),
+            OpKind::Pow(t) => t.span(),
+            OpKind::BitAnd(t) => t.span(),
+            OpKind::BitOr(t) => t.span(),
+            OpKind::BitXor(t) => t.span(),
+            OpKind::Shl(t) => t.span(),
+            OpKind::Shr(t) => t.span(),
+            OpKind::Eq(t) => t.span(),
+            OpKind::Ne(t) => t.span(),
+            OpKind::Lt(t) => t.span(),
+            OpKind::Gt(t) => t.span(),
+            OpKind::Le(t) => t.span(),
+            OpKind::Ge(t) => t.span(),
+            OpKind::Range(t) => t.span(),
+            OpKind::RangeInclusive(t) => t.span(),
+            OpKind::Custom(s) => s.span.clone(),
         }
     }
+}
+
+impl OpKind {
+    fn from_symbol(symbol: OpSymbol) -> Self {
+        let span = symbol.span.clone();
+        let mut chars = symbol.text.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            match c {
+                token::Plus::TOKEN => return OpKind::Add(token::Plus::from_span(span)),
+                token::Minus::TOKEN => return OpKind::Sub(token::Minus::from_span(span)),
+                token::Asterisk::TOKEN => return OpKind::Mul(token::Asterisk::from_span(span)),
+                token::Slash::TOKEN => return OpKind::Div(token::Slash::from_span(span)),
+                token::Percent::TOKEN => return OpKind::Rem(token::Percent::from_span(span)),
+                token::Amp::TOKEN => return OpKind::BitAnd(token::Amp::from_span(span)),
+                token::Pipe::TOKEN => return OpKind::BitOr(token::Pipe::from_span(span)),
+                token::Caret::TOKEN => return OpKind::BitXor(token::Caret::from_span(span)),
+                token::Lt::TOKEN => return OpKind::Lt(token::Lt::from_span(span)),
+                token::Gt::TOKEN => return OpKind::Gt(token::Gt::from_span(span)),
+                _ => (),
+            }
+        }
 
-    fn associativity(&self) -> parcom::parsers::binary_expr::Associativity {
-        parcom::parsers::binary_expr::Associativity::Left
+        match symbol.text.as_str() {
+            token::StarStar::TOKEN => OpKind::Pow(token::StarStar::from_span(span)),
+            token::Shl::TOKEN => OpKind::Shl(token::Shl::from_span(span)),
+            token::Shr::TOKEN => OpKind::Shr(token::Shr::from_span(span)),
+            token::EqEq::TOKEN => OpKind::Eq(token::EqEq::from_span(span)),
+            token::Ne::TOKEN => OpKind::Ne(token::Ne::from_span(span)),
+            token::Le::TOKEN => OpKind::Le(token::Le::from_span(span)),
+            token::Ge::TOKEN => OpKind::Ge(token::Ge::from_span(span)),
+            token::DotDot::TOKEN => OpKind::Range(token::DotDot::from_span(span)),
+            token::DotDotEq::TOKEN => OpKind::RangeInclusive(token::DotDotEq::from_span(span)),
+            _ => OpKind::Custom(symbol),
+        }
+    }
+}
+
+/// Characters that may form an operator symbol.
+pub(crate) fn is_symbol_char(c: char) -> bool {
+    matches!(
+        c,
+        '+' | '-'
+            | '*'
+            | '/'
+            | '%'
+            | '<'
+            | '>'
+            | '='
+            | '!'
+            | '&'
+            | '|'
+            | '^'
+            | '~'
+            | '.'
+            | ':'
+            | '?'
+            | '@'
+            | '$'
+    )
+}
+
+impl Parse for OpSymbol {
+    type Error = ParseOpSymbolError;
+    type Fatal = Never;
+
+    async fn parse<S: crate::InputStream>(
+        input: S,
+        _ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let start = input.position();
+        let anchor = input.anchor();
+        let (kind, text, rest) = Box::pin(scan(input)).await;
+        // `//` starts a comment rather than an operator.
+        if kind != TokenKind::Symbol || text.starts_with("//") {
+            let span = Span::points(start);
+            return Fail(
+                ParseOpSymbolError::Missing { span },
+                rest.rewind(anchor).into(),
+            );
+        }
+
+        let span = Span::new(start, rest.position());
+        Done(Self { text, span }, rest)
     }
 }
 
@@ -50,28 +177,44 @@ impl Parse for Op {
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        ctx: &ParseContext,
     ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let start = input.metrics();
+        let start = input.position();
+
+        let anchor = input.anchor();
+        let (leading_spacing, rest) = match Spacing::parse(input, ctx).await {
+            Done(v, r) => (Some(v), r),
+            Fail(_, r) => (None, r.rewind(anchor)),
+            Fatal(e, _) => e.never(),
+        };
 
-        let (leading_spacing, rest) = match Spacing::parse(input).await {
+        let (symbol, rest) = match OpSymbol::parse(rest, ctx).await {
             Done(v, r) => (v, r),
-            Fail(_, r) => {
+            Fail(ParseOpSymbolError::Missing { .. }, r) if leading_spacing.is_none() => {
                 return Fail(ParseOpError::MissingLeadingSpace(Span::points(start)), r);
             }
+            Fail(ParseOpSymbolError::Missing { span }, r) => {
+                return Fail(ParseOpError::NoSymbol(span), r)
+            }
             Fatal(e, _) => e.never(),
         };
 
-        let just_op = rest.metrics();
-        let span = Span::points(just_op);
-        let (op_char, rest) = match any_char().parse(rest).await {
-            Done(v, r) => (v, r),
-            Fail(_, r) => return Fail(ParseOpError::NoSymbol(span), r),
-            Fatal(e, _) => e.never(),
+        let leading_spacing = match leading_spacing {
+            Some(v) => v,
+            None if may_be_unspaced(&symbol.text) => Spacing::empty(start),
+            None => {
+                let span = Span::points(start);
+                return Fail(ParseOpError::MissingLeadingSpace(span), rest.into());
+            }
         };
 
-        let before_trailing_space = rest.metrics();
-        let (trailing_spacing, rest) = match Spacing::parse(rest).await {
+        let before_trailing_space = rest.position();
+        let anchor = rest.anchor();
+        let (trailing_spacing, rest) = match Spacing::parse(rest, ctx).await {
             Done(v, r) => (v, r),
+            Fail(_, r) if may_be_unspaced(&symbol.text) => {
+                (Spacing::empty(before_trailing_space), r.rewind(anchor))
+            }
             Fail(_, r) => {
                 return Fail(
                     ParseOpError::MissingTrailingSpace(Span::points(before_trailing_space)),
@@ -81,37 +224,24 @@ impl Parse for Op {
             Fatal(e, _) => e.never(),
         };
 
-        let op = match op_char {
-            token::Plus::TOKEN => Op::Add {
-                leading_spacing,
-                token: token::Plus::from_span(span),
-                trailing_spacing,
-            },
-            token::Minus::TOKEN => Op::Sub {
-                leading_spacing,
-                token: token::Minus::from_span(span),
-                trailing_spacing,
-            },
-            token::Asterisk::TOKEN => Op::Mul {
-                leading_spacing,
-                token: token::Asterisk::from_span(span),
-                trailing_spacing,
-            },
-            token::Slash::TOKEN => Op::Div {
-                leading_spacing,
-                token: token::Slash::from_span(span),
-                trailing_spacing,
-            },
-            _ => {
-                return Fail(
-                    ParseOpError::UnknownSymbol(Span::points(start)),
-                    rest.into(),
-                )
-            }
+        let Some(fixity) = ctx.precedence().get(&symbol.text) else {
+            return Fail(ParseOpError::UnknownSymbol(symbol.span), rest.into());
+        };
+
+        let op = Op {
+            leading_spacing,
+            kind: OpKind::from_symbol(symbol),
+            trailing_spacing,
+            fixity,
         };
         Done(op, rest)
     }
 }
+/// Range operators may be written without surrounding spaces, as in `0..n`.
+fn may_be_unspaced(symbol: &str) -> bool {
+    symbol == token::DotDot::TOKEN || symbol == token::DotDotEq::TOKEN
+}
+
 #[derive(Debug)]
 pub enum ParseOpError {
     MissingLeadingSpace(Span),
@@ -119,3 +249,8 @@ pub enum ParseOpError {
     NoSymbol(Span),
     UnknownSymbol(Span),
 }
+
+#[derive(Debug)]
+pub enum ParseOpSymbolError {
+    Missing { span: Span },
+}
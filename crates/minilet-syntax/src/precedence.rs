@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+// Builtin precedence levels follow Rust, with `**` binding tighter than multiplication.
+
+/// Precedence of range operators (`..`, `..=`).
+pub const RANGE: usize = 1;
+/// Precedence of comparison operators (`==`, `!=`, `<`, `>`, `<=`, `>=`).
+pub const COMPARISON: usize = 2;
+/// Precedence of bitwise or (`|`).
+pub const BIT_OR: usize = 3;
+/// Precedence of bitwise xor (`^`).
+pub const BIT_XOR: usize = 4;
+/// Precedence of bitwise and (`&`).
+pub const BIT_AND: usize = 5;
+/// Precedence of shift operators (`<<`, `>>`).
+pub const SHIFT: usize = 6;
+/// Precedence of additive operators (`+`, `-`).
+pub const ADDITIVE: usize = 7;
+/// Precedence of multiplicative operators (`*`, `/`, `%`).
+pub const MULTIPLICATIVE: usize = 8;
+/// Precedence of exponentiation (`**`).
+pub const POWER: usize = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+    /// Chaining (e.g. `a < b < c`) is a parse error.
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixity {
+    pub precedence: usize,
+    pub associativity: Associativity,
+}
+
+impl Fixity {
+    pub fn new(precedence: usize, associativity: Associativity) -> Self {
+        Self {
+            precedence,
+            associativity,
+        }
+    }
+
+    pub fn left(precedence: usize) -> Self {
+        Self::new(precedence, Associativity::Left)
+    }
+
+    pub fn right(precedence: usize) -> Self {
+        Self::new(precedence, Associativity::Right)
+    }
+
+    pub fn non(precedence: usize) -> Self {
+        Self::new(precedence, Associativity::None)
+    }
+}
+
+/// Maps infix operator symbols to their fixity.
+///
+/// Only symbols present in the table are accepted as binary operators by [`crate::op::Op`].
+/// The default table contains the builtin operators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrecedenceTable {
+    fixities: HashMap<String, Fixity>,
+}
+
+impl PrecedenceTable {
+    pub fn empty() -> Self {
+        Self {
+            fixities: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<Fixity> {
+        self.fixities.get(symbol).copied()
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.fixities.contains_key(symbol)
+    }
+
+    /// Sets the fixity of `symbol`, returning the previous one if any.
+    pub fn insert(&mut self, symbol: impl Into<String>, fixity: Fixity) -> Option<Fixity> {
+        self.fixities.insert(symbol.into(), fixity)
+    }
+
+    pub fn remove(&mut self, symbol: &str) -> Option<Fixity> {
+        self.fixities.remove(symbol)
+    }
+
+    pub fn with(mut self, symbol: impl Into<String>, fixity: Fixity) -> Self {
+        self.insert(symbol, fixity);
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Fixity)> {
+        self.fixities.iter().map(|(k, v)| (k.as_str(), *v))
+    }
+}
+
+impl Default for PrecedenceTable {
+    fn default() -> Self {
+        Self::empty()
+            .with("..", Fixity::non(RANGE))
+            .with("..=", Fixity::non(RANGE))
+            .with("==", Fixity::non(COMPARISON))
+            .with("!=", Fixity::non(COMPARISON))
+            .with("<", Fixity::non(COMPARISON))
+            .with(">", Fixity::non(COMPARISON))
+            .with("<=", Fixity::non(COMPARISON))
+            .with(">=", Fixity::non(COMPARISON))
+            .with("|", Fixity::left(BIT_OR))
+            .with("^", Fixity::left(BIT_XOR))
+            .with("&", Fixity::left(BIT_AND))
+            .with("<<", Fixity::left(SHIFT))
+            .with(">>", Fixity::left(SHIFT))
+            .with("+", Fixity::left(ADDITIVE))
+            .with("-", Fixity::left(ADDITIVE))
+            .with("*", Fixity::left(MULTIPLICATIVE))
+            .with("/", Fixity::left(MULTIPLICATIVE))
+            .with("%", Fixity::left(MULTIPLICATIVE))
+            .with("**", Fixity::right(POWER))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_builtin_levels_like_rust() {
+        let table = PrecedenceTable::default();
+        let levels: Vec<usize> = ["..", "==", "|", "^", "&", "<<", "+", "*", "**"]
+            .iter()
+            .map(|symbol| table.get(symbol).unwrap().precedence)
+            .collect();
+        assert!(levels.windows(2).all(|w| w[0] < w[1]), "{levels:?}");
+
+        assert_eq!(table.get("**"), Some(Fixity::right(POWER)));
+        assert_eq!(table.get("-"), Some(Fixity::left(ADDITIVE)));
+        assert_eq!(table.get("<="), Some(Fixity::non(COMPARISON)));
+        assert_eq!(table.get("..="), Some(Fixity::non(RANGE)));
+        assert_eq!(table.get("<+>"), None);
+    }
+
+    #[test]
+    fn inserts_and_removes_operators() {
+        let mut table = PrecedenceTable::empty();
+        assert!(!table.contains("+"));
+        assert_eq!(table.iter().count(), 0);
+
+        assert_eq!(table.insert("<+>", Fixity::left(ADDITIVE)), None);
+        assert_eq!(
+            table.insert("<+>", Fixity::right(SHIFT)),
+            Some(Fixity::left(ADDITIVE))
+        );
+        assert_eq!(table.get("<+>"), Some(Fixity::right(SHIFT)));
+
+        assert_eq!(table.remove("<+>"), Some(Fixity::right(SHIFT)));
+        assert!(!table.contains("<+>"));
+        assert_eq!(table.remove("<+>"), None);
+    }
+}
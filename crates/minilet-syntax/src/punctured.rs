@@ -1,4 +1,5 @@
 use crate::Parse;
+use crate::ParseContext;
 use parcom::prelude::*;
 
 #[derive(Debug)]
@@ -13,20 +14,39 @@ impl<T: Parse, P: Parse> Parse for Punctured<T, P> {
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        ctx: &ParseContext,
     ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        Self::parse_until_failure(input, ctx).await.0
+    }
+}
+
+impl<T: Parse, P: Parse> Punctured<T, P> {
+    /// Parses like [`Parse::parse`], also giving back the error of the item the list stopped at,
+    /// if an item failed where one could start.
+    pub(crate) async fn parse_until_failure<S: crate::InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> (
+        ParseResult<S, Self, Never, ParsePuncturedError<T, P>>,
+        Option<T::Error>,
+    ) {
         let mut lasts = Vec::new();
 
         let anchor = input.anchor();
-        let (first, mut rest) = match T::parse(input).await {
+        let (first, mut rest) = match T::parse(input, ctx).await {
             Done(v, r) => (v, r),
-            Fail(_, r) => return Done(Self { first: None, lasts }, r.rewind(anchor)),
-            Fatal(e, r) => return Fatal(ParsePuncturedError::Term(e), r),
+            Fail(e, r) => {
+                let me = Self { first: None, lasts };
+                return (Done(me, r.rewind(anchor)), Some(e));
+            }
+            Fatal(e, r) => return (Fatal(ParsePuncturedError::Term(e), r), None),
         };
 
+        let mut failure = None;
         loop {
             let anchor = rest.anchor();
 
-            let punct = match P::parse(rest).await {
+            let punct = match P::parse(rest, ctx).await {
                 Done(v, r) => {
                     rest = r;
                     v
@@ -35,19 +55,20 @@ impl<T: Parse, P: Parse> Parse for Punctured<T, P> {
                     rest = r.rewind(anchor);
                     break;
                 }
-                Fatal(e, r) => return Fatal(ParsePuncturedError::Punct(e), r),
+                Fatal(e, r) => return (Fatal(ParsePuncturedError::Punct(e), r), None),
             };
 
-            let term = match T::parse(rest).await {
+            let term = match T::parse(rest, ctx).await {
                 Done(v, r) => {
                     rest = r;
                     v
                 }
-                Fail(_, r) => {
+                Fail(e, r) => {
                     rest = r.rewind(anchor);
+                    failure = Some(e);
                     break;
                 }
-                Fatal(e, r) => return Fatal(ParsePuncturedError::Term(e), r),
+                Fatal(e, r) => return (Fatal(ParsePuncturedError::Term(e), r), None),
             };
 
             lasts.push((punct, term));
@@ -58,11 +79,33 @@ impl<T: Parse, P: Parse> Parse for Punctured<T, P> {
             lasts,
         };
 
-        Done(me, rest)
+        (Done(me, rest), failure)
+    }
+
+    pub fn from_parts(first: Option<T>, lasts: Vec<(P, T)>) -> Self {
+        Self { first, lasts }
+    }
+
+    /// The first item and the remaining items with their preceding separators.
+    pub fn parts(&self) -> (Option<&T>, &[(P, T)]) {
+        (self.first.as_ref(), &self.lasts)
+    }
+
+    pub fn parts_mut(&mut self) -> (Option<&mut T>, &mut [(P, T)]) {
+        (self.first.as_mut(), &mut self.lasts)
+    }
+
+    pub fn into_parts(self) -> (Option<T>, Vec<(P, T)>) {
+        (self.first, self.lasts)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        Iter {
+            first: self.first.as_ref(),
+            lasts: self.lasts.iter(),
+        }
     }
-}
 
-impl<T: Parse, P: Parse> Punctured<T, P> {
     pub fn first(&self) -> Option<&T> {
         self.first.as_ref()
     }
@@ -0,0 +1,135 @@
+//! Recovering parse mode.
+//!
+//! Instead of stopping at the first error, statements and tuples that fail to parse are replaced
+//! by [`ErrorNode`]s spanning the input skipped up to the next synchronisation point (`;`, `}` or
+//! `)`), and the errors are collected in the [`ParseContext`].
+
+use crate::{
+    expr::ParseExprError, stmt::ParseStmtError, term::ParseTermError, util::any_char, InputStream,
+    Parse, ParseContext, SourceFile, Span, Spanned, Stmts, Trivia,
+};
+use parcom::prelude::*;
+
+/// Placeholder for input that could not be parsed.
+#[derive(Debug)]
+pub struct ErrorNode {
+    pub text: String,
+    pub span: Span,
+}
+
+impl Spanned for ErrorNode {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
+#[derive(Debug)]
+pub enum SyntaxError {
+    Stmt(ParseStmtError),
+    Expr(ParseExprError),
+    Term(ParseTermError),
+    /// Input left over after a complete statement.
+    UnexpectedInput {
+        span: Span,
+    },
+}
+
+/// A partial syntax tree together with the errors found while building it.
+#[derive(Debug)]
+pub struct Recovered<T> {
+    pub value: T,
+    pub errors: Vec<SyntaxError>,
+}
+
+impl<T> Recovered<T> {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Parses the whole `input`, recovering from syntax errors.
+///
+/// All of `input` ends up in the returned tree, so printing it reproduces `input` exactly.
+pub async fn parse_recovering<S: InputStream>(
+    input: S,
+    ctx: &ParseContext,
+) -> Recovered<SourceFile> {
+    ctx.begin_recovery();
+
+    let (leading_trivia, rest) = match Trivia::parse(input, ctx).await {
+        Done(v, r) => (v, r),
+        Fail(e, _) | Fatal(e, _) => e.never(),
+    };
+    let (stmts, rest) = Stmts::parse_recovering(rest, ctx, true).await;
+    let trailing_trivia = match Trivia::parse(rest, ctx).await {
+        Done(v, _) => v,
+        Fail(e, _) | Fatal(e, _) => e.never(),
+    };
+
+    let errors = ctx.end_recovery();
+    let value = SourceFile {
+        leading_trivia,
+        stmts,
+        trailing_trivia,
+    };
+    Recovered { value, errors }
+}
+
+/// Skips input until a character for which `stop` returns `true` appears outside of any
+/// delimiters opened in the skipped input. `;` only stops the skip outside of braces.
+pub(crate) async fn skip_until<S: InputStream>(
+    input: S,
+    stop: impl Fn(char) -> bool,
+) -> (ErrorNode, S) {
+    let start = input.position();
+    let mut text = String::new();
+    let mut open: Vec<char> = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut in_comment = false;
+    let mut prev = None;
+
+    let mut rest = input;
+    let rest = loop {
+        let anchor = rest.anchor();
+        let (c, r) = match any_char().parse(rest).await {
+            Done(c, r) => (c, r),
+            Fail(_, r) => break r.rewind(anchor),
+            Fatal(e, _) => break e.never(),
+        };
+
+        if in_comment {
+            in_comment = c != '\n';
+        } else if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => (),
+            }
+        } else {
+            match c {
+                '"' => in_string = true,
+                '/' if prev == Some('/') => in_comment = true,
+                '(' | '{' => open.push(c),
+                ')' | '}' => {
+                    let opening = if c == ')' { '(' } else { '{' };
+                    match open.iter().rposition(|o| *o == opening) {
+                        Some(i) => open.truncate(i),
+                        None if stop(c) => break r.rewind(anchor),
+                        None => (),
+                    }
+                }
+                ';' if stop(c) && !open.contains(&'{') => break r.rewind(anchor),
+                _ => (),
+            }
+        }
+
+        text.push(c);
+        prev = Some(c);
+        rest = r;
+    };
+
+    let span = Span::new(start, rest.position());
+    (ErrorNode { text, span }, rest)
+}
@@ -1,4 +1,4 @@
-use crate::{InputStream, Parse, Trivia};
+use crate::{InputStream, Parse, ParseContext, Span, Spanned, Trivia};
 use parcom::prelude::*;
 
 #[derive(Debug)]
@@ -8,25 +8,34 @@ pub struct Relaxed<T: Parse> {
     pub trailing_trivia: Trivia,
 }
 
+impl<T: Parse> Spanned for Relaxed<T> {
+    fn span(&self) -> Span {
+        self.leading_trivia.span.to(&self.trailing_trivia.span)
+    }
+}
+
 impl<T: Parse> Parse for Relaxed<T> {
     type Error = T::Error;
     type Fatal = T::Fatal;
 
-    async fn parse<S: InputStream>(input: S) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let (leading_trivia, rest) = match Trivia::parse(input).await {
+    async fn parse<S: InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let (leading_trivia, rest) = match Trivia::parse(input, ctx).await {
             Done(v, r) => (v, r),
-            _ => unreachable!(),
+            Fail(e, _) | Fatal(e, _) => e.never(),
         };
 
-        let (item, rest) = match T::parse(rest).await {
+        let (item, rest) = match T::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, r) => return Fail(e, r),
             Fatal(e, r) => return Fatal(e, r),
         };
 
-        let (trailing_trivia, rest) = match Trivia::parse(rest).await {
+        let (trailing_trivia, rest) = match Trivia::parse(rest, ctx).await {
             Done(v, r) => (v, r),
-            _ => unreachable!(),
+            Fail(e, _) | Fatal(e, _) => e.never(),
         };
 
         let me = Self {
@@ -0,0 +1,51 @@
+use crate::{
+    stmts::ParseStmtsError, InputStream, Parse, ParseContext, Span, Spanned, Stmts, Trivia,
+};
+use parcom::prelude::*;
+
+/// Statements of a whole source file, with the surrounding whitespace.
+#[derive(Debug)]
+pub struct SourceFile {
+    pub leading_trivia: Trivia,
+    pub stmts: Stmts,
+    pub trailing_trivia: Trivia,
+}
+
+impl Spanned for SourceFile {
+    fn span(&self) -> Span {
+        self.leading_trivia.span.to(&self.trailing_trivia.span)
+    }
+}
+
+impl Parse for SourceFile {
+    type Error = Never;
+    type Fatal = ParseStmtsError;
+
+    async fn parse<S: InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let (leading_trivia, rest) = match Trivia::parse(input, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, _) | Fatal(e, _) => e.never(),
+        };
+
+        let (stmts, rest) = match Stmts::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, _) => e.never(),
+            Fatal(e, r) => return Fatal(e, r),
+        };
+
+        let (trailing_trivia, rest) = match Trivia::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, _) | Fatal(e, _) => e.never(),
+        };
+
+        let me = Self {
+            leading_trivia,
+            stmts,
+            trailing_trivia,
+        };
+        Done(me, rest)
+    }
+}
@@ -0,0 +1,188 @@
+//! Contents of parsed files, for turning spans back into text and converting between positions.
+use crate::span::{Position, Span};
+
+/// Identifies a file in a [`SourceMap`]. Streams measured without a file get the default id.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(u32);
+
+impl FileId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// 0-based line and column in UTF-16 code units, as used by the language server protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Utf16Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+#[derive(Debug)]
+struct File {
+    name: String,
+    text: String,
+    /// Byte offset of the start of every line.
+    line_starts: Vec<usize>,
+}
+
+impl File {
+    fn new(name: String, text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            name,
+            text,
+            line_starts,
+        }
+    }
+
+    /// Byte range of `line` without its line break.
+    fn line_range(&self, line: usize) -> Option<std::ops::Range<usize>> {
+        let start = *self.line_starts.get(line)?;
+        let end = match self.line_starts.get(line + 1) {
+            Some(next) => next - 1,
+            None => self.text.len(),
+        };
+        Some(start..end)
+    }
+
+    fn line_of(&self, byte: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= byte) - 1
+    }
+}
+
+/// Files added to a `SourceMap` are never removed, so their ids stay valid. Methods taking a
+/// [`FileId`] panic if it was not returned by [`SourceMap::add`] on the same map.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<File>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, text: impl Into<String>) -> FileId {
+        let id = FileId(self.files.len() as u32);
+        self.files.push(File::new(name.into(), text.into()));
+        id
+    }
+
+    pub fn name(&self, file: FileId) -> &str {
+        &self.file(file).name
+    }
+
+    pub fn text(&self, file: FileId) -> &str {
+        &self.file(file).text
+    }
+
+    /// Source text covered by `span`.
+    pub fn slice(&self, span: &Span) -> Option<&str> {
+        self.text(span.file()).get(span.bytes())
+    }
+
+    /// Text of `line` without its line break.
+    pub fn line_text(&self, file: FileId, line: usize) -> Option<&str> {
+        let file = self.file(file);
+        Some(&file.text[file.line_range(line)?])
+    }
+
+    /// Position of the byte offset `byte`, which must lie on a `char` boundary.
+    pub fn position(&self, file_id: FileId, byte: usize) -> Option<Position> {
+        let file = self.file(file_id);
+        let before = file.text.get(..byte)?;
+        let line = file.line_of(byte);
+        let line_before = &before[file.line_starts[line]..];
+        Some(Position {
+            file: file_id,
+            byte,
+            char: before.chars().count(),
+            line,
+            column: line_before.chars().count(),
+            utf16_column: line_before.encode_utf16().count(),
+        })
+    }
+
+    /// Byte offset of a line and `char` column, which may point just past the end of the line.
+    pub fn byte_offset(&self, file: FileId, line: usize, column: usize) -> Option<usize> {
+        let file = self.file(file);
+        let range = file.line_range(line)?;
+        let text = &file.text[range.clone()];
+        match text.char_indices().nth(column) {
+            Some((i, _)) => Some(range.start + i),
+            None if text.chars().count() == column => Some(range.end),
+            None => None,
+        }
+    }
+
+    pub fn utf16_position(&self, file: FileId, byte: usize) -> Option<Utf16Position> {
+        let file = self.file(file);
+        let before = file.text.get(..byte)?;
+        let line = file.line_of(byte);
+        Some(Utf16Position {
+            line,
+            character: before[file.line_starts[line]..].encode_utf16().count(),
+        })
+    }
+
+    /// Byte offset of a UTF-16 position, or `None` if it is outside the line or splits a
+    /// surrogate pair.
+    pub fn byte_offset_utf16(&self, file: FileId, position: Utf16Position) -> Option<usize> {
+        let file = self.file(file);
+        let range = file.line_range(position.line)?;
+        let mut units = 0;
+        for (i, c) in file.text[range.clone()].char_indices() {
+            if units == position.character {
+                return Some(range.start + i);
+            }
+            units += c.len_utf16();
+            if units > position.character {
+                return None;
+            }
+        }
+        (units == position.character).then_some(range.end)
+    }
+
+    fn file(&self, file: FileId) -> &File {
+        &self.files[file.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SourceMap, Utf16Position};
+
+    #[test]
+    fn converts_between_positions() {
+        let mut map = SourceMap::new();
+        map.add("empty.ml", "");
+        let file = map.add("main.ml", "let a = 1;\nlet é = \"😀\";\n");
+
+        let position = map.position(file, 20).unwrap();
+        assert_eq!(position.file, file);
+        assert_eq!((position.line, position.column, position.char), (1, 8, 19));
+        assert_eq!(map.byte_offset(file, 1, 8), Some(20));
+        assert_eq!(map.byte_offset(file, 1, 12), Some(27));
+        assert_eq!(map.byte_offset(file, 1, 13), None);
+        assert_eq!(map.line_text(file, 1), Some("let é = \"😀\";"));
+
+        let after_emoji = map.utf16_position(file, 25).unwrap();
+        assert_eq!(
+            after_emoji,
+            Utf16Position {
+                line: 1,
+                character: 11
+            }
+        );
+        assert_eq!(map.byte_offset_utf16(file, after_emoji), Some(25));
+        let inside_pair = Utf16Position {
+            line: 1,
+            character: 10,
+        };
+        assert_eq!(map.byte_offset_utf16(file, inside_pair), None);
+        assert!(map.position(file, 22).is_none());
+    }
+}
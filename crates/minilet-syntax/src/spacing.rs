@@ -1,5 +1,5 @@
 use crate::util::any_char;
-use crate::{Parse, Span};
+use crate::{Parse, ParseContext, Position, Span, Spanned};
 use parcom::prelude::*;
 use parcom::{Never, Parser};
 
@@ -9,22 +9,45 @@ pub struct Spacing {
     pub span: Span,
 }
 
+impl Spacing {
+    /// Spacing of no characters at `at`, where spacing is optional.
+    pub fn empty(at: Position) -> Self {
+        Self {
+            text: String::new(),
+            span: Span::points(at),
+        }
+    }
+}
+
+impl Spanned for Spacing {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
 impl Parse for Spacing {
     type Error = ParseSpacingError;
     type Fatal = Never;
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        _ctx: &ParseContext,
     ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let start = input.metrics();
+        let start = input.position();
         let mut buf = String::new();
         let mut rest = match any_char().parse(input).await {
             Done(c, r) if c.is_ascii_whitespace() => {
                 buf.push(c);
                 r
             }
-            Done(_, r) => return Fail(ParseSpacingError::MissingSpace, r.into()),
-            Fail(_, r) => return Fail(ParseSpacingError::MissingSpace, r),
+            Done(_, r) => {
+                let span = Span::points(start);
+                return Fail(ParseSpacingError::MissingSpace { span }, r.into());
+            }
+            Fail(_, r) => {
+                let span = Span::points(start);
+                return Fail(ParseSpacingError::MissingSpace { span }, r);
+            }
             Fatal(e, _) => e.never(),
         };
 
@@ -47,7 +70,7 @@ impl Parse for Spacing {
             };
         }
 
-        let end = rest.metrics();
+        let end = rest.position();
         let me = Self {
             text: buf,
             span: Span::new(start, end),
@@ -58,5 +81,5 @@ impl Parse for Spacing {
 
 #[derive(Debug)]
 pub enum ParseSpacingError {
-    MissingSpace,
+    MissingSpace { span: Span },
 }
@@ -1,25 +1,127 @@
-use parcom::metrics::LineColumn;
+use crate::source_map::FileId;
+use parcom::{Meter, Metrics};
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// A point in a source file, measured while parsing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Position {
+    pub file: FileId,
+    /// Offset in bytes from the start of the file.
+    pub byte: usize,
+    /// Offset in `char`s from the start of the file.
+    pub char: usize,
+    /// 0-based line.
+    pub line: usize,
+    /// 0-based column, in `char`s.
+    pub column: usize,
+    /// 0-based column, in UTF-16 code units.
+    pub utf16_column: usize,
+}
+
+impl Position {
+    /// Orders positions in the same file by how far into it they are.
+    ///
+    /// Positions from streams measured with [coarser metrics](crate::metrics) leave the fields
+    /// they do not measure at zero, so every field is compared in turn rather than relying on
+    /// any single one.
+    pub fn cmp_offset(&self, other: &Position) -> Ordering {
+        let key = |p: &Position| (p.byte, p.char, p.line, p.column, p.utf16_column);
+        key(self).cmp(&key(other))
+    }
+}
+
+impl Metrics<str> for Position {
+    type Meter = PositionMeter;
+}
+
+/// Meter producing [`Position`]s in one file.
+#[derive(Debug, Clone, Default)]
+pub struct PositionMeter(Position);
+
+impl PositionMeter {
+    pub fn new(file: FileId) -> Self {
+        Self(Position {
+            file,
+            ..Default::default()
+        })
+    }
+}
+
+impl Meter<str> for PositionMeter {
+    type Metrics = Position;
+
+    fn advance(mut self, segment: &str) -> Self {
+        self.0.byte += segment.len();
+        for c in segment.chars() {
+            self.0.char += 1;
+            if c == '\n' {
+                self.0.line += 1;
+                self.0.column = 0;
+                self.0.utf16_column = 0;
+            } else {
+                self.0.column += 1;
+                self.0.utf16_column += c.len_utf16();
+            }
+        }
+        self
+    }
+
+    fn metrics(&self) -> Position {
+        self.0.clone()
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Span {
-    start: LineColumn,
-    end: LineColumn,
+    start: Position,
+    end: Position,
 }
 
 impl Span {
-    pub fn new(start: LineColumn, end: LineColumn) -> Self {
+    pub fn new(start: Position, end: Position) -> Self {
         Self { start, end }
     }
 
-    pub fn points(point: LineColumn) -> Self {
+    pub fn points(point: Position) -> Self {
         Self::new(point.clone(), point)
     }
 
-    pub fn start(&self) -> &LineColumn {
+    pub fn start(&self) -> &Position {
         &self.start
     }
 
-    pub fn end(&self) -> &LineColumn {
+    pub fn end(&self) -> &Position {
         &self.end
     }
+
+    pub fn file(&self) -> FileId {
+        self.start.file
+    }
+
+    /// Byte range of the span, for slicing the source text. Empty unless the input was measured
+    /// in bytes, with [`Position`] or [`ByteOffset`](crate::metrics::ByteOffset).
+    pub fn bytes(&self) -> Range<usize> {
+        self.start.byte..self.end.byte
+    }
+
+    pub fn chars(&self) -> Range<usize> {
+        self.start.char..self.end.char
+    }
+
+    /// The span from the start of `self` to the end of `other`.
+    pub fn to(&self, other: &Span) -> Span {
+        Self::new(self.start.clone(), other.end.clone())
+    }
+}
+
+/// Syntax nodes that know the region of source they were parsed from.
+pub trait Spanned {
+    fn span(&self) -> Span;
+}
+
+impl<T: Spanned + ?Sized> Spanned for Box<T> {
+    fn span(&self) -> Span {
+        (**self).span()
+    }
 }
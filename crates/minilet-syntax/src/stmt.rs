@@ -1,13 +1,39 @@
 mod bind;
+mod fixity;
+mod import;
 
-use crate::{expr::ParseExprError, Expr, Parse};
-use bind::{ParseStmtLetError, StmtLet};
+use crate::{
+    expr::ParseExprError,
+    recovery::ErrorNode,
+    term::let_in::{LetIn, ParseLetInError},
+    Expr, Parse, ParseContext, Span, Spanned, Term,
+};
 use parcom::prelude::*;
 
+pub use bind::{Binding, ParseBindingError, ParseStmtLetError, StmtLet};
+pub use fixity::{FixityKeyword, ParseFixityKeywordError, ParseStmtFixityError, StmtFixity};
+pub use import::{ParseStmtImportError, StmtImport};
+
 #[derive(Debug)]
 pub enum Stmt {
     Let(StmtLet),
+    Fixity(StmtFixity),
+    Import(StmtImport),
     Expr(Expr),
+    /// Statement skipped by the recovering parser.
+    Error(ErrorNode),
+}
+
+impl Spanned for Stmt {
+    fn span(&self) -> Span {
+        match self {
+            Stmt::Let(v) => v.span(),
+            Stmt::Fixity(v) => v.span(),
+            Stmt::Import(v) => v.span(),
+            Stmt::Expr(v) => v.span(),
+            Stmt::Error(v) => v.span.clone(),
+        }
+    }
 }
 
 impl Parse for Stmt {
@@ -16,11 +42,40 @@ impl Parse for Stmt {
 
     async fn parse<S: crate::InputStream>(
         input: S,
-    ) -> parcom::ParseResult<S, Self, Self::Error, Self::Fatal> {
+        ctx: &ParseContext,
+    ) -> parcom::ParseResult<S, Self, <Self as Parse>::Error, <Self as Parse>::Fatal> {
+        let anchor = input.anchor();
+        let input = match StmtFixity::parse(input, ctx).await {
+            Done(v, r) => {
+                ctx.declare_fixity(v.symbol.text.clone(), v.fixity());
+                return Done(Stmt::Fixity(v), r);
+            }
+            Fail(_, r) => r.rewind(anchor),
+            Fatal(e, r) => {
+                return Fatal(ParseStmtError::Fixity(e), r);
+            }
+        };
+
+        let anchor = input.anchor();
+        let input = match StmtImport::parse(input, ctx).await {
+            Done(v, r) => {
+                return Done(Stmt::Import(v), r);
+            }
+            Fail(_, r) => r.rewind(anchor),
+            Fatal(e, r) => {
+                return Fatal(ParseStmtError::Import(e), r);
+            }
+        };
+
         let anchor = input.anchor();
-        let input = match StmtLet::parse(input).await {
+        let input = match StmtLet::parse(input, ctx).await {
             Done(v, r) => {
-                return Done(Stmt::Let(v), r);
+                let anchor = r.anchor();
+                return match LetIn::parse_body(v, r, ctx).await {
+                    Done(v, r) => Done(Stmt::Expr(Term::LetIn(Box::new(v)).into()), r),
+                    Fail((v, _), r) => Done(Stmt::Let(v), r.rewind(anchor)),
+                    Fatal(e, r) => Fatal(ParseStmtError::LetIn(e), r),
+                };
             }
             Fail(_, r) => r.rewind(anchor),
             Fatal(e, r) => {
@@ -28,7 +83,7 @@ impl Parse for Stmt {
             }
         };
 
-        match Expr::parse(input).await {
+        match Expr::parse(input, ctx).await {
             Done(v, r) => Done(Stmt::Expr(v), r),
             Fail(e, r) => Fail(ParseStmtError::Expr(e), r),
             Fatal(e, r) => Fatal(ParseStmtError::Expr(e), r),
@@ -40,4 +95,7 @@ impl Parse for Stmt {
 pub enum ParseStmtError {
     Expr(ParseExprError),
     Let(ParseStmtLetError),
+    LetIn(ParseLetInError),
+    Fixity(ParseStmtFixityError),
+    Import(ParseStmtImportError),
 }
@@ -1,9 +1,10 @@
 use crate::{
+    binder::{Binder, ParseBinderError},
     expr::ParseExprError,
+    recovery::{ErrorNode, SyntaxError},
     spacing::ParseSpacingError,
-    term::ident::ParseIdentError,
     token::{self, ParseTokenError},
-    Expr, Ident, Parse, Spacing, Trivia,
+    Expr, Parse, ParseContext, Spacing, Span, Spanned, Trivia,
 };
 use parcom::prelude::*;
 
@@ -11,21 +12,40 @@ use parcom::prelude::*;
 pub struct StmtLet {
     pub let_token: token::Let,
     pub let_spacing: Spacing,
-    pub ident: Ident,
-    pub ident_trivia: Trivia,
+    pub binding: Binding,
+}
+
+impl Spanned for StmtLet {
+    fn span(&self) -> Span {
+        self.let_token.span.to(&self.binding.span())
+    }
+}
+
+/// `<binder> = <expr>`, shared by `let` statements and `where` clauses.
+#[derive(Debug)]
+pub struct Binding {
+    pub binder: Binder,
+    pub binder_trivia: Trivia,
     pub eq: token::Eq,
     pub eq_trivia: Trivia,
     pub expr: Expr,
 }
 
+impl Spanned for Binding {
+    fn span(&self) -> Span {
+        self.binder.span().to(&self.expr.span())
+    }
+}
+
 impl Parse for StmtLet {
     type Error = ParseStmtLetError;
     type Fatal = ParseStmtLetError;
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        ctx: &ParseContext,
     ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let (let_token, rest) = match token::Let::parse(input).await {
+        let (let_token, rest) = match token::Let::parse(input, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, r) => {
                 return Fail(ParseStmtLetError::Let(e), r);
@@ -33,53 +53,88 @@ impl Parse for StmtLet {
             Fatal(e, _) => e.never(),
         };
 
-        let (let_spacing, rest) = match Spacing::parse(rest).await {
+        let (let_spacing, rest) = match Spacing::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, r) => {
-                return Fatal(ParseStmtLetError::Spacing(e), r);
+                return Fail(ParseStmtLetError::Spacing(e), r);
             }
             Fatal(e, _) => e.never(),
         };
 
-        let (ident, rest) = match Ident::parse(rest).await {
+        let (binding, rest) = match Binding::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) | Fatal(e, r) => {
+                return Fatal(ParseStmtLetError::Binding(e), r);
+            }
+        };
+
+        let me = Self {
+            let_token,
+            let_spacing,
+            binding,
+        };
+        Done(me, rest)
+    }
+}
+
+impl Parse for Binding {
+    type Error = ParseBindingError;
+    type Fatal = ParseBindingError;
+
+    async fn parse<S: crate::InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let (binder, rest) = match Binder::parse(input, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, r) => {
-                return Fatal(ParseStmtLetError::Ident(e), r);
+                return Fail(ParseBindingError::Binder(e), r);
+            }
+            Fatal(e, r) => {
+                return Fatal(ParseBindingError::Binder(e), r);
             }
-            Fatal(e, _) => e.never(),
         };
 
-        let (ident_trivia, rest) = match Trivia::parse(rest).await {
+        let (binder_trivia, rest) = match Trivia::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, _) | Fatal(e, _) => e.never(),
         };
 
-        let (eq, rest) = match token::Eq::parse(rest).await {
+        let (eq, rest) = match token::Eq::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, r) => {
-                return Fatal(ParseStmtLetError::Eq(e), r);
+                return Fatal(ParseBindingError::Eq(e), r);
             }
             Fatal(e, _) => e.never(),
         };
 
-        let (eq_trivia, rest) = match Trivia::parse(rest).await {
+        let (eq_trivia, rest) = match Trivia::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, _) | Fatal(e, _) => e.never(),
         };
 
-        let (expr, rest) = match Expr::parse(rest).await {
+        // boxed since `where` clauses parse bindings from within `Expr::parse`.
+        let anchor = rest.anchor();
+        let expr_start = rest.position();
+        let (expr, rest) = match Box::pin(Expr::parse(rest, ctx)).await {
             Done(v, r) => (v, r),
+            Fail(e, r) if ctx.is_recovering() => {
+                ctx.report(SyntaxError::Expr(e));
+                let node = ErrorNode {
+                    text: String::new(),
+                    span: Span::points(expr_start),
+                };
+                (Expr::Error(node), r.rewind(anchor))
+            }
             Fail(e, r) => {
-                return Fatal(ParseStmtLetError::Expr(e), r);
+                return Fatal(ParseBindingError::Expr(e), r);
             }
-            Fatal(e, r) => return Fatal(ParseStmtLetError::Expr(e), r),
+            Fatal(e, r) => return Fatal(ParseBindingError::Expr(e), r),
         };
 
         let me = Self {
-            let_token,
-            let_spacing,
-            ident,
-            ident_trivia,
+            binder,
+            binder_trivia,
             eq,
             eq_trivia,
             expr,
@@ -92,7 +147,12 @@ impl Parse for StmtLet {
 pub enum ParseStmtLetError {
     Let(ParseTokenError<token::Let>),
     Spacing(ParseSpacingError),
+    Binding(ParseBindingError),
+}
+
+#[derive(Debug)]
+pub enum ParseBindingError {
+    Binder(ParseBinderError),
     Eq(ParseTokenError<token::Eq>),
-    Ident(ParseIdentError),
     Expr(ParseExprError),
 }
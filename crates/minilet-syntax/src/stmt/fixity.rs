@@ -0,0 +1,160 @@
+use crate::{
+    literal::{integer::ParseIntegerLiteralError, IntegerLiteral},
+    op::{OpSymbol, ParseOpSymbolError},
+    precedence::{Associativity, Fixity},
+    spacing::ParseSpacingError,
+    token, InputStream, Parse, ParseContext, Spacing, Span, Spanned,
+};
+use parcom::prelude::*;
+
+/// Fixity declaration such as `infixl 6 <+>`.
+#[derive(Debug)]
+pub struct StmtFixity {
+    pub keyword: FixityKeyword,
+    pub keyword_spacing: Spacing,
+    pub precedence: IntegerLiteral,
+    pub precedence_spacing: Spacing,
+    pub symbol: OpSymbol,
+}
+
+impl Spanned for StmtFixity {
+    fn span(&self) -> Span {
+        self.keyword.span().to(&self.symbol.span)
+    }
+}
+
+#[derive(Debug)]
+pub enum FixityKeyword {
+    Infixl(token::Infixl),
+    Infixr(token::Infixr),
+    Infix(token::Infix),
+}
+
+impl FixityKeyword {
+    pub fn associativity(&self) -> Associativity {
+        match self {
+            FixityKeyword::Infixl(_) => Associativity::Left,
+            FixityKeyword::Infixr(_) => Associativity::Right,
+            FixityKeyword::Infix(_) => Associativity::None,
+        }
+    }
+}
+
+impl Spanned for FixityKeyword {
+    fn span(&self) -> Span {
+        match self {
+            FixityKeyword::Infixl(t) => t.span(),
+            FixityKeyword::Infixr(t) => t.span(),
+            FixityKeyword::Infix(t) => t.span(),
+        }
+    }
+}
+
+impl StmtFixity {
+    pub fn fixity(&self) -> Fixity {
+        Fixity::new(
+            self.precedence.number as usize,
+            self.keyword.associativity(),
+        )
+    }
+}
+
+impl Parse for FixityKeyword {
+    type Error = ParseFixityKeywordError;
+    type Fatal = Never;
+
+    async fn parse<S: InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let start = input.position();
+
+        let anchor = input.anchor();
+        let input = match token::Infixl::parse(input, ctx).await {
+            Done(v, r) => return Done(FixityKeyword::Infixl(v), r),
+            Fail(_, r) => r.rewind(anchor),
+            Fatal(e, _) => e.never(),
+        };
+
+        let anchor = input.anchor();
+        let input = match token::Infixr::parse(input, ctx).await {
+            Done(v, r) => return Done(FixityKeyword::Infixr(v), r),
+            Fail(_, r) => r.rewind(anchor),
+            Fatal(e, _) => e.never(),
+        };
+
+        match token::Infix::parse(input, ctx).await {
+            Done(v, r) => Done(FixityKeyword::Infix(v), r),
+            Fail(_, r) => Fail(
+                ParseFixityKeywordError::Missing {
+                    span: Span::points(start),
+                },
+                r,
+            ),
+            Fatal(e, _) => e.never(),
+        }
+    }
+}
+
+impl Parse for StmtFixity {
+    type Error = ParseStmtFixityError;
+    type Fatal = ParseStmtFixityError;
+
+    async fn parse<S: InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let (keyword, rest) = match FixityKeyword::parse(input, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseStmtFixityError::Keyword(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        // `infixl` and friends are ordinary identifiers unless a precedence follows.
+        let (keyword_spacing, rest) = match Spacing::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseStmtFixityError::Spacing(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (precedence, rest) = match IntegerLiteral::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseStmtFixityError::Precedence(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (precedence_spacing, rest) = match Spacing::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fatal(ParseStmtFixityError::Spacing(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (symbol, rest) = match OpSymbol::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fatal(ParseStmtFixityError::Symbol(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let me = Self {
+            keyword,
+            keyword_spacing,
+            precedence,
+            precedence_spacing,
+            symbol,
+        };
+        Done(me, rest)
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseFixityKeywordError {
+    Missing { span: Span },
+}
+
+#[derive(Debug)]
+pub enum ParseStmtFixityError {
+    Keyword(ParseFixityKeywordError),
+    Spacing(ParseSpacingError),
+    Precedence(ParseIntegerLiteralError),
+    Symbol(ParseOpSymbolError),
+}
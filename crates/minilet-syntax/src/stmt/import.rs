@@ -0,0 +1,102 @@
+use crate::{
+    literal::{string::ParseStringLiteralError, StringLiteral},
+    spacing::ParseSpacingError,
+    term::ident::ParseIdentError,
+    token::{self, ParseTokenError},
+    Ident, InputStream, Parse, ParseContext, Spacing, Span, Spanned,
+};
+use parcom::prelude::*;
+
+/// `import "path/to/lib.ml" as lib`
+#[derive(Debug)]
+pub struct StmtImport {
+    pub import_token: token::Import,
+    pub import_spacing: Spacing,
+    pub path: StringLiteral,
+    pub path_spacing: Spacing,
+    pub as_token: token::As,
+    pub as_spacing: Spacing,
+    pub alias: Ident,
+}
+
+impl Spanned for StmtImport {
+    fn span(&self) -> Span {
+        Span::new(
+            self.import_token.span.start().clone(),
+            self.alias.span.end().clone(),
+        )
+    }
+}
+
+impl Parse for StmtImport {
+    type Error = ParseStmtImportError;
+    type Fatal = ParseStmtImportError;
+
+    async fn parse<S: InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let (import_token, rest) = match token::Import::parse(input, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseStmtImportError::Import(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        // `import` is an ordinary identifier unless a path follows.
+        let (import_spacing, rest) = match Spacing::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseStmtImportError::Spacing(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (path, rest) = match StringLiteral::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseStmtImportError::Path(e), r),
+            Fatal(e, r) => return Fatal(ParseStmtImportError::Path(e), r),
+        };
+
+        let (path_spacing, rest) = match Spacing::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fatal(ParseStmtImportError::Spacing(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (as_token, rest) = match token::As::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fatal(ParseStmtImportError::As(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (as_spacing, rest) = match Spacing::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fatal(ParseStmtImportError::Spacing(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (alias, rest) = match Ident::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fatal(ParseStmtImportError::Alias(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let me = Self {
+            import_token,
+            import_spacing,
+            path,
+            path_spacing,
+            as_token,
+            as_spacing,
+            alias,
+        };
+        Done(me, rest)
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseStmtImportError {
+    Import(ParseTokenError<token::Import>),
+    Spacing(ParseSpacingError),
+    Path(ParseStringLiteralError),
+    As(ParseTokenError<token::As>),
+    Alias(ParseIdentError),
+}
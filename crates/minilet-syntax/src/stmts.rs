@@ -1,16 +1,26 @@
 use crate::{
     punctured::{ParsePuncturedError, Punctured},
+    recovery::{skip_until, ErrorNode, SyntaxError},
     relaxed::Relaxed,
     stmt::Stmt,
     token::{ParseTokenError, Semi},
-    Expr, InputStream, Parse, Trivia,
+    util::peek_char,
+    Expr, InputStream, Parse, ParseContext, Position, Span, Spanned, Trivia,
 };
 use parcom::prelude::*;
+use std::fmt::Debug;
 
 #[derive(Debug)]
 pub struct Stmts {
     pub stmts: Punctured<Stmt, Relaxed<Semi>>,
     pub trailing_semi: Option<(Trivia, Semi)>,
+    pub span: Span,
+}
+
+impl Spanned for Stmts {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
 }
 
 impl Stmts {
@@ -32,20 +42,90 @@ impl Parse for Stmts {
 
     async fn parse<S: InputStream>(
         input: S,
+        ctx: &ParseContext,
     ) -> parcom::ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let (stmts, rest) = match Punctured::parse(input).await {
+        if ctx.is_recovering() {
+            let (me, rest) = Self::parse_recovering(input, ctx, false).await;
+            return Done(me, rest);
+        }
+
+        let start = input.position();
+        // fixity declarations are scoped to the statements they appear in.
+        let outer_precedence = ctx.precedence().clone();
+        let result = Punctured::parse(input, ctx).await;
+        ctx.replace_precedence(outer_precedence);
+
+        let (stmts, rest) = match result {
             Done(v, r) => (v, r),
             Fail(e, _) => return e.never(),
             Fatal(e, r) => return Fatal(ParseStmtsError::Punctured(e), r),
         };
 
+        let (me, rest) = Self::parse_trailing_semi(start, stmts, rest, ctx).await;
+        Done(me, rest)
+    }
+}
+
+impl Stmts {
+    /// Parses statements, replacing the ones that fail to parse by [`Stmt::Error`].
+    ///
+    /// Stops at the end of input, or at a `}` unless `top_level` is set, where unmatched closing
+    /// delimiters are skipped as errors. The statement cache of `ctx` is asked for each statement
+    /// before parsing it.
+    pub(crate) async fn parse_recovering<S: InputStream>(
+        input: S,
+        ctx: &ParseContext,
+        top_level: bool,
+    ) -> (Self, S) {
+        let start = input.position();
+        let outer_precedence = ctx.precedence().clone();
+
+        let mut lasts = Vec::new();
+        let (first, mut rest) = next_stmt(input, ctx, top_level).await;
+        if first.is_some() {
+            loop {
+                let anchor = rest.anchor();
+                let (semi, r) = match Relaxed::<Semi>::parse(rest, ctx).await {
+                    Done(v, r) => (v, r),
+                    Fail(_, r) => {
+                        rest = r.rewind(anchor);
+                        break;
+                    }
+                    Fatal(e, _) => return e.never(),
+                };
+
+                match next_stmt(r, ctx, top_level).await {
+                    (Some(stmt), r) => {
+                        lasts.push((semi, stmt));
+                        rest = r;
+                    }
+                    (None, r) => {
+                        rest = r.rewind(anchor);
+                        break;
+                    }
+                }
+            }
+        }
+
+        ctx.replace_precedence(outer_precedence);
+
+        let stmts = Punctured::from_parts(first, lasts);
+        Self::parse_trailing_semi(start, stmts, rest, ctx).await
+    }
+
+    async fn parse_trailing_semi<S: InputStream>(
+        start: Position,
+        stmts: Punctured<Stmt, Relaxed<Semi>>,
+        rest: S,
+        ctx: &ParseContext,
+    ) -> (Self, S) {
         let anchor = rest.anchor();
-        let (trivia, rest) = match Trivia::parse(rest).await {
+        let (trivia, rest) = match Trivia::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, _) | Fatal(e, _) => e.never(),
         };
 
-        let (trailing_semi, rest) = match Semi::parse(rest).await {
+        let (trailing_semi, rest) = match Semi::parse(rest, ctx).await {
             Done(v, r) => (Some((trivia, v)), r),
             Fail(_, r) => (None, r.rewind(anchor)),
             Fatal(e, _) => e.never(),
@@ -54,12 +134,97 @@ impl Parse for Stmts {
         let me = Self {
             stmts,
             trailing_semi,
+            span: Span::new(start, rest.position()),
         };
 
-        Done(me, rest)
+        (me, rest)
+    }
+}
+
+/// Source of statements parsed before, used to skip parsing them again.
+pub(crate) trait StmtCache: Debug {
+    /// Returns the statement that parsing at `at`, in the state of `ctx`, would produce, applying
+    /// its effects on `ctx`.
+    fn take(&mut self, at: &Position, ctx: &ParseContext) -> Option<Stmt>;
+}
+
+/// Takes the next statement from the cache of `ctx`, or parses it with [`recover_stmt`].
+async fn next_stmt<S: InputStream>(
+    input: S,
+    ctx: &ParseContext,
+    top_level: bool,
+) -> (Option<Stmt>, S) {
+    match ctx.cached_stmt(&input.position()) {
+        Some(stmt) => {
+            let len = stmt.span().bytes().len();
+            (Some(stmt), input.advance(len).await)
+        }
+        None => recover_stmt(input, ctx, top_level).await,
     }
 }
 
+/// Parses a statement, skipping up to the next `;` on errors.
+///
+/// Returns `None` at the end of the enclosing statements.
+async fn recover_stmt<S: InputStream>(
+    input: S,
+    ctx: &ParseContext,
+    top_level: bool,
+) -> (Option<Stmt>, S) {
+    let is_end = |c: Option<char>| match c {
+        None => true,
+        Some('}') => !top_level,
+        _ => false,
+    };
+    let is_sync = |c: Option<char>| c == Some(';') || is_end(c);
+
+    let (next, input) = peek_char(input).await;
+    if is_end(next) {
+        return (None, input);
+    }
+
+    let anchor = input.anchor();
+    let reports = ctx.reports();
+    let input = match Stmt::parse(input, ctx).await {
+        Done(stmt, r) => {
+            let end = r.anchor();
+            let r = match Trivia::parse(r, ctx).await {
+                Done(_, r) => r,
+                Fail(e, _) | Fatal(e, _) => e.never(),
+            };
+
+            let (next, r) = peek_char(r).await;
+            if is_sync(next) {
+                return (Some(stmt), r.rewind(end));
+            }
+
+            let start = r.position();
+            let (_, r) = skip_stmt(r, top_level).await;
+            // an error the statement reported where the input is left over, or past it, says why
+            // the statement stopped short.
+            if !ctx.reported_since(reports, &start) {
+                let span = Span::new(start, r.position());
+                ctx.discard_reports(reports);
+                ctx.report(SyntaxError::UnexpectedInput { span });
+            }
+            r.rewind(anchor)
+        }
+        Fail(e, r) | Fatal(e, r) => {
+            ctx.discard_reports(reports);
+            ctx.report(SyntaxError::Stmt(e));
+            r.rewind(anchor)
+        }
+    };
+
+    let (node, rest) = skip_stmt(input, top_level).await;
+    (Some(Stmt::Error(node)), rest)
+}
+
+/// Skips to the next `;`, consuming unmatched `)` and, at top level, `}` on the way.
+async fn skip_stmt<S: InputStream>(input: S, top_level: bool) -> (ErrorNode, S) {
+    skip_until(input, |c| c == ';' || (c == '}' && !top_level)).await
+}
+
 #[derive(Debug)]
 pub enum ParseStmtsError {
     Punctured(ParsePuncturedError<Stmt, Relaxed<Semi>>),
@@ -81,19 +246,22 @@ impl Parse for StmtSeparator {
     type Error = ParseStmtSeparatorError;
     type Fatal = Never;
 
-    async fn parse<S: InputStream>(input: S) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let (leading_trivia, rest) = match Trivia::parse(input).await {
+    async fn parse<S: InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let (leading_trivia, rest) = match Trivia::parse(input, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, _) | Fatal(e, _) => e.never(),
         };
 
-        let (semi, rest) = match Semi::parse(rest).await {
+        let (semi, rest) = match Semi::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, r) => return Fail(ParseStmtSeparatorError { semi: e }, r),
             Fatal(e, _) => e.never(),
         };
 
-        let (trailing_trivia, rest) = match Trivia::parse(rest).await {
+        let (trailing_trivia, rest) = match Trivia::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, _) | Fatal(e, _) => e.never(),
         };
@@ -107,3 +275,122 @@ impl Parse for StmtSeparator {
         Done(me, rest)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        binder::Binder,
+        stmt::Stmt,
+        util::{grouping, peek_char},
+        Parse, ParseContext, Position, SourceFile,
+    };
+    use minilet_utils::stream::StrStream;
+    use parcom::{prelude::*, IntoMeasured};
+
+    /// Parses `source`, with or without recovery, returning the grouping of each expression
+    /// statement or `None` if it has errors.
+    fn groupings(source: &str, recovering: bool) -> Option<Vec<String>> {
+        let ctx = ParseContext::new();
+        let input = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            Default::default(),
+        );
+        let file = if recovering {
+            let recovered = pollster::block_on(crate::parse_recovering(input, &ctx));
+            recovered.is_ok().then_some(recovered.value)?
+        } else {
+            let (file, rest) = match pollster::block_on(SourceFile::parse(input, &ctx)) {
+                Done(v, r) => (v, r),
+                Fail(_, _) | Fatal(_, _) => return None,
+            };
+            pollster::block_on(peek_char(rest))
+                .0
+                .is_none()
+                .then_some(file)?
+        };
+
+        let exprs = file.stmts.stmts.iter().filter_map(|stmt| match stmt {
+            Stmt::Expr(expr) => Some(grouping(expr)),
+            _ => None,
+        });
+        Some(exprs.collect())
+    }
+
+    fn assert_groupings(source: &str, expected: &[&str]) {
+        let expected: Vec<String> = expected.iter().map(|e| e.to_string()).collect();
+        for recovering in [false, true] {
+            let actual = groupings(source, recovering);
+            assert_eq!(
+                actual.as_ref(),
+                Some(&expected),
+                "{source:?}, recovering: {recovering}"
+            );
+        }
+    }
+
+    fn assert_rejected(source: &str) {
+        for recovering in [false, true] {
+            assert_eq!(groupings(source, recovering), None, "{source:?}");
+        }
+    }
+
+    #[test]
+    fn declares_operators_with_their_associativity() {
+        assert_groupings(
+            "infixl 6 <+>; infixr 6 <->; infix 4 ==.; a <+> b <+> c; a <-> b <-> c; a ==. b <+> c",
+            &[
+                "((a <+> b) <+> c)",
+                "(a <-> (b <-> c))",
+                "(a ==. (b <+> c))",
+            ],
+        );
+        assert_groupings("infixl 9 <+>; 1 * 2 <+> 3", &["(1 * (2 <+> 3))"]);
+        assert_rejected("infix 4 ==.; a ==. b ==. c");
+        assert_rejected("a <+> b");
+    }
+
+    #[test]
+    fn redeclares_builtin_operators() {
+        assert_groupings("infixr 6 -; a - b - c", &["(a - (b - c))"]);
+    }
+
+    #[test]
+    fn restores_fixities_after_a_block() {
+        assert_groupings(
+            "infixl 6 <+>; { infixl 9 <+>; infixl 1 <*>; a * b <+> c }; a * b <+> c",
+            &[
+                "{ infixl 9 <+>; infixl 1 <*>; a * b <+> c }",
+                "((a * b) <+> c)",
+            ],
+        );
+        assert_rejected("{ infixl 1 <*>; a <*> b }; a <*> b");
+    }
+
+    #[test]
+    fn binds_operators_by_name() {
+        let source = "infixl 6 <+>; let (<+>) = add; let (<*>) = mul; 1 <+> 2";
+        assert_groupings(source, &["(1 <+> 2)"]);
+
+        let ctx = ParseContext::new();
+        let input = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            Default::default(),
+        );
+        let Done(file, _) = pollster::block_on(SourceFile::parse(input, &ctx)) else {
+            panic!("{source:?} should parse");
+        };
+        let symbols: Vec<&str> = file
+            .stmts
+            .stmts
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::Let(stmt) => match &stmt.binding.binder {
+                    Binder::Op(op) => Some(op.symbol.text.as_str()),
+                    Binder::Ident(_) => None,
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(symbols, ["<+>", "<*>"]);
+    }
+}
@@ -0,0 +1,590 @@
+//! Lossless syntax trees for editor features.
+//!
+//! A green tree is an immutable tree of [`GreenNode`]s and [`GreenToken`]s that stores kinds,
+//! texts and lengths only, so its subtrees can be shared and cloned cheaply. A red tree of
+//! [`SyntaxNode`]s is a cursor over a green tree computing absolute offsets and parents on the
+//! way down. [`ast`] wraps red nodes in typed views.
+//!
+//! Green trees are built from the syntax tree of a parse with [`GreenNode::from_source_file`].
+//! Every byte of the source belongs to exactly one token, so the text of the root node is the
+//! source text.
+pub mod ast;
+
+use crate::{
+    binder::OpBinder,
+    expr::BinOp,
+    literal::{IntegerLiteral, StringLiteral},
+    op::{is_symbol_char, Op, OpSymbol},
+    recovery::ErrorNode,
+    stmt::{Binding, StmtFixity, StmtImport, StmtLet},
+    term::{app::App, block::Block, field::Field, for_loop::For, let_in::LetIn, Where},
+    token::AnyToken,
+    unary_op::UnaryOp,
+    visit::{self, Visit},
+    Ident, SourceFile, Spacing, Span, Spanned, Stmts, Trivia, Tuple, Unary,
+};
+use std::{fmt, ops::Range, rc::Rc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    // nodes
+    SourceFile,
+    Stmts,
+    StmtLet,
+    Binding,
+    StmtFixity,
+    StmtImport,
+    OpBinder,
+    BinExpr,
+    Op,
+    Tuple,
+    Block,
+    Unary,
+    App,
+    For,
+    Field,
+    LetIn,
+    Where,
+    // tokens
+    Trivia,
+    Spacing,
+    Ident,
+    Integer,
+    String,
+    Keyword,
+    /// Operator symbols, including `=` and `.`.
+    Symbol,
+    /// Delimiters, `,` and `;`.
+    Punct,
+    /// Input skipped by the recovering parser.
+    Error,
+}
+
+impl SyntaxKind {
+    pub fn is_token(self) -> bool {
+        self as u8 >= SyntaxKind::Trivia as u8
+    }
+
+    pub fn is_trivia(self) -> bool {
+        matches!(self, SyntaxKind::Trivia | SyntaxKind::Spacing)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct GreenNodeData {
+    kind: SyntaxKind,
+    len: usize,
+    children: Vec<GreenElement>,
+}
+
+/// Immutable node of a green tree. Cloning it only clones a reference.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GreenNode(Rc<GreenNodeData>);
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let len = children.iter().map(GreenElement::len).sum();
+        Self(Rc::new(GreenNodeData {
+            kind,
+            len,
+            children,
+        }))
+    }
+
+    /// Builds the green tree of `file`, which must have been parsed from `source`.
+    pub fn from_source_file(file: &SourceFile, source: &str) -> Self {
+        let mut builder = Builder {
+            source,
+            stack: vec![(SyntaxKind::SourceFile, Vec::new())],
+        };
+        builder.visit_source_file(file);
+        let (kind, children) = builder.stack.pop().unwrap();
+        Self::new(kind, children)
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind
+    }
+
+    /// Length of the text of the node in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.0.children
+    }
+}
+
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.children().iter().try_for_each(|child| match child {
+            GreenElement::Node(node) => node.fmt(f),
+            GreenElement::Token(token) => f.write_str(token.text()),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct GreenTokenData {
+    kind: SyntaxKind,
+    text: String,
+}
+
+/// Immutable leaf of a green tree. Cloning it only clones a reference.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GreenToken(Rc<GreenTokenData>);
+
+impl GreenToken {
+    pub fn new(kind: SyntaxKind, text: impl Into<String>) -> Self {
+        Self(Rc::new(GreenTokenData {
+            kind,
+            text: text.into(),
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.0.text
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GreenElement {
+    Node(GreenNode),
+    Token(GreenToken),
+}
+
+impl GreenElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            GreenElement::Node(n) => n.kind(),
+            GreenElement::Token(t) => t.kind(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(n) => n.len(),
+            GreenElement::Token(t) => t.text().len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Collects the tokens of a syntax tree into green nodes.
+struct Builder<'a> {
+    source: &'a str,
+    /// Nodes being built, with the children finished so far.
+    stack: Vec<(SyntaxKind, Vec<GreenElement>)>,
+}
+
+impl Builder<'_> {
+    fn node(&mut self, kind: SyntaxKind, walk: impl FnOnce(&mut Self)) {
+        self.stack.push((kind, Vec::new()));
+        walk(self);
+        let (kind, children) = self.stack.pop().unwrap();
+        self.push(GreenElement::Node(GreenNode::new(kind, children)));
+    }
+
+    fn token(&mut self, kind: SyntaxKind, span: Span) {
+        let text = &self.source[span.bytes()];
+        if !text.is_empty() {
+            self.push(GreenElement::Token(GreenToken::new(kind, text)));
+        }
+    }
+
+    fn push(&mut self, element: GreenElement) {
+        self.stack.last_mut().unwrap().1.push(element);
+    }
+}
+
+impl Visit for Builder<'_> {
+    fn visit_stmts(&mut self, node: &Stmts) {
+        self.node(SyntaxKind::Stmts, |b| visit::walk_stmts(b, node));
+    }
+
+    fn visit_stmt_let(&mut self, node: &StmtLet) {
+        self.node(SyntaxKind::StmtLet, |b| visit::walk_stmt_let(b, node));
+    }
+
+    fn visit_binding(&mut self, node: &Binding) {
+        self.node(SyntaxKind::Binding, |b| visit::walk_binding(b, node));
+    }
+
+    fn visit_stmt_fixity(&mut self, node: &StmtFixity) {
+        self.node(SyntaxKind::StmtFixity, |b| visit::walk_stmt_fixity(b, node));
+    }
+
+    fn visit_stmt_import(&mut self, node: &StmtImport) {
+        self.node(SyntaxKind::StmtImport, |b| visit::walk_stmt_import(b, node));
+    }
+
+    fn visit_op_binder(&mut self, node: &OpBinder) {
+        self.node(SyntaxKind::OpBinder, |b| visit::walk_op_binder(b, node));
+    }
+
+    fn visit_bin_op(&mut self, node: &BinOp) {
+        self.node(SyntaxKind::BinExpr, |b| visit::walk_bin_op(b, node));
+    }
+
+    fn visit_op(&mut self, node: &Op) {
+        self.node(SyntaxKind::Op, |b| visit::walk_op(b, node));
+    }
+
+    fn visit_tuple(&mut self, node: &Tuple) {
+        self.node(SyntaxKind::Tuple, |b| visit::walk_tuple(b, node));
+    }
+
+    fn visit_block(&mut self, node: &Block) {
+        self.node(SyntaxKind::Block, |b| visit::walk_block(b, node));
+    }
+
+    fn visit_unary(&mut self, node: &Unary) {
+        self.node(SyntaxKind::Unary, |b| visit::walk_unary(b, node));
+    }
+
+    fn visit_app(&mut self, node: &App) {
+        self.node(SyntaxKind::App, |b| visit::walk_app(b, node));
+    }
+
+    fn visit_for(&mut self, node: &For) {
+        self.node(SyntaxKind::For, |b| visit::walk_for(b, node));
+    }
+
+    fn visit_field(&mut self, node: &Field) {
+        self.node(SyntaxKind::Field, |b| visit::walk_field(b, node));
+    }
+
+    fn visit_let_in(&mut self, node: &LetIn) {
+        self.node(SyntaxKind::LetIn, |b| visit::walk_let_in(b, node));
+    }
+
+    fn visit_where(&mut self, node: &Where) {
+        self.node(SyntaxKind::Where, |b| visit::walk_where(b, node));
+    }
+
+    fn visit_integer_literal(&mut self, node: &IntegerLiteral) {
+        self.token(SyntaxKind::Integer, node.span());
+    }
+
+    fn visit_string_literal(&mut self, node: &StringLiteral) {
+        self.token(SyntaxKind::String, node.span());
+    }
+
+    fn visit_ident(&mut self, node: &Ident) {
+        self.token(SyntaxKind::Ident, node.span());
+    }
+
+    fn visit_op_symbol(&mut self, node: &OpSymbol) {
+        self.token(SyntaxKind::Symbol, node.span());
+    }
+
+    fn visit_unary_op(&mut self, node: &UnaryOp) {
+        self.token(SyntaxKind::Symbol, node.span());
+    }
+
+    fn visit_error_node(&mut self, node: &ErrorNode) {
+        self.token(SyntaxKind::Error, node.span());
+    }
+
+    fn visit_trivia(&mut self, node: &Trivia) {
+        self.token(SyntaxKind::Trivia, node.span());
+    }
+
+    fn visit_spacing(&mut self, node: &Spacing) {
+        self.token(SyntaxKind::Spacing, node.span());
+    }
+
+    fn visit_token(&mut self, node: &dyn AnyToken) {
+        let span = node.span();
+        let kind = match self.source[span.bytes()].chars().next() {
+            Some(c) if c.is_ascii_alphabetic() => SyntaxKind::Keyword,
+            Some(c) if is_symbol_char(c) => SyntaxKind::Symbol,
+            _ => SyntaxKind::Punct,
+        };
+        self.token(kind, span);
+    }
+}
+
+#[derive(Debug)]
+struct NodeData {
+    green: GreenNode,
+    parent: Option<SyntaxNode>,
+    offset: usize,
+}
+
+/// Node of a red tree: a green node together with its position and parent.
+#[derive(Debug, Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.green().0, &other.green().0) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl SyntaxNode {
+    pub fn new_root(green: GreenNode) -> Self {
+        Self(Rc::new(NodeData {
+            green,
+            parent: None,
+            offset: 0,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.green().kind()
+    }
+
+    pub fn green(&self) -> &GreenNode {
+        &self.0.green
+    }
+
+    /// Byte range of the node in the source.
+    pub fn text_range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.green().len()
+    }
+
+    pub fn text(&self) -> String {
+        self.green().to_string()
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    /// The node itself followed by its parents up to the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(Some(self.clone()), SyntaxNode::parent)
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.children_with_tokens()
+            .filter_map(SyntaxElement::into_node)
+    }
+
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+        let mut offset = self.0.offset;
+        self.green()
+            .children()
+            .iter()
+            .enumerate()
+            .map(move |(index, child)| {
+                let element = self.child(index, child, offset);
+                offset += child.len();
+                element
+            })
+    }
+
+    pub fn first_token(&self) -> Option<SyntaxToken> {
+        match self.children_with_tokens().next()? {
+            SyntaxElement::Node(n) => n.first_token(),
+            SyntaxElement::Token(t) => Some(t),
+        }
+    }
+
+    /// The innermost element containing the byte at `offset`. An offset at the end of the node
+    /// gives its last token.
+    ///
+    /// Panics if `offset` is outside of the node.
+    pub fn covering_element(&self, offset: usize) -> SyntaxElement {
+        let range = self.text_range();
+        assert!(
+            range.contains(&offset) || offset == range.end,
+            "offset {offset} outside of {range:?}"
+        );
+
+        let mut node = self.clone();
+        loop {
+            let last = node.children_with_tokens().last();
+            let child = node
+                .children_with_tokens()
+                .find(|child| child.text_range().contains(&offset))
+                .or(last);
+            match child {
+                Some(SyntaxElement::Node(child)) => node = child,
+                Some(token @ SyntaxElement::Token(_)) => return token,
+                None => return SyntaxElement::Node(node),
+            }
+        }
+    }
+
+    fn child(&self, index: usize, green: &GreenElement, offset: usize) -> SyntaxElement {
+        match green {
+            GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                green: green.clone(),
+                parent: Some(self.clone()),
+                offset,
+            }))),
+            GreenElement::Token(_) => SyntaxElement::Token(SyntaxToken {
+                parent: self.clone(),
+                index,
+                offset,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.green().fmt(f)
+    }
+}
+
+/// Leaf of a red tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxToken {
+    parent: SyntaxNode,
+    index: usize,
+    offset: usize,
+}
+
+impl SyntaxToken {
+    pub fn green(&self) -> &GreenToken {
+        match &self.parent.green().children()[self.index] {
+            GreenElement::Token(t) => t,
+            GreenElement::Node(_) => unreachable!("tokens are created for green tokens only"),
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.green().kind()
+    }
+
+    pub fn text(&self) -> &str {
+        self.green().text()
+    }
+
+    pub fn text_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.text().len()
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            SyntaxElement::Node(n) => n.kind(),
+            SyntaxElement::Token(t) => t.kind(),
+        }
+    }
+
+    pub fn text_range(&self) -> Range<usize> {
+        match self {
+            SyntaxElement::Node(n) => n.text_range(),
+            SyntaxElement::Token(t) => t.text_range(),
+        }
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        match self {
+            SyntaxElement::Node(n) => n.parent(),
+            SyntaxElement::Token(t) => Some(t.parent()),
+        }
+    }
+
+    pub fn into_node(self) -> Option<SyntaxNode> {
+        match self {
+            SyntaxElement::Node(n) => Some(n),
+            SyntaxElement::Token(_) => None,
+        }
+    }
+
+    pub fn into_token(self) -> Option<SyntaxToken> {
+        match self {
+            SyntaxElement::Node(_) => None,
+            SyntaxElement::Token(t) => Some(t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ast, ast::AstNode, GreenNode, SyntaxElement, SyntaxKind, SyntaxNode};
+    use crate::{parse_recovering, ParseContext, Position};
+    use minilet_utils::stream::StrStream;
+    use parcom::IntoMeasured;
+
+    fn tree(source: &str) -> SyntaxNode {
+        let ctx = ParseContext::new();
+        let input = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            Default::default(),
+        );
+        let file = pollster::block_on(parse_recovering(input, &ctx)).value;
+        SyntaxNode::new_root(GreenNode::from_source_file(&file, source))
+    }
+
+    #[test]
+    fn is_lossless() {
+        let source = " let x = f(1, \"a\") + -2; // note\nfor i in 0 .. n { g(i); }; let = ;";
+        let root = tree(source);
+        assert_eq!(root.text(), source);
+        assert_eq!(root.text_range(), 0..source.len());
+    }
+
+    #[test]
+    fn walks_from_offset_to_parents() {
+        let source = "let y = { a; f(b + 1) };";
+        let root = tree(source);
+
+        let SyntaxElement::Token(token) = root.covering_element(source.find('b').unwrap()) else {
+            panic!("expected a token");
+        };
+        assert_eq!((token.kind(), token.text()), (SyntaxKind::Ident, "b"));
+
+        let kinds: Vec<_> = token.parent().ancestors().map(|n| n.kind()).collect();
+        assert_eq!(
+            kinds,
+            [
+                SyntaxKind::BinExpr,
+                SyntaxKind::Tuple,
+                SyntaxKind::App,
+                SyntaxKind::Stmts,
+                SyntaxKind::Block,
+                SyntaxKind::Binding,
+                SyntaxKind::StmtLet,
+                SyntaxKind::Stmts,
+                SyntaxKind::SourceFile,
+            ]
+        );
+
+        let bin = token
+            .parent()
+            .ancestors()
+            .find_map(ast::BinExpr::cast)
+            .unwrap();
+        assert_eq!(bin.op().map(|t| t.text().to_string()), Some("+".into()));
+        assert_eq!(bin.rhs().map(|e| e.text_range()), Some(19..20));
+        assert_eq!(bin.syntax().text_range(), 15..20);
+
+        let app = bin.syntax().ancestors().find_map(ast::App::cast).unwrap();
+        assert_eq!(app.arg().unwrap().items().count(), 1);
+        assert_eq!(
+            root.covering_element(source.len()).kind(),
+            SyntaxKind::Punct
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Typed views of red tree nodes.
+//!
+//! Each view wraps a [`SyntaxNode`] of one kind. Accessors skip trivia and return `None` for
+//! parts missing from an erroneous source. Leaves such as identifiers and literals are tokens, so
+//! accessors for arbitrary expressions return [`SyntaxElement`]s.
+use super::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
+pub trait AstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+
+    fn cast(node: SyntaxNode) -> Option<Self>;
+
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+macro_rules! ast_nodes {
+    ($($name: ident),* $(,)?) => {
+        $(
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct $name(SyntaxNode);
+
+            impl AstNode for $name {
+                fn can_cast(kind: SyntaxKind) -> bool {
+                    kind == SyntaxKind::$name
+                }
+
+                fn cast(node: SyntaxNode) -> Option<Self> {
+                    Self::can_cast(node.kind()).then(|| Self(node))
+                }
+
+                fn syntax(&self) -> &SyntaxNode {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+ast_nodes![
+    SourceFile, Stmts, StmtLet, Binding, StmtFixity, StmtImport, OpBinder, BinExpr, Op, Tuple,
+    Block, Unary, App, For, Field, LetIn, Where,
+];
+
+/// Children of `node` other than trivia.
+fn elements(node: &SyntaxNode) -> impl Iterator<Item = SyntaxElement> + '_ {
+    node.children_with_tokens()
+        .filter(|e| !e.kind().is_trivia())
+}
+
+fn child<N: AstNode>(node: &SyntaxNode) -> Option<N> {
+    node.children().find_map(N::cast)
+}
+
+fn token(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(SyntaxElement::into_token)
+        .find(|t| t.kind() == kind)
+}
+
+/// Elements separated by `,` or `;`.
+fn items(node: &SyntaxNode) -> impl Iterator<Item = SyntaxElement> + '_ {
+    elements(node).filter(|e| e.kind() != SyntaxKind::Punct)
+}
+
+impl SourceFile {
+    pub fn stmts(&self) -> Option<Stmts> {
+        child(&self.0)
+    }
+}
+
+impl Stmts {
+    pub fn stmts(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+        items(&self.0)
+    }
+}
+
+impl StmtLet {
+    pub fn binding(&self) -> Option<Binding> {
+        child(&self.0)
+    }
+}
+
+impl Binding {
+    /// Identifier token or [`OpBinder`] node being bound.
+    pub fn binder(&self) -> Option<SyntaxElement> {
+        elements(&self.0).next()
+    }
+
+    pub fn expr(&self) -> Option<SyntaxElement> {
+        elements(&self.0).nth(2)
+    }
+}
+
+impl StmtFixity {
+    pub fn precedence(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Integer)
+    }
+
+    pub fn symbol(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+}
+
+impl StmtImport {
+    pub fn path(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::String)
+    }
+
+    pub fn alias(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
+
+impl OpBinder {
+    pub fn symbol(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+}
+
+impl BinExpr {
+    pub fn lhs(&self) -> Option<SyntaxElement> {
+        elements(&self.0).next()
+    }
+
+    pub fn op(&self) -> Option<SyntaxToken> {
+        child::<Op>(&self.0)?.symbol()
+    }
+
+    pub fn rhs(&self) -> Option<SyntaxElement> {
+        elements(&self.0).nth(2)
+    }
+}
+
+impl Op {
+    pub fn symbol(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+}
+
+impl Tuple {
+    pub fn items(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+        items(&self.0)
+    }
+}
+
+impl Block {
+    pub fn stmts(&self) -> Option<Stmts> {
+        child(&self.0)
+    }
+}
+
+impl Unary {
+    pub fn op(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Symbol)
+    }
+
+    pub fn operand(&self) -> Option<SyntaxElement> {
+        elements(&self.0).nth(1)
+    }
+}
+
+impl App {
+    pub fn receiver(&self) -> Option<SyntaxElement> {
+        elements(&self.0).next()
+    }
+
+    pub fn arg(&self) -> Option<Tuple> {
+        elements(&self.0).last()?.into_node().and_then(Tuple::cast)
+    }
+}
+
+impl For {
+    pub fn binder(&self) -> Option<SyntaxElement> {
+        elements(&self.0).nth(1)
+    }
+
+    pub fn iter(&self) -> Option<SyntaxElement> {
+        elements(&self.0).nth(3)
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        elements(&self.0).last()?.into_node().and_then(Block::cast)
+    }
+}
+
+impl Field {
+    pub fn receiver(&self) -> Option<SyntaxElement> {
+        elements(&self.0).next()
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        elements(&self.0).last()?.into_token()
+    }
+}
+
+impl LetIn {
+    pub fn binding(&self) -> Option<StmtLet> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<SyntaxElement> {
+        elements(&self.0).nth(2)
+    }
+}
+
+impl Where {
+    pub fn expr(&self) -> Option<SyntaxElement> {
+        elements(&self.0).next()
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = Binding> + '_ {
+        self.0.children().filter_map(Binding::cast)
+    }
+}
@@ -1,15 +1,28 @@
 pub mod app;
 pub mod block;
+pub mod field;
+pub mod for_loop;
 pub mod ident;
+pub mod let_in;
 pub mod tuple;
 pub mod unary;
+pub mod where_clause;
 
 use crate::{
+    expected::{Expected, Found},
     literal::{Literal, ParseLiteralError},
-    InputStream, Parse,
+    op::is_symbol_char,
+    recovery::ErrorNode,
+    token,
+    util::{peek_char, GrowStack},
+    InputStream, Parse, ParseContext, Span, Spanned,
 };
 use app::App;
 use block::{Block, ParseBlockError};
+use field::Field;
+use for_loop::{For, ParseForError};
+use ident::ParseIdentError;
+use let_in::{LetIn, ParseLetInError};
 use parcom::{
     ParseResult::{self, Done, Fail, Fatal},
     ShouldNeverExtension,
@@ -21,6 +34,7 @@ use unary::ParseUnaryError;
 pub use ident::Ident;
 pub use tuple::Tuple;
 pub use unary::Unary;
+pub use where_clause::Where;
 
 // todo: add fn call
 
@@ -32,6 +46,30 @@ pub enum Term {
     Unary(Box<Unary>),
     Block(Block),
     App(Box<App>),
+    For(Box<For>),
+    Field(Box<Field>),
+    LetIn(Box<LetIn>),
+    Where(Box<Where>),
+    /// Term skipped by the recovering parser.
+    Error(ErrorNode),
+}
+
+impl Spanned for Term {
+    fn span(&self) -> Span {
+        match self {
+            Term::Tuple(v) => v.span(),
+            Term::Literal(v) => v.span(),
+            Term::Ident(v) => v.span.clone(),
+            Term::Unary(v) => v.span(),
+            Term::Block(v) => v.span(),
+            Term::App(v) => v.span(),
+            Term::For(v) => v.span(),
+            Term::Field(v) => v.span(),
+            Term::LetIn(v) => v.span(),
+            Term::Where(v) => v.span(),
+            Term::Error(v) => v.span.clone(),
+        }
+    }
 }
 
 impl Parse for Term {
@@ -40,97 +78,377 @@ impl Parse for Term {
 
     fn parse<S: InputStream>(
         input: S,
+        ctx: &ParseContext,
     ) -> impl Future<Output = ParseResult<S, Self, <Self as Parse>::Error, <Self as Parse>::Fatal>>
     {
-        Box::pin(async {
-            let anchor = input.anchor();
-            let input = match Unary::parse(input).await {
-                Done(v, r) => {
-                    let me = Self::Unary(Box::new(v));
-                    return Done(me, r);
+        GrowStack(Box::pin(async move {
+            let Some(_depth) = ctx.enter() else {
+                let span = Span::points(input.position());
+                return Fatal(ParseTermError::TooDeep { span }, input.into());
+            };
+
+            // alternatives are only tried when their first character matches; the others would
+            // fail without consuming input.
+            let (first, input) = peek_char(input).await;
+            let input = if first.is_some_and(is_symbol_char) {
+                let anchor = input.anchor();
+                match Unary::parse(input, ctx).await {
+                    Done(v, r) => {
+                        let me = Self::Unary(Box::new(v));
+                        return Done(me, r);
+                    }
+                    // the operand's error says more than failing again on the operator would.
+                    Fail(ParseUnaryError::Term(e), r) => return Fail(e, r),
+                    Fail(_, r) => r.rewind(anchor),
+                    Fatal(e, r) => return Fatal(ParseTermError::Unary(Box::new(e)), r),
                 }
-                Fail(_, r) => r.rewind(anchor),
-                Fatal(e, r) => return Fatal(ParseTermError::Unary(Box::new(e)), r),
+            } else {
+                input
             };
 
-            let (mut receiver, mut rest) = match parse_atom(input).await {
+            let (mut receiver, mut rest) = match parse_atom(input, first, ctx).await {
                 Done(v, r) => (v, r),
                 e @ _ => return e,
             };
 
             loop {
-                let anchor = rest.anchor();
-                match Tuple::parse(rest).await {
-                    Done(arg, r) => {
-                        rest = r;
-                        receiver = Term::App(Box::new(App { receiver, arg }))
+                let (next, input) = peek_char(rest).await;
+                if next == Some('(') {
+                    let anchor = input.anchor();
+                    match Tuple::parse(input, ctx).await {
+                        Done(arg, r) => {
+                            rest = r;
+                            receiver = Term::App(Box::new(App { receiver, arg }));
+                            continue;
+                        }
+                        Fail(_, r) => {
+                            rest = r.rewind(anchor);
+                            break;
+                        }
+                        Fatal(e, r) => return Fatal(ParseTermError::Tuple(e), r),
                     }
+                }
+
+                let anchor = input.anchor();
+                let (dot, input) = match token::Dot::parse(input, ctx).await {
+                    Done(v, r) => (v, r),
                     Fail(_, r) => {
                         rest = r.rewind(anchor);
                         break;
                     }
-                    Fatal(e, r) => return Fatal(ParseTermError::Tuple(e), r),
+                    Fatal(e, _) => e.never(),
+                };
+
+                // a `.` followed by a symbol starts an operator such as `..`, not a field.
+                let (after_dot, input) = peek_char(input).await;
+                if after_dot.is_some_and(is_symbol_char) {
+                    rest = input.rewind(anchor);
+                    break;
+                }
+
+                match Ident::parse(input, ctx).await {
+                    Done(name, r) => {
+                        rest = r;
+                        receiver = Term::Field(Box::new(Field {
+                            receiver,
+                            dot,
+                            name,
+                        }));
+                    }
+                    Fail(e, r) => return Fatal(ParseTermError::Field(e), r),
+                    Fatal(e, _) => return e.never(),
                 }
             }
 
             Done(receiver, rest)
-        })
+        }))
     }
 }
 
+/// Parses a term without postfix calls and fields. `first` is the next character of `input`.
 async fn parse_atom<S: InputStream>(
     input: S,
+    first: Option<char>,
+    ctx: &ParseContext,
 ) -> ParseResult<S, Term, ParseTermError, ParseTermError> {
-    let anchor = input.anchor();
-    let input = match Block::parse(input).await {
-        Done(v, r) => {
-            let me = Term::Block(v);
-            return Done(me, r);
+    let start = input.position();
+    let start_anchor = input.anchor();
+
+    let input = if first == Some('{') {
+        let anchor = input.anchor();
+        match Block::parse(input, ctx).await {
+            Done(v, r) => {
+                let me = Term::Block(v);
+                return Done(me, r);
+            }
+            Fail(_, r) => r.rewind(anchor),
+            Fatal(e, r) => {
+                return Fatal(ParseTermError::Block(e), r);
+            }
         }
+    } else {
+        input
+    };
 
-        Fail(_, r) => r.rewind(anchor),
-        Fatal(e, r) => {
-            return Fatal(ParseTermError::Block(e), r);
+    let input = if first == Some('(') {
+        let anchor = input.anchor();
+        match Tuple::parse(input, ctx).await {
+            Done(v, r) => {
+                let me = Term::Tuple(v);
+                return Done(me, r);
+            }
+            Fail(_, r) => r.rewind(anchor),
+            Fatal(e, r) => {
+                return Fatal(ParseTermError::Tuple(e), r);
+            }
         }
+    } else {
+        input
     };
 
-    let anchor = input.anchor();
-    let input = match Tuple::parse(input).await {
-        Done(v, r) => {
-            let me = Term::Tuple(v);
-            return Done(me, r);
+    // `let` and `for` are covered by the identifier alternative in error messages.
+    let input = if first == Some('l') {
+        let anchor = input.anchor();
+        match LetIn::parse(input, ctx).await {
+            Done(v, r) => {
+                let me = Term::LetIn(Box::new(v));
+                return Done(me, r);
+            }
+            Fail(_, r) => r.rewind(anchor),
+            Fatal(e, r) => {
+                return Fatal(ParseTermError::LetIn(Box::new(e)), r);
+            }
         }
+    } else {
+        input
+    };
 
-        Fail(_, r) => r.rewind(anchor),
-        Fatal(e, r) => {
-            return Fatal(ParseTermError::Tuple(e), r);
+    let input = if first == Some('f') {
+        let anchor = input.anchor();
+        match For::parse(input, ctx).await {
+            Done(v, r) => {
+                let me = Term::For(Box::new(v));
+                return Done(me, r);
+            }
+            Fail(_, r) => r.rewind(anchor),
+            Fatal(e, r) => {
+                return Fatal(ParseTermError::For(Box::new(e)), r);
+            }
         }
+    } else {
+        input
     };
 
     let anchor = input.anchor();
-    let input = match Ident::parse(input).await {
+    let (expected, input) = match Ident::parse(input, ctx).await {
         Done(v, r) => {
             let me = Term::Ident(v);
             return Done(me, r);
         }
-        Fail(_, r) => r.rewind(anchor),
+        Fail(e, r) => {
+            // built only now that every alternative has failed, as most terms parse. Prefix
+            // operators are read by `Term::parse` before it gets here, but may start a term too.
+            let at = || Span::points(start.clone());
+            let expected = Expected::token::<token::LBrace>(at())
+                .merge(Expected::token::<token::LParen>(at()))
+                .merge(Expected::token::<token::Plus>(at()))
+                .merge(Expected::token::<token::Minus>(at()))
+                .merge(Expected::token::<token::Tilde>(at()))
+                .merge(Expected::from(&e));
+            (expected, r.rewind(anchor))
+        }
         Fatal(e, _) => e.never(),
     };
 
-    match Literal::parse(input).await {
+    let (expected, rest) = match Literal::parse(input, ctx).await {
         Done(v, r) => {
             let me = Term::Literal(v);
-            Done(me, r)
+            return Done(me, r);
         }
-        Fail(e, r) => Fail(ParseTermError::Literal(e), r),
-        Fatal(e, r) => Fatal(ParseTermError::Literal(e), r),
+        // a malformed literal such as `0x` says more than the alternatives expected here.
+        Fail(e, r) if e.is_malformed() => return Fail(ParseTermError::Literal(e), r),
+        Fail(e, r) => {
+            let literal = Expected::construct(e.integer.span(), "literal");
+            (expected.merge(literal), r.rewind(start_anchor))
+        }
+        Fatal(e, r) => return Fatal(ParseTermError::Literal(e), r),
+    };
+
+    if expected.span.start().cmp_offset(&start).is_ne() {
+        return Fail(ParseTermError::Expected(expected), rest.into());
     }
+
+    let (found, rest) = peek_char(rest).await;
+    let found = match found {
+        Some(c) => Found::Char(c),
+        None => Found::EndOfInput,
+    };
+    Fail(
+        ParseTermError::Expected(expected.with_found(found)),
+        rest.into(),
+    )
 }
 
 #[derive(Debug)]
 pub enum ParseTermError {
+    /// None of the alternatives for a term matched.
+    Expected(Expected),
     Unary(Box<ParseUnaryError>),
     Tuple(ParseTupleError),
     Literal(ParseLiteralError),
     Block(ParseBlockError),
+    For(Box<ParseForError>),
+    Field(ParseIdentError),
+    LetIn(Box<ParseLetInError>),
+    /// Terms nested deeper than [`ParseContext::max_depth`].
+    TooDeep {
+        span: Span,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        diagnostics::ToDiagnostic, parse_expr, parse_program, util::Rng, Expr, Parse, ParseContext,
+        Position, DEFAULT_MAX_DEPTH,
+    };
+    use minilet_utils::stream::StrStream;
+    use parcom::{prelude::*, IntoMeasured};
+
+    const TOO_DEEP: &str = "expression nested too deeply";
+
+    fn nested(open: &str, close: &str, depth: usize) -> String {
+        format!("{}1{}", open.repeat(depth), close.repeat(depth))
+    }
+
+    /// Parses `source` as an expression, returning the message of its error if any.
+    fn error_with(source: &str, ctx: &ParseContext) -> Option<String> {
+        let input = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            Default::default(),
+        );
+        match pollster::block_on(Expr::parse(input, ctx)) {
+            Done(_, _) => None,
+            Fail(e, _) => Some(e.to_diagnostic().message),
+            Fatal(e, _) => Some(e.to_diagnostic().message),
+        }
+    }
+
+    #[test]
+    fn lists_every_way_to_start_a_term() {
+        let ctx = ParseContext::new();
+        let expected = "expected `(`, `+`, `-`, `{`, `~`, identifier or literal, found `*`";
+        assert_eq!(error_with("*", &ctx).as_deref(), Some(expected));
+        assert_eq!(error_with("-*", &ctx).as_deref(), Some(expected));
+        assert_eq!(
+            parse_program("let x = ;").unwrap_err().to_string(),
+            "1:9: error: expected `(`, `+`, `-`, `{`, `~`, identifier or literal, found `;`\n"
+        );
+    }
+
+    #[test]
+    fn limits_nesting_to_max_depth() {
+        let ctx = ParseContext::new().with_max_depth(3);
+        assert_eq!(error_with(&nested("(", ")", 2), &ctx), None);
+        assert_eq!(
+            error_with(&nested("(", ")", 3), &ctx).as_deref(),
+            Some(TOO_DEEP)
+        );
+        assert_eq!(
+            error_with(&nested("-(", ")", 3), &ctx).as_deref(),
+            Some(TOO_DEEP)
+        );
+
+        // the depth is released once a term is parsed, so siblings do not add up.
+        assert_eq!(error_with("((1), (1), 1)", &ctx), None);
+    }
+
+    /// Openers and closers of nested terms, with how many terms each level nests.
+    const SHAPES: &[(&str, &str, usize)] = &[
+        ("(", ")", 1),
+        ("{", "}", 1),
+        ("f(", ")", 1),
+        ("(let x = 1 in ", ")", 2),
+        ("-", "", 1),
+        ("~", "", 1),
+        ("-(", ")", 2),
+        ("{ a; (", ") }", 2),
+    ];
+
+    /// Pathological nesting is reported instead of overflowing the stack. Runs on the default
+    /// test thread stack, as parsing grows the stack as it nests.
+    #[test]
+    fn reports_deep_nesting() {
+        for &(open, close, _) in SHAPES {
+            let source = nested(open, close, 10_000);
+            let diagnostics = parse_expr(&source).unwrap_err();
+            assert!(
+                diagnostics.iter().any(|d| d.message == TOO_DEEP),
+                "{open:?}: {diagnostics}"
+            );
+
+            let diagnostics = parse_program(&source).unwrap_err();
+            assert!(
+                diagnostics.iter().any(|d| d.message == TOO_DEEP),
+                "{open:?}: {diagnostics}"
+            );
+
+            let source = nested(open, close, 20);
+            assert!(parse_expr(&source).is_ok(), "{source:?}");
+        }
+
+        // unclosed openers, as typed before their closing delimiters. Each repetition nests three
+        // terms; every level skips the rest of the input while recovering, so it is kept short.
+        let source = "(f({".repeat(DEFAULT_MAX_DEPTH / 2);
+        assert!(parse_program(&source).is_err());
+    }
+
+    #[test]
+    fn parses_up_to_the_limit_on_small_stacks() {
+        let source = nested("(", ")", DEFAULT_MAX_DEPTH - 1);
+        let parsed = std::thread::Builder::new()
+            .stack_size(256 << 10)
+            .spawn(move || parse_expr(&source).is_ok())
+            .unwrap()
+            .join()
+            .unwrap();
+        assert!(parsed);
+    }
+
+    /// Each level of blocks, bindings, calls and tuples below takes about three terms, so a
+    /// hundred of them nest about three hundred deep, within the default limit.
+    #[test]
+    fn parses_deeply_nested_programs() {
+        let depth = 100;
+        let mut source = String::new();
+        for i in 0..depth {
+            source.push_str(&format!("{{ let x{i} = f((-x, "));
+        }
+        source.push('1');
+        for i in (0..depth).rev() {
+            source.push_str(&format!("), 2) + 3 where y = 4; g(x{i}).z }}"));
+        }
+        source.push(';');
+        assert!(parse_program(&source).is_ok(), "{source}");
+    }
+
+    /// Mixed shapes nest as deeply as repeated ones.
+    #[test]
+    fn reports_generated_deep_nesting() {
+        const MAX_DEPTH: usize = 64;
+        let ctx = ParseContext::new().with_max_depth(MAX_DEPTH);
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..200 {
+            let depth = rng.below(2 * MAX_DEPTH);
+            let shapes: Vec<_> = (0..depth).map(|_| *rng.pick(SHAPES)).collect();
+            let mut source: String = shapes.iter().map(|(open, _, _)| *open).collect();
+            source.push('1');
+            source.extend(shapes.iter().rev().map(|(_, close, _)| *close));
+
+            let levels: usize = shapes.iter().map(|(_, _, levels)| levels).sum();
+            let too_deep = error_with(&source, &ctx).as_deref() == Some(TOO_DEEP);
+            assert_eq!(too_deep, levels >= MAX_DEPTH, "{source:?}");
+        }
+    }
 }
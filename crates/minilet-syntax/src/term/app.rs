@@ -1,7 +1,14 @@
 use super::{Term, Tuple};
+use crate::{Span, Spanned};
 
 #[derive(Debug)]
 pub struct App {
     pub receiver: Term,
     pub arg: Tuple,
 }
+
+impl Spanned for App {
+    fn span(&self) -> Span {
+        self.receiver.span().to(&self.arg.span())
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     stmts::{ParseStmtsError, Stmts},
     token::{LBrace, ParseTokenError, RBrace},
-    Parse, Trivia,
+    Parse, ParseContext, Span, Spanned, Trivia,
 };
 use parcom::prelude::*;
 
@@ -14,38 +14,53 @@ pub struct Block {
     pub rbrace: RBrace,
 }
 
+impl Spanned for Block {
+    fn span(&self) -> Span {
+        self.lbrace.span.to(&self.rbrace.span)
+    }
+}
+
 impl Parse for Block {
     type Error = ParseBlockError;
     type Fatal = ParseBlockError;
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        ctx: &ParseContext,
     ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let (lbrace, rest) = match LBrace::parse(input).await {
+        let (lbrace, rest) = match LBrace::parse(input, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, r) => return Fail(ParseBlockError::MissingOpeningBrace(e), r),
             Fatal(e, _) => e.never(),
         };
 
-        let (ltrivia, rest) = match Trivia::parse(rest).await {
+        let (ltrivia, rest) = match Trivia::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, _) | Fatal(e, _) => e.never(),
         };
 
-        let (stmts, rest) = match Stmts::parse(rest).await {
+        let (stmts, rest) = match Stmts::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, _) => e.never(),
             Fatal(e, r) => return Fatal(ParseBlockError::Stmts(e), r),
         };
 
-        let (rtrivia, rest) = match Trivia::parse(rest).await {
+        let (rtrivia, rest) = match Trivia::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, _) | Fatal(e, _) => e.never(),
         };
 
-        let (rbrace, rest) = match RBrace::parse(rest).await {
+        let (rbrace, rest) = match RBrace::parse(rest, ctx).await {
             Done(v, r) => (v, r),
-            Fail(e, r) => return Fail(ParseBlockError::MissingClosingBrace(e), r),
+            Fail(e, r) => {
+                return Fatal(
+                    ParseBlockError::MissingClosingBrace {
+                        lbrace: lbrace.span,
+                        error: e,
+                    },
+                    r,
+                )
+            }
             Fatal(e, _) => e.never(),
         };
 
@@ -65,5 +80,8 @@ impl Parse for Block {
 pub enum ParseBlockError {
     MissingOpeningBrace(ParseTokenError<LBrace>),
     Stmts(ParseStmtsError),
-    MissingClosingBrace(ParseTokenError<RBrace>),
+    MissingClosingBrace {
+        lbrace: Span,
+        error: ParseTokenError<RBrace>,
+    },
 }
@@ -0,0 +1,17 @@
+use super::{Ident, Term};
+use crate::token;
+use crate::{Span, Spanned};
+
+/// Member access such as `lib.name`.
+#[derive(Debug)]
+pub struct Field {
+    pub receiver: Term,
+    pub dot: token::Dot,
+    pub name: Ident,
+}
+
+impl Spanned for Field {
+    fn span(&self) -> Span {
+        self.receiver.span().to(&self.name.span)
+    }
+}
@@ -0,0 +1,114 @@
+use super::block::{Block, ParseBlockError};
+use crate::{
+    binder::{Binder, ParseBinderError},
+    expr::ParseExprError,
+    spacing::ParseSpacingError,
+    token::{self, ParseTokenError},
+    Expr, InputStream, Parse, ParseContext, Spacing, Span, Spanned, Trivia,
+};
+use parcom::prelude::*;
+
+/// `for <binder> in <expr> { ... }`
+#[derive(Debug)]
+pub struct For {
+    pub for_token: token::For,
+    pub for_spacing: Spacing,
+    pub binder: Binder,
+    pub binder_spacing: Spacing,
+    pub in_token: token::In,
+    pub in_spacing: Spacing,
+    pub iter: Expr,
+    pub iter_trivia: Trivia,
+    pub body: Block,
+}
+
+impl Spanned for For {
+    fn span(&self) -> Span {
+        self.for_token.span.to(&self.body.span())
+    }
+}
+
+impl Parse for For {
+    type Error = ParseForError;
+    type Fatal = ParseForError;
+
+    async fn parse<S: InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let (for_token, rest) = match token::For::parse(input, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseForError::For(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        // `for` is an ordinary identifier unless a binder follows.
+        let (for_spacing, rest) = match Spacing::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseForError::Spacing(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (binder, rest) = match Binder::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseForError::Binder(e), r),
+            Fatal(e, r) => return Fatal(ParseForError::Binder(e), r),
+        };
+
+        let (binder_spacing, rest) = match Spacing::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fatal(ParseForError::Spacing(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (in_token, rest) = match token::In::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fatal(ParseForError::In(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (in_spacing, rest) = match Spacing::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fatal(ParseForError::Spacing(e), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (iter, rest) = match Expr::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) | Fatal(e, r) => return Fatal(ParseForError::Iter(e), r),
+        };
+
+        let (iter_trivia, rest) = match Trivia::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, _) | Fatal(e, _) => e.never(),
+        };
+
+        let (body, rest) = match Block::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) | Fatal(e, r) => return Fatal(ParseForError::Body(e), r),
+        };
+
+        let me = Self {
+            for_token,
+            for_spacing,
+            binder,
+            binder_spacing,
+            in_token,
+            in_spacing,
+            iter,
+            iter_trivia,
+            body,
+        };
+        Done(me, rest)
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseForError {
+    For(ParseTokenError<token::For>),
+    Spacing(ParseSpacingError),
+    Binder(ParseBinderError),
+    In(ParseTokenError<token::In>),
+    Iter(ParseExprError),
+    Body(ParseBlockError),
+}
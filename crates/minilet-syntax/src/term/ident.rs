@@ -1,4 +1,4 @@
-use crate::{util::any_char, Parse, Span};
+use crate::{lexer::scan, Parse, ParseContext, Span, Spanned};
 use parcom::prelude::*;
 
 #[derive(Debug)]
@@ -7,55 +7,38 @@ pub struct Ident {
     pub span: Span,
 }
 
+impl Spanned for Ident {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
 impl Parse for Ident {
     type Error = ParseIdentError;
     type Fatal = Never;
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        _ctx: &ParseContext,
     ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let start = input.metrics();
-        let mut buf = String::new();
-
-        let mut rest = input;
-        let rest = loop {
-            let anchor = rest.anchor();
-            match any_char().parse(rest).await {
-                Done(c, r) => {
-                    if buf.is_empty() {
-                        if !c.is_ascii_alphabetic() {
-                            break r.rewind(anchor);
-                        }
-                    } else {
-                        if !c.is_ascii_alphanumeric() {
-                            break r.rewind(anchor);
-                        }
-                    }
-
-                    buf.push(c);
-                    rest = r;
-                }
-                Fail(_, r) => break r.rewind(anchor),
-                Fatal(_, _) => unreachable!(),
-            }
-        };
-
-        if buf.is_empty() {
-            Fail(
+        let start = input.position();
+        let anchor = input.anchor();
+        let (kind, text, rest) = Box::pin(scan(input)).await;
+        if !kind.is_word() {
+            return Fail(
                 ParseIdentError::Missing {
                     span: Span::points(start),
                 },
-                rest.into(),
-            )
-        } else {
-            let end = rest.metrics();
-
-            let me = Self {
-                text: buf,
-                span: Span::new(start, end),
-            };
-            Done(me, rest)
+                rest.rewind(anchor).into(),
+            );
         }
+
+        let end = rest.position();
+        let me = Self {
+            text,
+            span: Span::new(start, end),
+        };
+        Done(me, rest)
     }
 }
 
@@ -0,0 +1,92 @@
+use crate::{
+    expr::ParseExprError,
+    stmt::{ParseStmtLetError, StmtLet},
+    token::{self, ParseTokenError},
+    Expr, InputStream, Parse, ParseContext, Span, Spanned, Trivia,
+};
+use parcom::prelude::*;
+
+/// `let <binding> in <expr>`
+#[derive(Debug)]
+pub struct LetIn {
+    pub binding: StmtLet,
+    pub in_trivia: Trivia,
+    pub in_token: token::In,
+    pub body_trivia: Trivia,
+    pub body: Expr,
+}
+
+impl Spanned for LetIn {
+    fn span(&self) -> Span {
+        self.binding.span().to(&self.body.span())
+    }
+}
+
+impl LetIn {
+    /// Parses the `in <expr>` following `binding`.
+    ///
+    /// Fails if no `in` follows, so that the caller can keep `binding` as a statement.
+    pub(crate) async fn parse_body<S: InputStream>(
+        binding: StmtLet,
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, (StmtLet, ParseLetInError), ParseLetInError> {
+        let (in_trivia, rest) = match Trivia::parse(input, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, _) | Fatal(e, _) => e.never(),
+        };
+
+        let (in_token, rest) = match token::In::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail((binding, ParseLetInError::In(e)), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (body_trivia, rest) = match Trivia::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, _) | Fatal(e, _) => e.never(),
+        };
+
+        let (body, rest) = match Expr::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) | Fatal(e, r) => return Fatal(ParseLetInError::Body(e), r),
+        };
+
+        let me = Self {
+            binding,
+            in_trivia,
+            in_token,
+            body_trivia,
+            body,
+        };
+        Done(me, rest)
+    }
+}
+
+impl Parse for LetIn {
+    type Error = ParseLetInError;
+    type Fatal = ParseLetInError;
+
+    async fn parse<S: InputStream>(
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
+        let (binding, rest) = match StmtLet::parse(input, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail(ParseLetInError::Let(e), r),
+            Fatal(e, r) => return Fatal(ParseLetInError::Let(e), r),
+        };
+
+        match LetIn::parse_body(binding, rest, ctx).await {
+            Done(v, r) => Done(v, r),
+            Fail((_, e), r) | Fatal(e, r) => Fatal(e, r),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseLetInError {
+    Let(ParseStmtLetError),
+    In(ParseTokenError<token::In>),
+    Body(ParseExprError),
+}
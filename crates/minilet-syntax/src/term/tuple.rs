@@ -1,9 +1,14 @@
 use crate::{
-    expr::Expr,
+    diagnostics::ToDiagnostic,
+    expected::Found,
+    expr::{Expr, ParseExprError},
     punctured::{ParsePuncturedError, Punctured},
+    recovery::{skip_until, SyntaxError},
     relaxed::Relaxed,
-    token::{self, Comma},
-    InputStream, Parse, Span, Trivia,
+    term::ParseTermError,
+    token::{self, Comma, ParseTokenError},
+    util::peek_char,
+    InputStream, Parse, ParseContext, Span, Spanned, Trivia,
 };
 use parcom::{
     ParseResult::{Done, Fail, Fatal},
@@ -18,38 +23,68 @@ pub struct Tuple {
     pub rparen: token::RParen,
 }
 
+impl Spanned for Tuple {
+    fn span(&self) -> Span {
+        self.lparen.span.to(&self.rparen.span)
+    }
+}
+
 impl Parse for Tuple {
     type Error = ParseTupleError;
     type Fatal = ParseTupleError;
 
     async fn parse<S: InputStream>(
         input: S,
+        ctx: &ParseContext,
     ) -> parcom::ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let (lparen, rest) = match token::LParen::parse(input).await {
+        let (lparen, rest) = match token::LParen::parse(input, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, r) => return Fail(ParseTupleError::MissingOpeningParen(e.span), r),
             Fatal(e, _) => e.never(),
         };
 
-        let (ltrivia, rest) = match Trivia::parse(rest).await {
+        let (ltrivia, rest) = match Trivia::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, _) | Fatal(e, _) => e.never(),
         };
 
-        let (items, rest) = match Punctured::parse(rest).await {
+        let items_anchor = rest.anchor();
+        let reports = ctx.reports();
+        let (items, failure) = Punctured::parse_until_failure(rest, ctx).await;
+        let (items, rest) = match items {
             Done(v, r) => (v, r),
             Fail(e, _) => e.never(),
+            Fatal(e, r) if ctx.is_recovering() => {
+                let error = ParseTupleError::Punct(e);
+                ctx.discard_reports(reports);
+                return recover(lparen, ltrivia, error, r.rewind(items_anchor), ctx).await;
+            }
             Fatal(e, r) => return Fatal(ParseTupleError::Punct(e), r),
         };
 
-        let (rtrivia, rest) = match Trivia::parse(rest).await {
+        let (rtrivia, rest) = match Trivia::parse(rest, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, _) | Fatal(e, _) => e.never(),
         };
 
-        let (rparen, rest) = match token::RParen::parse(rest).await {
+        let anchor = rest.anchor();
+        let (rparen, rest) = match token::RParen::parse(rest, ctx).await {
             Done(v, r) => (v, r),
-            Fail(e, r) => return Fatal(ParseTupleError::MissingOpeningParen(e.span), r),
+            Fail(e, r) => {
+                let (next, r) = peek_char(r.rewind(anchor)).await;
+                let error = match failure {
+                    // an item that failed past the `)` expected here says why the tuple stopped.
+                    Some(failure) if is_past(&failure, &e.span) => {
+                        ParseTupleError::Punct(ParsePuncturedError::Term(failure))
+                    }
+                    _ => missing_rparen(&lparen, items.first().is_some(), e, next),
+                };
+                if ctx.is_recovering() {
+                    ctx.discard_reports(reports);
+                    return recover(lparen, ltrivia, error, r.rewind(items_anchor), ctx).await;
+                }
+                return Fatal(error, r.into());
+            }
             Fatal(e, _) => e.never(),
         };
 
@@ -67,6 +102,82 @@ impl Parse for Tuple {
 #[derive(Debug)]
 pub enum ParseTupleError {
     MissingOpeningParen(Span),
-    MissingClosingParen(Span),
+    MissingClosingParen {
+        lparen: Span,
+        error: ParseTokenError<token::RParen>,
+    },
+    /// Input other than `)` or a further item where the tuple could continue.
+    Unexpected {
+        span: Span,
+        expected: &'static str,
+        found: Found,
+    },
     Punct(ParsePuncturedError<Expr, Relaxed<Comma>>),
 }
+
+/// Whether `error` points past the start of `span`.
+fn is_past(error: &ParseExprError, span: &Span) -> bool {
+    error
+        .to_diagnostic()
+        .primary_span()
+        .is_some_and(|s| s.start().cmp_offset(span.start()).is_gt())
+}
+
+/// Error for a tuple not continued by `,` or closed by `)`, where `next` is the input found
+/// instead. The tuple is only reported unclosed if the input ends or a `}` closes first.
+fn missing_rparen(
+    lparen: &token::LParen,
+    has_items: bool,
+    error: ParseTokenError<token::RParen>,
+    next: Option<char>,
+) -> ParseTupleError {
+    let found = match next {
+        None | Some('}') => {
+            return ParseTupleError::MissingClosingParen {
+                lparen: lparen.span.clone(),
+                error,
+            }
+        }
+        Some(c) => Found::Char(c),
+    };
+    let expected = if has_items {
+        "`,` or `)`"
+    } else {
+        "expression or `)`"
+    };
+    ParseTupleError::Unexpected {
+        span: error.span,
+        expected,
+        found,
+    }
+}
+
+/// Skips the items of a tuple up to its closing paren, reporting `error`.
+///
+/// Gives `error` back if the tuple is not closed before the end of the statement.
+async fn recover<S: InputStream>(
+    lparen: token::LParen,
+    ltrivia: Trivia,
+    error: ParseTupleError,
+    input: S,
+    ctx: &ParseContext,
+) -> parcom::ParseResult<S, Tuple, ParseTupleError, ParseTupleError> {
+    let (node, rest) = skip_until(input, |c| matches!(c, ';' | '}' | ')')).await;
+    let rtrivia = Trivia::empty(Span::points(rest.position()));
+
+    let (rparen, rest) = match token::RParen::parse(rest, ctx).await {
+        Done(v, r) => (v, r),
+        Fail(_, r) => return Fatal(error, r),
+        Fatal(e, _) => e.never(),
+    };
+    ctx.report(SyntaxError::Term(ParseTermError::Tuple(error)));
+
+    let me = Tuple {
+        lparen,
+        ltrivia,
+        items: Punctured::from_parts(Some(Expr::Error(node)), Vec::new()),
+        rtrivia,
+        rparen,
+    };
+    Done(me, rest)
+}
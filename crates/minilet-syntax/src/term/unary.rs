@@ -1,7 +1,8 @@
 use super::{ParseTermError, Term};
 use crate::{
+    recovery::{ErrorNode, SyntaxError},
     unary_op::{ParseUnaryOpError, UnaryOp},
-    Parse,
+    Parse, ParseContext, Span, Spanned,
 };
 use parcom::prelude::*;
 
@@ -11,21 +12,38 @@ pub struct Unary {
     pub term: Term,
 }
 
+impl Spanned for Unary {
+    fn span(&self) -> Span {
+        self.op.span().to(&self.term.span())
+    }
+}
+
 impl Parse for Unary {
     type Error = ParseUnaryError;
     type Fatal = ParseUnaryError;
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        ctx: &ParseContext,
     ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let (op, rest) = match UnaryOp::parse(input).await {
+        let (op, rest) = match UnaryOp::parse(input, ctx).await {
             Done(v, r) => (v, r),
             Fail(e, r) => return Fail(ParseUnaryError::Op(e), r),
             Fatal(e, _) => e.never(),
         };
 
-        let (term, rest) = match Term::parse(rest).await {
+        let anchor = rest.anchor();
+        let operand_start = rest.position();
+        let (term, rest) = match Term::parse(rest, ctx).await {
             Done(v, r) => (v, r),
+            Fail(e, r) if ctx.is_recovering() => {
+                ctx.report(SyntaxError::Term(e));
+                let node = ErrorNode {
+                    text: String::new(),
+                    span: Span::points(operand_start),
+                };
+                (Term::Error(node), r.rewind(anchor))
+            }
             Fail(e, r) => return Fail(ParseUnaryError::Term(e), r),
             Fatal(e, r) => return Fatal(ParseUnaryError::Term(e), r),
         };
@@ -40,3 +58,38 @@ pub enum ParseUnaryError {
     Op(ParseUnaryOpError),
     Term(ParseTermError),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Unary;
+    use crate::{parse_expr, unary_op::UnaryOp, Expr, Term};
+
+    /// The operators of a chain of prefix operators, outermost first.
+    fn prefix_ops(source: &str) -> Vec<char> {
+        let expr = parse_expr(source).unwrap_or_else(|e| panic!("{source:?}: {e}"));
+        let Expr::Term(term) = expr else {
+            panic!("{source:?} is not a term");
+        };
+        let mut term = *term;
+        let mut ops = Vec::new();
+        while let Term::Unary(unary) = term {
+            let Unary { op, term: operand } = *unary;
+            ops.push(match op {
+                UnaryOp::Plus { .. } => '+',
+                UnaryOp::Sub { .. } => '-',
+                UnaryOp::BitNot { .. } => '~',
+            });
+            term = operand;
+        }
+        ops
+    }
+
+    #[test]
+    fn stacks_prefix_operators() {
+        assert_eq!(prefix_ops("--x"), ['-', '-']);
+        assert_eq!(prefix_ops("-~x"), ['-', '~']);
+        assert_eq!(prefix_ops("~-x"), ['~', '-']);
+        assert_eq!(prefix_ops("+-(1)"), ['+', '-']);
+        assert_eq!(prefix_ops("------1"), ['-'; 6]);
+    }
+}
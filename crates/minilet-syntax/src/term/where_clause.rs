@@ -0,0 +1,86 @@
+use crate::{
+    punctured::{ParsePuncturedError, Punctured},
+    relaxed::Relaxed,
+    stmt::Binding,
+    token::{self, Comma, ParseTokenError},
+    Expr, InputStream, Parse, ParseContext, Span, Spanned, Trivia,
+};
+use parcom::prelude::*;
+
+/// `<expr> where <binding>, <binding>, ...`
+#[derive(Debug)]
+pub struct Where {
+    pub expr: Expr,
+    pub where_trivia: Trivia,
+    pub where_token: token::Where,
+    pub bindings_trivia: Trivia,
+    pub bindings: Punctured<Binding, Relaxed<Comma>>,
+}
+
+impl Spanned for Where {
+    fn span(&self) -> Span {
+        let end = match self.bindings.iter().last() {
+            Some(binding) => binding.span(),
+            None => self.where_token.span.clone(),
+        };
+        self.expr.span().to(&end)
+    }
+}
+
+impl Where {
+    /// Parses the `where <bindings>` following `expr`.
+    ///
+    /// Fails if no `where` follows, so that the caller can keep `expr` as is.
+    pub(crate) async fn parse_clause<S: InputStream>(
+        expr: Expr,
+        input: S,
+        ctx: &ParseContext,
+    ) -> ParseResult<S, Self, (Expr, ParseWhereError), ParseWhereError> {
+        let (where_trivia, rest) = match Trivia::parse(input, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, _) | Fatal(e, _) => e.never(),
+        };
+
+        let (where_token, rest) = match token::Where::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, r) => return Fail((expr, ParseWhereError::Where(e)), r),
+            Fatal(e, _) => e.never(),
+        };
+
+        let (bindings_trivia, rest) = match Trivia::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, _) | Fatal(e, _) => e.never(),
+        };
+
+        let start = rest.position();
+        let (bindings, rest) = match Punctured::parse(rest, ctx).await {
+            Done(v, r) => (v, r),
+            Fail(e, _) => return e.never(),
+            Fatal(e, r) => return Fatal(ParseWhereError::Bindings(Box::new(e)), r),
+        };
+
+        if bindings.first().is_none() {
+            let span = Span::points(start);
+            return Fatal(ParseWhereError::MissingBinding { span }, rest.into());
+        }
+
+        let me = Self {
+            expr,
+            where_trivia,
+            where_token,
+            bindings_trivia,
+            bindings,
+        };
+        Done(me, rest)
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseWhereError {
+    Where(ParseTokenError<token::Where>),
+    Bindings(Box<ParsePuncturedError<Binding, Relaxed<Comma>>>),
+    /// `where` is not followed by any binding.
+    MissingBinding {
+        span: Span,
+    },
+}
@@ -0,0 +1,653 @@
+//! Printing syntax trees back to source text.
+//!
+//! Every node keeps the text it was parsed from, so printing a parsed tree reproduces the input
+//! exactly. `Display` of every node prints its source.
+
+use crate::{
+    binder::{Binder, OpBinder},
+    expr::BinOp,
+    literal::{IntegerLiteral, StringLiteral},
+    op::{Op, OpKind, OpSymbol},
+    punctured::Punctured,
+    recovery::ErrorNode,
+    relaxed::Relaxed,
+    stmt::{Binding, FixityKeyword, StmtFixity, StmtImport, StmtLet},
+    term::{app::App, block::Block, field::Field, for_loop::For, let_in::LetIn, Where},
+    token::{self, Token},
+    unary_op::UnaryOp,
+    Expr, Ident, Literal, Parse, SourceFile, Spacing, Stmt, Stmts, Term, Trivia, Tuple, Unary,
+};
+use std::fmt::{self, Display, Write};
+
+pub trait ToSource {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result;
+
+    fn to_source(&self) -> String {
+        let mut out = String::new();
+        // writing to a `String` never fails.
+        let _ = self.write_source(&mut out);
+        out
+    }
+}
+
+impl<T: ToSource + ?Sized> ToSource for Box<T> {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        (**self).write_source(out)
+    }
+}
+
+impl<T: ToSource> ToSource for Option<T> {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            Some(v) => v.write_source(out),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<A: ToSource, B: ToSource> ToSource for (A, B) {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.0.write_source(out)?;
+        self.1.write_source(out)
+    }
+}
+
+macro_rules! impl_token_source {
+    ($($name: ident),* $(,)*) => {
+        $(
+            impl ToSource for token::$name {
+                fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+                    write!(out, "{}", <token::$name as Token>::TOKEN)
+                }
+            }
+        )*
+    };
+}
+
+impl_token_source!(
+    Semi, Plus, Minus, Asterisk, Slash, LParen, RParen, LBrace, RBrace, Eq, Comma, Lt, Gt, Percent,
+    Amp, Pipe, Caret, Tilde, Dot, Let, Infixl, Infixr, Infix, EqEq, Ne, Le, Ge, StarStar, Shl, Shr,
+    DotDot, DotDotEq, For, In, Import, As, Where,
+);
+
+impl ToSource for Trivia {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(&self.text)
+    }
+}
+
+impl ToSource for Spacing {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(&self.text)
+    }
+}
+
+impl ToSource for ErrorNode {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(&self.text)
+    }
+}
+
+impl ToSource for Ident {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(&self.text)
+    }
+}
+
+impl ToSource for IntegerLiteral {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(&self.raw_text)
+    }
+}
+
+impl ToSource for StringLiteral {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(&self.raw_text)
+    }
+}
+
+impl ToSource for Literal {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            Literal::Integer(v) => v.write_source(out),
+            Literal::String(v) => v.write_source(out),
+        }
+    }
+}
+
+impl ToSource for OpSymbol {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        out.write_str(&self.text)
+    }
+}
+
+impl ToSource for OpKind {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            OpKind::Add(t) => t.write_source(out),
+            OpKind::Sub(t) => t.write_source(out),
+            OpKind::Mul(t) => t.write_source(out),
+            OpKind::Div(t) => t.write_source(out),
+            OpKind::Rem(t) => t.write_source(out),
+            OpKind::Pow(t) => t.write_source(out),
+            OpKind::BitAnd(t) => t.write_source(out),
+            OpKind::BitOr(t) => t.write_source(out),
+            OpKind::BitXor(t) => t.write_source(out),
+            OpKind::Shl(t) => t.write_source(out),
+            OpKind::Shr(t) => t.write_source(out),
+            OpKind::Eq(t) => t.write_source(out),
+            OpKind::Ne(t) => t.write_source(out),
+            OpKind::Lt(t) => t.write_source(out),
+            OpKind::Gt(t) => t.write_source(out),
+            OpKind::Le(t) => t.write_source(out),
+            OpKind::Ge(t) => t.write_source(out),
+            OpKind::Range(t) => t.write_source(out),
+            OpKind::RangeInclusive(t) => t.write_source(out),
+            OpKind::Custom(s) => s.write_source(out),
+        }
+    }
+}
+
+impl ToSource for Op {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.leading_spacing.write_source(out)?;
+        self.kind.write_source(out)?;
+        self.trailing_spacing.write_source(out)
+    }
+}
+
+impl ToSource for UnaryOp {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            UnaryOp::Plus { .. } => out.write_char('+'),
+            UnaryOp::Sub { .. } => out.write_char('-'),
+            UnaryOp::BitNot { .. } => out.write_char('~'),
+        }
+    }
+}
+
+impl<T: Parse + ToSource> ToSource for Relaxed<T> {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.leading_trivia.write_source(out)?;
+        self.item.write_source(out)?;
+        self.trailing_trivia.write_source(out)
+    }
+}
+
+impl<T: Parse + ToSource, P: Parse + ToSource> ToSource for Punctured<T, P> {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        let (first, lasts) = self.parts();
+        first.write_source(out)?;
+        for (punct, item) in lasts {
+            punct.write_source(out)?;
+            item.write_source(out)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: ToSource> ToSource for &T {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        (**self).write_source(out)
+    }
+}
+
+impl ToSource for Binder {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            Binder::Ident(v) => v.write_source(out),
+            Binder::Op(v) => v.write_source(out),
+        }
+    }
+}
+
+impl ToSource for OpBinder {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.lparen.write_source(out)?;
+        self.symbol.write_source(out)?;
+        self.rparen.write_source(out)
+    }
+}
+
+impl ToSource for Expr {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            Expr::Term(v) => v.write_source(out),
+            Expr::Bin(v) => v.write_source(out),
+            Expr::Error(v) => v.write_source(out),
+        }
+    }
+}
+
+impl ToSource for BinOp {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.lhs.write_source(out)?;
+        self.op.write_source(out)?;
+        self.rhs.write_source(out)
+    }
+}
+
+impl ToSource for Term {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            Term::Tuple(v) => v.write_source(out),
+            Term::Literal(v) => v.write_source(out),
+            Term::Ident(v) => v.write_source(out),
+            Term::Unary(v) => v.write_source(out),
+            Term::Block(v) => v.write_source(out),
+            Term::App(v) => v.write_source(out),
+            Term::For(v) => v.write_source(out),
+            Term::Field(v) => v.write_source(out),
+            Term::LetIn(v) => v.write_source(out),
+            Term::Where(v) => v.write_source(out),
+            Term::Error(v) => v.write_source(out),
+        }
+    }
+}
+
+impl ToSource for Tuple {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.lparen.write_source(out)?;
+        self.ltrivia.write_source(out)?;
+        self.items.write_source(out)?;
+        self.rtrivia.write_source(out)?;
+        self.rparen.write_source(out)
+    }
+}
+
+impl ToSource for Block {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.lbrace.write_source(out)?;
+        self.ltrivia.write_source(out)?;
+        self.stmts.write_source(out)?;
+        self.rtrivia.write_source(out)?;
+        self.rbrace.write_source(out)
+    }
+}
+
+impl ToSource for Unary {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.op.write_source(out)?;
+        self.term.write_source(out)
+    }
+}
+
+impl ToSource for App {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.receiver.write_source(out)?;
+        self.arg.write_source(out)
+    }
+}
+
+impl ToSource for Field {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.receiver.write_source(out)?;
+        self.dot.write_source(out)?;
+        self.name.write_source(out)
+    }
+}
+
+impl ToSource for For {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.for_token.write_source(out)?;
+        self.for_spacing.write_source(out)?;
+        self.binder.write_source(out)?;
+        self.binder_spacing.write_source(out)?;
+        self.in_token.write_source(out)?;
+        self.in_spacing.write_source(out)?;
+        self.iter.write_source(out)?;
+        self.iter_trivia.write_source(out)?;
+        self.body.write_source(out)
+    }
+}
+
+impl ToSource for LetIn {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.binding.write_source(out)?;
+        self.in_trivia.write_source(out)?;
+        self.in_token.write_source(out)?;
+        self.body_trivia.write_source(out)?;
+        self.body.write_source(out)
+    }
+}
+
+impl ToSource for Where {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.expr.write_source(out)?;
+        self.where_trivia.write_source(out)?;
+        self.where_token.write_source(out)?;
+        self.bindings_trivia.write_source(out)?;
+        self.bindings.write_source(out)
+    }
+}
+
+impl ToSource for Binding {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.binder.write_source(out)?;
+        self.binder_trivia.write_source(out)?;
+        self.eq.write_source(out)?;
+        self.eq_trivia.write_source(out)?;
+        self.expr.write_source(out)
+    }
+}
+
+impl ToSource for StmtLet {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.let_token.write_source(out)?;
+        self.let_spacing.write_source(out)?;
+        self.binding.write_source(out)
+    }
+}
+
+impl ToSource for FixityKeyword {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            FixityKeyword::Infixl(t) => t.write_source(out),
+            FixityKeyword::Infixr(t) => t.write_source(out),
+            FixityKeyword::Infix(t) => t.write_source(out),
+        }
+    }
+}
+
+impl ToSource for StmtFixity {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.keyword.write_source(out)?;
+        self.keyword_spacing.write_source(out)?;
+        self.precedence.write_source(out)?;
+        self.precedence_spacing.write_source(out)?;
+        self.symbol.write_source(out)
+    }
+}
+
+impl ToSource for StmtImport {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.import_token.write_source(out)?;
+        self.import_spacing.write_source(out)?;
+        self.path.write_source(out)?;
+        self.path_spacing.write_source(out)?;
+        self.as_token.write_source(out)?;
+        self.as_spacing.write_source(out)?;
+        self.alias.write_source(out)
+    }
+}
+
+impl ToSource for Stmt {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        match self {
+            Stmt::Let(v) => v.write_source(out),
+            Stmt::Fixity(v) => v.write_source(out),
+            Stmt::Import(v) => v.write_source(out),
+            Stmt::Expr(v) => v.write_source(out),
+            Stmt::Error(v) => v.write_source(out),
+        }
+    }
+}
+
+impl ToSource for Stmts {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.stmts.write_source(out)?;
+        self.trailing_semi.write_source(out)
+    }
+}
+
+impl ToSource for SourceFile {
+    fn write_source(&self, out: &mut dyn Write) -> fmt::Result {
+        self.leading_trivia.write_source(out)?;
+        self.stmts.write_source(out)?;
+        self.trailing_trivia.write_source(out)
+    }
+}
+
+macro_rules! impl_display {
+    ($($ty: ty),* $(,)*) => {
+        $(
+            impl Display for $ty {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    self.write_source(f)
+                }
+            }
+        )*
+    };
+}
+
+impl_display!(
+    Trivia,
+    Spacing,
+    ErrorNode,
+    Ident,
+    IntegerLiteral,
+    StringLiteral,
+    Literal,
+    OpSymbol,
+    OpKind,
+    Op,
+    UnaryOp,
+    Binder,
+    OpBinder,
+    Expr,
+    BinOp,
+    Term,
+    Tuple,
+    Block,
+    Unary,
+    App,
+    Field,
+    For,
+    LetIn,
+    Where,
+    Binding,
+    StmtLet,
+    FixityKeyword,
+    StmtFixity,
+    StmtImport,
+    Stmt,
+    Stmts,
+    SourceFile,
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        binder::{Binder, OpBinder},
+        expr::BinOp,
+        literal::{IntegerLiteral, StringLiteral},
+        op::{Op, OpKind, OpSymbol},
+        parse_recovering,
+        recovery::ErrorNode,
+        stmt::{Binding, FixityKeyword, StmtFixity, StmtImport, StmtLet},
+        term::{app::App, block::Block, field::Field, for_loop::For, let_in::LetIn, Where},
+        unary_op::UnaryOp,
+        util::Rng,
+        visit::{self, Visit},
+        Expr, Ident, Literal, ParseContext, Position, SourceFile, Spacing, Spanned, Stmt, Stmts,
+        Term, ToSource, Trivia, Tuple, Unary,
+    };
+    use minilet_utils::stream::StrStream;
+    use parcom::IntoMeasured;
+    use std::{any::type_name, collections::BTreeSet};
+
+    fn parse(source: &str) -> SourceFile {
+        let ctx = ParseContext::new();
+        let input = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            Default::default(),
+        );
+        pollster::block_on(parse_recovering(input, &ctx)).value
+    }
+
+    fn round_trip(source: &str) -> String {
+        let file = parse(source);
+        for stmt in file.stmts.stmts.iter() {
+            assert_eq!(&source[stmt.span().bytes()], stmt.to_source(), "{source:?}");
+        }
+        file.to_source()
+    }
+
+    const FRAGMENTS: &[&str] = &[
+        " ",
+        "\n",
+        "\t",
+        ";",
+        "(",
+        ")",
+        "{",
+        "}",
+        ",",
+        ".",
+        "=",
+        "+",
+        "-",
+        "*",
+        "**",
+        "~",
+        "..",
+        "..=",
+        "<=",
+        "==",
+        "<+>",
+        "0",
+        "07",
+        "0x1F",
+        "0b101",
+        "0o17",
+        "42",
+        "\"s\"",
+        "\"a\\\"b\"",
+        "\"",
+        "x",
+        "foo",
+        "let",
+        "in",
+        "where",
+        "for",
+        "import",
+        "as",
+        "infixl",
+        "infixr",
+        "infix",
+        "@",
+        "#",
+        "é",
+        "/",
+        "// note",
+    ];
+
+    #[test]
+    fn round_trips_samples() {
+        let samples = [
+            "",
+            "  let x = 0x1F + 07;\n  x ",
+            "infixl 6 <+>; let (<+>) = add; 1 <+> 2",
+            "import \"lib.ml\" as lib; lib.f(1, (2 , 3))",
+            "for i in 0 .. 10 { print(i); };",
+            "let y = a * b where a = 1, b = 2 ; let z = 1 in z",
+            "{ a; { b; c }; }",
+        ];
+        for sample in samples {
+            assert_eq!(round_trip(sample), sample);
+        }
+    }
+
+    /// Printing the tree of any input, even an invalid one, gives the input back.
+    #[test]
+    fn round_trips_generated_inputs() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..2000 {
+            let len = rng.below(24);
+            let source: String = (0..len).map(|_| *rng.pick(FRAGMENTS)).collect();
+            assert_eq!(round_trip(&source), source, "{source:?}");
+        }
+    }
+
+    /// Checks that every node it visits spans exactly the text it prints, and records the kinds
+    /// of node it has seen.
+    struct CheckSpans<'a> {
+        source: &'a str,
+        seen: BTreeSet<&'static str>,
+    }
+
+    impl CheckSpans<'_> {
+        fn check<T: Spanned + ToSource>(&mut self, node: &T) {
+            let kind = type_name::<T>();
+            assert_eq!(
+                &self.source[node.span().bytes()],
+                node.to_source(),
+                "{kind}"
+            );
+            self.seen.insert(kind);
+        }
+    }
+
+    macro_rules! check_spans {
+        ($($visit: ident($node: ty) $(=> $walk: ident)?;)*) => {
+            const NODE_KINDS: usize = [$(stringify!($node)),*].len();
+
+            impl Visit for CheckSpans<'_> {
+                $(
+                    fn $visit(&mut self, node: &$node) {
+                        self.check(node);
+                        $(visit::$walk(self, node))?
+                    }
+                )*
+            }
+        };
+    }
+
+    check_spans! {
+        visit_source_file(SourceFile) => walk_source_file;
+        visit_stmts(Stmts) => walk_stmts;
+        visit_stmt(Stmt) => walk_stmt;
+        visit_stmt_let(StmtLet) => walk_stmt_let;
+        visit_binding(Binding) => walk_binding;
+        visit_stmt_fixity(StmtFixity) => walk_stmt_fixity;
+        visit_fixity_keyword(FixityKeyword) => walk_fixity_keyword;
+        visit_stmt_import(StmtImport) => walk_stmt_import;
+        visit_binder(Binder) => walk_binder;
+        visit_op_binder(OpBinder) => walk_op_binder;
+        visit_expr(Expr) => walk_expr;
+        visit_bin_op(BinOp) => walk_bin_op;
+        visit_op(Op) => walk_op;
+        visit_op_kind(OpKind) => walk_op_kind;
+        visit_term(Term) => walk_term;
+        visit_tuple(Tuple) => walk_tuple;
+        visit_block(Block) => walk_block;
+        visit_unary(Unary) => walk_unary;
+        visit_app(App) => walk_app;
+        visit_for(For) => walk_for;
+        visit_field(Field) => walk_field;
+        visit_let_in(LetIn) => walk_let_in;
+        visit_where(Where) => walk_where;
+        visit_literal(Literal) => walk_literal;
+        visit_integer_literal(IntegerLiteral);
+        visit_string_literal(StringLiteral);
+        visit_ident(Ident);
+        visit_op_symbol(OpSymbol);
+        visit_unary_op(UnaryOp);
+        visit_error_node(ErrorNode);
+        visit_trivia(Trivia);
+        visit_spacing(Spacing);
+    }
+
+    #[test]
+    fn spans_cover_the_printed_text_of_every_node() {
+        let source = "\
+            // header
+            import \"lib.ml\" as lib;
+            infixl 6 <+>;
+            let (<+>) = add;
+            let x = -lib.f(1, (2 , 3)) <+> \"s\" // note
+              ;
+            for i in 0 .. 10 { print(i); };
+            let y = a * b where a = 1, b = 2;
+            let w = @;
+            let z = 1 in z
+        ";
+        let mut check = CheckSpans {
+            source,
+            seen: BTreeSet::new(),
+        };
+        check.visit_source_file(&parse(source));
+        assert_eq!(check.seen.len(), NODE_KINDS, "{:#?}", check.seen);
+    }
+}
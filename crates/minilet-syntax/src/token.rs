@@ -1,13 +1,15 @@
-use crate::{util::any_char, Parse, Span};
-use parcom::parsers::primitive::str::atom;
+use crate::{lexer::scan, util::any_char, Parse, Span, Spanned};
 use parcom::prelude::*;
 use std::marker::PhantomData;
 
-pub trait Token {
+pub trait Token: AnyToken {
     type Base;
     const TOKEN: Self::Base;
+}
 
-    fn span(&self) -> Span;
+/// The object safe part of [`Token`], through which visitors see tokens.
+pub trait AnyToken: Spanned {
+    fn span_mut(&mut self) -> &mut Span;
 }
 
 #[derive(Debug)]
@@ -41,7 +43,15 @@ macro_rules! declare_short_tokens {
         impl Token for $name {
             type Base = char;
             const TOKEN: Self::Base = $expr;
+        }
+
+        impl AnyToken for $name {
+            fn span_mut(&mut self) -> &mut Span {
+                &mut self.span
+            }
+        }
 
+        impl Spanned for $name {
             fn span(&self) -> Span {
                 self.span.clone()
             }
@@ -53,11 +63,12 @@ macro_rules! declare_short_tokens {
 
             async fn parse<S: crate::InputStream>(
                 input: S,
+                _ctx: &crate::ParseContext,
             ) -> parcom::ParseResult<S, Self, Self::Error, Self::Fatal> {
-                let start = input.metrics();
+                let start = input.position();
                 match any_char().parse(input).await {
                     Done(Self::TOKEN, r) => {
-                        let end = r.metrics();
+                        let end = r.position();
                         Done(
                             Self {
                                 span: Span::new(start, end),
@@ -111,7 +122,15 @@ macro_rules! declare_tokens {
         impl Token for $name {
             type Base = &'static str;
             const TOKEN: Self::Base = $expr;
+        }
 
+        impl AnyToken for $name {
+            fn span_mut(&mut self) -> &mut Span {
+                &mut self.span
+            }
+        }
+
+        impl Spanned for $name {
             fn span(&self) -> Span {
                 self.span.clone()
             }
@@ -123,22 +142,22 @@ macro_rules! declare_tokens {
 
             async fn parse<S: crate::InputStream>(
                 input: S,
+                _ctx: &crate::ParseContext,
             ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
-                let start = input.metrics();
-                let rest = match atom(Self::TOKEN).parse(input).await {
-                    Done(_, r) => r,
-                    Fail(_, r) => {
-                        return Fail(
-                            ParseTokenError {
-                                span: Span::points(start),
-                                _mark: PhantomData,
-                            },
-                            r,
-                        );
-                    }
-                    Fatal(e, _) => e.never(),
-                };
-                let end = rest.metrics();
+                // the whole token is read, so that `let` does not match the start of `letter`.
+                let start = input.position();
+                let anchor = input.anchor();
+                let (_, text, rest) = Box::pin(scan(input)).await;
+                if text != Self::TOKEN {
+                    return Fail(
+                        ParseTokenError {
+                            span: Span::points(start),
+                            _mark: PhantomData,
+                        },
+                        rest.rewind(anchor).into(),
+                    );
+                }
+                let end = rest.position();
                 let span = Span::new(start, end);
                 let me = Self { span };
 
@@ -160,8 +179,61 @@ declare_short_tokens![
     RBrace   = '}';
     Eq       = '=';
     Comma    = ',';
+    Lt       = '<';
+    Gt       = '>';
+    Percent  = '%';
+    Amp      = '&';
+    Pipe     = '|';
+    Caret    = '^';
+    Tilde    = '~';
+    Dot      = '.';
 ];
 
 declare_tokens![
-    Let = "let";
+    Let      = "let";
+    Infixl   = "infixl";
+    Infixr   = "infixr";
+    Infix    = "infix";
+    EqEq     = "==";
+    Ne       = "!=";
+    Le       = "<=";
+    Ge       = ">=";
+    StarStar = "**";
+    Shl      = "<<";
+    Shr      = ">>";
+    DotDot   = "..";
+    DotDotEq = "..=";
+    For      = "for";
+    In       = "in";
+    Import   = "import";
+    As       = "as";
+    Where    = "where";
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::{Infix, Let};
+    use crate::{Parse, ParseContext, Position};
+    use minilet_utils::stream::StrStream;
+    use parcom::{prelude::*, IntoMeasured};
+
+    fn parses<T: Parse>(source: &str) -> bool {
+        let input = IntoMeasured::<Position>::into_measured_with(
+            StrStream::new(source),
+            Default::default(),
+        );
+        matches!(
+            pollster::block_on(T::parse(input, &ParseContext::new())),
+            Done(_, _)
+        )
+    }
+
+    #[test]
+    fn keywords_match_whole_words() {
+        assert!(parses::<Let>("let x"));
+        assert!(parses::<Let>("let(x)"));
+        assert!(!parses::<Let>("letter"));
+        assert!(parses::<Infix>("infix 6"));
+        assert!(!parses::<Infix>("infixl 6"));
+    }
+}
@@ -1,14 +1,21 @@
-use crate::util::any_char;
-use crate::{Parse, Span};
+use crate::util::take_while;
+use crate::{Parse, ParseContext, Span, Spanned};
 use parcom::prelude::*;
-use parcom::{Never, Parser};
+use parcom::Never;
 
+/// Whitespace and `//` line comments between tokens.
 #[derive(Debug)]
 pub struct Trivia {
     pub text: String,
     pub span: Span,
 }
 
+impl Spanned for Trivia {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
 impl Trivia {
     pub fn empty(span: Span) -> Self {
         Self {
@@ -24,31 +31,37 @@ impl Parse for Trivia {
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        _ctx: &ParseContext,
     ) -> ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let start = input.metrics();
+        let start = input.position();
 
         let mut rest = input;
         let mut buf = String::new();
-        let mut anchor = rest.anchor();
         loop {
-            match any_char().parse(rest).await {
-                Done(c, r) if c.is_ascii_whitespace() => {
-                    buf.push(c);
-                    anchor = r.anchor();
-                    rest = r;
-                }
-                Done(_, r) => {
-                    rest = r.rewind(anchor);
-                    break;
-                }
-                Fail(_, r) | Fatal(_, r) => {
-                    rest = r.rewind(anchor);
-                    break;
-                }
-            };
+            let (space, r) = take_while(rest, |c| c.is_ascii_whitespace()).await;
+            buf.push_str(&space);
+            rest = r;
+
+            let anchor = rest.anchor();
+            let mut count = 0;
+            let (slashes, r) = take_while(rest, |c| {
+                count += 1;
+                c == '/' && count <= 2
+            })
+            .await;
+            if slashes.len() < 2 {
+                rest = r.rewind(anchor);
+                break;
+            }
+
+            // the newline ending the comment is left to the whitespace run.
+            let (comment, r) = take_while(r, |c| c != '\n').await;
+            buf.push_str(&slashes);
+            buf.push_str(&comment);
+            rest = r;
         }
 
-        let end = rest.metrics();
+        let end = rest.position();
         let me = Self {
             text: buf,
             span: Span::new(start, end),
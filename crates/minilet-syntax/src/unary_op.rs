@@ -1,11 +1,23 @@
-use crate::{util::any_char, Parse, Span};
+use crate::util::any_char;
+use crate::{Parse, ParseContext, Span, Spanned};
 use parcom::prelude::*;
-use parcom::{Never, Parser};
+use parcom::Never;
 
 #[derive(Debug)]
 pub enum UnaryOp {
     Plus { span: Span },
     Sub { span: Span },
+    BitNot { span: Span },
+}
+
+impl Spanned for UnaryOp {
+    fn span(&self) -> Span {
+        match self {
+            UnaryOp::Plus { span } | UnaryOp::Sub { span } | UnaryOp::BitNot { span } => {
+                span.clone()
+            }
+        }
+    }
 }
 
 impl Parse for UnaryOp {
@@ -14,27 +26,31 @@ impl Parse for UnaryOp {
 
     async fn parse<S: crate::InputStream>(
         input: S,
+        _ctx: &ParseContext,
     ) -> parcom::ParseResult<S, Self, Self::Error, Self::Fatal> {
-        let start = input.metrics();
+        // a single character, so that prefix operators stack as in `-~x` without a space.
+        let start = input.position();
+        let anchor = input.anchor();
         let (op_char, rest) = match any_char().parse(input).await {
-            Done(v, r) => (v, r),
+            Done(c, r) => (c, r),
             Fail(_, r) => {
-                return Fail(
-                    ParseUnaryOpError::Missing {
-                        span: Span::points(start),
-                    },
-                    r,
-                )
+                let span = Span::points(start);
+                return Fail(ParseUnaryOpError::Missing { span }, r);
             }
             Fatal(e, _) => e.never(),
         };
 
-        let end = rest.metrics();
-        let span = Span::new(start, end);
+        let span = Span::new(start, rest.position());
         let op = match op_char {
             '+' => UnaryOp::Plus { span },
             '-' => UnaryOp::Sub { span },
-            _ => return Fail(ParseUnaryOpError::UnknownSymbol { span }, rest.into()),
+            '~' => UnaryOp::BitNot { span },
+            _ => {
+                return Fail(
+                    ParseUnaryOpError::UnknownSymbol { span },
+                    rest.rewind(anchor).into(),
+                )
+            }
         };
 
         Done(op, rest)
@@ -1,7 +1,10 @@
+use std::future::Future;
 use std::ops::Deref;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 use parcom::prelude::*;
-use parcom::ParcomStream;
+use parcom::{ParcomStream, ParseStream};
 
 pub fn any_char() -> AnyChar {
     AnyChar
@@ -30,3 +33,178 @@ impl<S: ParcomStream<Segment = str>> Parser<S> for AnyChar {
         }
     }
 }
+
+/// Consumes the longest prefix of `input` whose characters satisfy `accept`, in order.
+///
+/// Segments are scanned directly and the stream is advanced once at the end, instead of parsing
+/// a character at a time. Runs of ASCII are matched byte by byte without decoding.
+pub async fn take_while<S: ParcomStream<Segment = str>>(
+    input: S,
+    mut accept: impl FnMut(char) -> bool,
+) -> (String, S) {
+    let mut text = String::new();
+    let mut segments = input.segments();
+    'segments: while let Some(segment) = segments.next(0).await {
+        let mut rest: &str = &segment;
+        loop {
+            let ascii = rest
+                .bytes()
+                .position(|b| !b.is_ascii() || !accept(b as char))
+                .unwrap_or(rest.len());
+            text.push_str(&rest[..ascii]);
+            rest = &rest[ascii..];
+
+            match rest.chars().next() {
+                None => break,
+                Some(c) if !c.is_ascii() && accept(c) => {
+                    text.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+                Some(_) => break 'segments,
+            }
+        }
+    }
+
+    let rest = input.advance(text.len()).await;
+    (text, rest)
+}
+
+/// Returns the next character without consuming it.
+pub async fn peek_char<S: ParseStream<Segment = str>>(input: S) -> (Option<char>, S) {
+    let anchor = input.anchor();
+    match any_char().parse(input).await {
+        Done(c, r) => (Some(c), r.rewind(anchor)),
+        Fail(_, r) => (None, r.rewind(anchor)),
+        Fatal(e, _) => e.never(),
+    }
+}
+
+/// Stack left free before polling a nested parser; more than any one level of nesting takes,
+/// even in unoptimised builds.
+const STACK_RED_ZONE: usize = 1 << 20;
+/// Size of each stack segment allocated once the red zone is reached.
+const STACK_SEGMENT: usize = 8 << 20;
+
+/// Polls `F` on a fresh stack segment when the current stack is nearly used up, so that parsers
+/// recursing through nested input do not overflow it whatever the thread's stack size.
+pub(crate) struct GrowStack<F>(pub(crate) Pin<Box<F>>);
+
+impl<F: Future> Future for GrowStack<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = self.0.as_mut();
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || inner.poll(cx))
+    }
+}
+
+/// Deterministic pseudo-random numbers for generated test inputs, so that tests need no extra
+/// dependencies and failures reproduce.
+#[cfg(test)]
+pub(crate) struct Rng(u64);
+
+#[cfg(test)]
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// A number below `bound`, which must not be zero.
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        // xorshift64
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+
+    pub(crate) fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// Prints `expr` with every binary operation in parentheses, showing how operators grouped.
+#[cfg(test)]
+pub(crate) fn grouping(expr: &crate::Expr) -> String {
+    use crate::{Expr, ToSource};
+
+    match expr {
+        Expr::Bin(bin) => format!(
+            "({} {} {})",
+            grouping(&bin.lhs),
+            bin.op.kind.to_source(),
+            grouping(&bin.rhs)
+        ),
+        _ => expr.to_source(),
+    }
+}
+
+/// Every way of splitting `source` into pieces at character boundaries.
+#[cfg(test)]
+pub(crate) fn splits(source: &str) -> Vec<Vec<&str>> {
+    let boundaries: Vec<_> = source.char_indices().map(|(i, _)| i).skip(1).collect();
+    (0..1u32 << boundaries.len())
+        .map(|mask| {
+            let mut pieces = Vec::new();
+            let mut start = 0;
+            for (bit, &end) in boundaries.iter().enumerate() {
+                if mask & 1 << bit != 0 {
+                    pieces.push(&source[start..end]);
+                    start = end;
+                }
+            }
+            pieces.push(&source[start..]);
+            pieces
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{any_char, splits, take_while};
+    use minilet_utils::stream::RopeStream;
+    use parcom::{prelude::*, ParcomSegmentIterator, ParcomStream};
+
+    #[test]
+    fn any_char_reads_across_segments() {
+        let source = "aé🙂\nb";
+        for pieces in splits(source) {
+            let mut input = RopeStream::new(&pieces);
+            let mut chars = String::new();
+            loop {
+                input = match pollster::block_on(any_char().parse(input)) {
+                    Done(c, rest) => {
+                        chars.push(c);
+                        rest
+                    }
+                    Fail(_, _) => break,
+                    Fatal(e, _) => e.never(),
+                };
+            }
+            assert_eq!(chars, source, "{pieces:?}");
+        }
+
+        let empty = ["", ""];
+        assert!(matches!(
+            pollster::block_on(any_char().parse(RopeStream::new(&empty))),
+            Fail(_, _)
+        ));
+    }
+
+    #[test]
+    fn take_while_reads_across_segments() {
+        let source = "ab\u{e9}1 \u{e9}x";
+        for pieces in splits(source) {
+            let input = RopeStream::new(&pieces);
+            let (text, rest) = pollster::block_on(take_while(input, char::is_alphanumeric));
+            assert_eq!(text, "ab\u{e9}1", "{pieces:?}");
+
+            let mut segments = rest.segments();
+            let mut rest = String::new();
+            while let Some(segment) = pollster::block_on(segments.next(0)) {
+                rest.push_str(segment);
+            }
+            assert_eq!(rest, " \u{e9}x", "{pieces:?}");
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! Traversal of syntax trees by reference.
+//!
+//! Every method of [`Visit`] defaults to the matching `walk_*` function, which visits the
+//! children of the node in source order. Overriding a method and calling the `walk_*` function
+//! from it continues the traversal below the node.
+use crate::{
+    binder::{Binder, OpBinder},
+    expr::BinOp,
+    literal::{IntegerLiteral, StringLiteral},
+    op::{Op, OpKind, OpSymbol},
+    recovery::ErrorNode,
+    relaxed::Relaxed,
+    stmt::{Binding, FixityKeyword, StmtFixity, StmtImport, StmtLet},
+    term::{app::App, block::Block, field::Field, for_loop::For, let_in::LetIn, Where},
+    token::{AnyToken, Token},
+    unary_op::UnaryOp,
+    Expr, Ident, Literal, Parse, SourceFile, Spacing, Stmt, Stmts, Term, Trivia, Tuple, Unary,
+};
+
+pub trait Visit {
+    fn visit_source_file(&mut self, node: &SourceFile) {
+        walk_source_file(self, node)
+    }
+
+    fn visit_stmts(&mut self, node: &Stmts) {
+        walk_stmts(self, node)
+    }
+
+    fn visit_stmt(&mut self, node: &Stmt) {
+        walk_stmt(self, node)
+    }
+
+    fn visit_stmt_let(&mut self, node: &StmtLet) {
+        walk_stmt_let(self, node)
+    }
+
+    fn visit_binding(&mut self, node: &Binding) {
+        walk_binding(self, node)
+    }
+
+    fn visit_stmt_fixity(&mut self, node: &StmtFixity) {
+        walk_stmt_fixity(self, node)
+    }
+
+    fn visit_fixity_keyword(&mut self, node: &FixityKeyword) {
+        walk_fixity_keyword(self, node)
+    }
+
+    fn visit_stmt_import(&mut self, node: &StmtImport) {
+        walk_stmt_import(self, node)
+    }
+
+    fn visit_binder(&mut self, node: &Binder) {
+        walk_binder(self, node)
+    }
+
+    fn visit_op_binder(&mut self, node: &OpBinder) {
+        walk_op_binder(self, node)
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        walk_expr(self, node)
+    }
+
+    fn visit_bin_op(&mut self, node: &BinOp) {
+        walk_bin_op(self, node)
+    }
+
+    fn visit_op(&mut self, node: &Op) {
+        walk_op(self, node)
+    }
+
+    fn visit_op_kind(&mut self, node: &OpKind) {
+        walk_op_kind(self, node)
+    }
+
+    fn visit_term(&mut self, node: &Term) {
+        walk_term(self, node)
+    }
+
+    fn visit_tuple(&mut self, node: &Tuple) {
+        walk_tuple(self, node)
+    }
+
+    fn visit_block(&mut self, node: &Block) {
+        walk_block(self, node)
+    }
+
+    fn visit_unary(&mut self, node: &Unary) {
+        walk_unary(self, node)
+    }
+
+    fn visit_app(&mut self, node: &App) {
+        walk_app(self, node)
+    }
+
+    fn visit_for(&mut self, node: &For) {
+        walk_for(self, node)
+    }
+
+    fn visit_field(&mut self, node: &Field) {
+        walk_field(self, node)
+    }
+
+    fn visit_let_in(&mut self, node: &LetIn) {
+        walk_let_in(self, node)
+    }
+
+    fn visit_where(&mut self, node: &Where) {
+        walk_where(self, node)
+    }
+
+    fn visit_literal(&mut self, node: &Literal) {
+        walk_literal(self, node)
+    }
+
+    fn visit_integer_literal(&mut self, _node: &IntegerLiteral) {}
+
+    fn visit_string_literal(&mut self, _node: &StringLiteral) {}
+
+    fn visit_ident(&mut self, _node: &Ident) {}
+
+    fn visit_op_symbol(&mut self, _node: &OpSymbol) {}
+
+    fn visit_unary_op(&mut self, _node: &UnaryOp) {}
+
+    fn visit_error_node(&mut self, _node: &ErrorNode) {}
+
+    fn visit_trivia(&mut self, _node: &Trivia) {}
+
+    fn visit_spacing(&mut self, _node: &Spacing) {}
+
+    fn visit_token(&mut self, _node: &dyn AnyToken) {}
+}
+
+pub fn walk_source_file<V: Visit + ?Sized>(v: &mut V, node: &SourceFile) {
+    v.visit_trivia(&node.leading_trivia);
+    v.visit_stmts(&node.stmts);
+    v.visit_trivia(&node.trailing_trivia);
+}
+
+pub fn walk_stmts<V: Visit + ?Sized>(v: &mut V, node: &Stmts) {
+    let (first, lasts) = node.stmts.parts();
+    if let Some(first) = first {
+        v.visit_stmt(first);
+    }
+    for (semi, stmt) in lasts {
+        walk_relaxed_token(v, semi);
+        v.visit_stmt(stmt);
+    }
+    if let Some((trivia, semi)) = &node.trailing_semi {
+        v.visit_trivia(trivia);
+        v.visit_token(semi);
+    }
+}
+
+pub fn walk_stmt<V: Visit + ?Sized>(v: &mut V, node: &Stmt) {
+    match node {
+        Stmt::Let(n) => v.visit_stmt_let(n),
+        Stmt::Fixity(n) => v.visit_stmt_fixity(n),
+        Stmt::Import(n) => v.visit_stmt_import(n),
+        Stmt::Expr(n) => v.visit_expr(n),
+        Stmt::Error(n) => v.visit_error_node(n),
+    }
+}
+
+pub fn walk_stmt_let<V: Visit + ?Sized>(v: &mut V, node: &StmtLet) {
+    v.visit_token(&node.let_token);
+    v.visit_spacing(&node.let_spacing);
+    v.visit_binding(&node.binding);
+}
+
+pub fn walk_binding<V: Visit + ?Sized>(v: &mut V, node: &Binding) {
+    v.visit_binder(&node.binder);
+    v.visit_trivia(&node.binder_trivia);
+    v.visit_token(&node.eq);
+    v.visit_trivia(&node.eq_trivia);
+    v.visit_expr(&node.expr);
+}
+
+pub fn walk_stmt_fixity<V: Visit + ?Sized>(v: &mut V, node: &StmtFixity) {
+    v.visit_fixity_keyword(&node.keyword);
+    v.visit_spacing(&node.keyword_spacing);
+    v.visit_integer_literal(&node.precedence);
+    v.visit_spacing(&node.precedence_spacing);
+    v.visit_op_symbol(&node.symbol);
+}
+
+pub fn walk_fixity_keyword<V: Visit + ?Sized>(v: &mut V, node: &FixityKeyword) {
+    match node {
+        FixityKeyword::Infixl(t) => v.visit_token(t),
+        FixityKeyword::Infixr(t) => v.visit_token(t),
+        FixityKeyword::Infix(t) => v.visit_token(t),
+    }
+}
+
+pub fn walk_stmt_import<V: Visit + ?Sized>(v: &mut V, node: &StmtImport) {
+    v.visit_token(&node.import_token);
+    v.visit_spacing(&node.import_spacing);
+    v.visit_string_literal(&node.path);
+    v.visit_spacing(&node.path_spacing);
+    v.visit_token(&node.as_token);
+    v.visit_spacing(&node.as_spacing);
+    v.visit_ident(&node.alias);
+}
+
+pub fn walk_binder<V: Visit + ?Sized>(v: &mut V, node: &Binder) {
+    match node {
+        Binder::Ident(n) => v.visit_ident(n),
+        Binder::Op(n) => v.visit_op_binder(n),
+    }
+}
+
+pub fn walk_op_binder<V: Visit + ?Sized>(v: &mut V, node: &OpBinder) {
+    v.visit_token(&node.lparen);
+    v.visit_op_symbol(&node.symbol);
+    v.visit_token(&node.rparen);
+}
+
+pub fn walk_expr<V: Visit + ?Sized>(v: &mut V, node: &Expr) {
+    match node {
+        Expr::Term(n) => v.visit_term(n),
+        Expr::Bin(n) => v.visit_bin_op(n),
+        Expr::Error(n) => v.visit_error_node(n),
+    }
+}
+
+pub fn walk_bin_op<V: Visit + ?Sized>(v: &mut V, node: &BinOp) {
+    v.visit_expr(&node.lhs);
+    v.visit_op(&node.op);
+    v.visit_expr(&node.rhs);
+}
+
+pub fn walk_op<V: Visit + ?Sized>(v: &mut V, node: &Op) {
+    v.visit_spacing(&node.leading_spacing);
+    v.visit_op_kind(&node.kind);
+    v.visit_spacing(&node.trailing_spacing);
+}
+
+pub fn walk_op_kind<V: Visit + ?Sized>(v: &mut V, node: &OpKind) {
+    match node {
+        OpKind::Add(t) => v.visit_token(t),
+        OpKind::Sub(t) => v.visit_token(t),
+        OpKind::Mul(t) => v.visit_token(t),
+        OpKind::Div(t) => v.visit_token(t),
+        OpKind::Rem(t) => v.visit_token(t),
+        OpKind::Pow(t) => v.visit_token(t),
+        OpKind::BitAnd(t) => v.visit_token(t),
+        OpKind::BitOr(t) => v.visit_token(t),
+        OpKind::BitXor(t) => v.visit_token(t),
+        OpKind::Shl(t) => v.visit_token(t),
+        OpKind::Shr(t) => v.visit_token(t),
+        OpKind::Eq(t) => v.visit_token(t),
+        OpKind::Ne(t) => v.visit_token(t),
+        OpKind::Lt(t) => v.visit_token(t),
+        OpKind::Gt(t) => v.visit_token(t),
+        OpKind::Le(t) => v.visit_token(t),
+        OpKind::Ge(t) => v.visit_token(t),
+        OpKind::Range(t) => v.visit_token(t),
+        OpKind::RangeInclusive(t) => v.visit_token(t),
+        OpKind::Custom(s) => v.visit_op_symbol(s),
+    }
+}
+
+pub fn walk_term<V: Visit + ?Sized>(v: &mut V, node: &Term) {
+    match node {
+        Term::Tuple(n) => v.visit_tuple(n),
+        Term::Literal(n) => v.visit_literal(n),
+        Term::Ident(n) => v.visit_ident(n),
+        Term::Unary(n) => v.visit_unary(n),
+        Term::Block(n) => v.visit_block(n),
+        Term::App(n) => v.visit_app(n),
+        Term::For(n) => v.visit_for(n),
+        Term::Field(n) => v.visit_field(n),
+        Term::LetIn(n) => v.visit_let_in(n),
+        Term::Where(n) => v.visit_where(n),
+        Term::Error(n) => v.visit_error_node(n),
+    }
+}
+
+pub fn walk_tuple<V: Visit + ?Sized>(v: &mut V, node: &Tuple) {
+    v.visit_token(&node.lparen);
+    v.visit_trivia(&node.ltrivia);
+    let (first, lasts) = node.items.parts();
+    if let Some(first) = first {
+        v.visit_expr(first);
+    }
+    for (comma, item) in lasts {
+        walk_relaxed_token(v, comma);
+        v.visit_expr(item);
+    }
+    v.visit_trivia(&node.rtrivia);
+    v.visit_token(&node.rparen);
+}
+
+pub fn walk_block<V: Visit + ?Sized>(v: &mut V, node: &Block) {
+    v.visit_token(&node.lbrace);
+    v.visit_trivia(&node.ltrivia);
+    v.visit_stmts(&node.stmts);
+    v.visit_trivia(&node.rtrivia);
+    v.visit_token(&node.rbrace);
+}
+
+pub fn walk_unary<V: Visit + ?Sized>(v: &mut V, node: &Unary) {
+    v.visit_unary_op(&node.op);
+    v.visit_term(&node.term);
+}
+
+pub fn walk_app<V: Visit + ?Sized>(v: &mut V, node: &App) {
+    v.visit_term(&node.receiver);
+    v.visit_tuple(&node.arg);
+}
+
+pub fn walk_for<V: Visit + ?Sized>(v: &mut V, node: &For) {
+    v.visit_token(&node.for_token);
+    v.visit_spacing(&node.for_spacing);
+    v.visit_binder(&node.binder);
+    v.visit_spacing(&node.binder_spacing);
+    v.visit_token(&node.in_token);
+    v.visit_spacing(&node.in_spacing);
+    v.visit_expr(&node.iter);
+    v.visit_trivia(&node.iter_trivia);
+    v.visit_block(&node.body);
+}
+
+pub fn walk_field<V: Visit + ?Sized>(v: &mut V, node: &Field) {
+    v.visit_term(&node.receiver);
+    v.visit_token(&node.dot);
+    v.visit_ident(&node.name);
+}
+
+pub fn walk_let_in<V: Visit + ?Sized>(v: &mut V, node: &LetIn) {
+    v.visit_stmt_let(&node.binding);
+    v.visit_trivia(&node.in_trivia);
+    v.visit_token(&node.in_token);
+    v.visit_trivia(&node.body_trivia);
+    v.visit_expr(&node.body);
+}
+
+pub fn walk_where<V: Visit + ?Sized>(v: &mut V, node: &Where) {
+    v.visit_expr(&node.expr);
+    v.visit_trivia(&node.where_trivia);
+    v.visit_token(&node.where_token);
+    v.visit_trivia(&node.bindings_trivia);
+    let (first, lasts) = node.bindings.parts();
+    if let Some(first) = first {
+        v.visit_binding(first);
+    }
+    for (comma, binding) in lasts {
+        walk_relaxed_token(v, comma);
+        v.visit_binding(binding);
+    }
+}
+
+pub fn walk_literal<V: Visit + ?Sized>(v: &mut V, node: &Literal) {
+    match node {
+        Literal::Integer(n) => v.visit_integer_literal(n),
+        Literal::String(n) => v.visit_string_literal(n),
+    }
+}
+
+fn walk_relaxed_token<V: Visit + ?Sized, T: Token + Parse>(v: &mut V, node: &Relaxed<T>) {
+    v.visit_trivia(&node.leading_trivia);
+    v.visit_token(&node.item);
+    v.visit_trivia(&node.trailing_trivia);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Visit;
+    use crate::{parse_program, token::AnyToken, Ident};
+
+    /// Records identifiers and tokens in the order they are visited.
+    struct Record<'a> {
+        source: &'a str,
+        idents: Vec<String>,
+        tokens: Vec<&'a str>,
+    }
+
+    impl Visit for Record<'_> {
+        fn visit_ident(&mut self, node: &Ident) {
+            self.idents.push(node.text.clone());
+        }
+
+        fn visit_token(&mut self, node: &dyn AnyToken) {
+            self.tokens.push(&self.source[node.span().bytes()]);
+        }
+    }
+
+    #[test]
+    fn walks_a_parsed_program_in_source_order() {
+        let source = "let x = f(1, y);\nfor i in 0..n { x.len }";
+        let stmts = parse_program(source).unwrap();
+        let mut record = Record {
+            source,
+            idents: Vec::new(),
+            tokens: Vec::new(),
+        };
+        let visitor: &mut dyn Visit = &mut record;
+        visitor.visit_stmts(&stmts);
+
+        assert_eq!(record.idents, ["x", "f", "y", "i", "n", "x", "len"]);
+        assert_eq!(
+            record.tokens,
+            ["let", "=", "(", ",", ")", ";", "for", "in", "..", "{", ".", "}"]
+        );
+    }
+}
@@ -0,0 +1,394 @@
+//! Traversal of syntax trees by mutable reference.
+//!
+//! The mutable counterpart of [`crate::visit::Visit`]; nodes can be rewritten in place while
+//! walking.
+use crate::{
+    binder::{Binder, OpBinder},
+    expr::BinOp,
+    literal::{IntegerLiteral, StringLiteral},
+    op::{Op, OpKind, OpSymbol},
+    recovery::ErrorNode,
+    relaxed::Relaxed,
+    stmt::{Binding, FixityKeyword, StmtFixity, StmtImport, StmtLet},
+    term::{app::App, block::Block, field::Field, for_loop::For, let_in::LetIn, Where},
+    token::{AnyToken, Token},
+    unary_op::UnaryOp,
+    Expr, Ident, Literal, Parse, SourceFile, Spacing, Stmt, Stmts, Term, Trivia, Tuple, Unary,
+};
+
+pub trait VisitMut {
+    fn visit_source_file_mut(&mut self, node: &mut SourceFile) {
+        walk_source_file_mut(self, node)
+    }
+
+    fn visit_stmts_mut(&mut self, node: &mut Stmts) {
+        walk_stmts_mut(self, node)
+    }
+
+    fn visit_stmt_mut(&mut self, node: &mut Stmt) {
+        walk_stmt_mut(self, node)
+    }
+
+    fn visit_stmt_let_mut(&mut self, node: &mut StmtLet) {
+        walk_stmt_let_mut(self, node)
+    }
+
+    fn visit_binding_mut(&mut self, node: &mut Binding) {
+        walk_binding_mut(self, node)
+    }
+
+    fn visit_stmt_fixity_mut(&mut self, node: &mut StmtFixity) {
+        walk_stmt_fixity_mut(self, node)
+    }
+
+    fn visit_fixity_keyword_mut(&mut self, node: &mut FixityKeyword) {
+        walk_fixity_keyword_mut(self, node)
+    }
+
+    fn visit_stmt_import_mut(&mut self, node: &mut StmtImport) {
+        walk_stmt_import_mut(self, node)
+    }
+
+    fn visit_binder_mut(&mut self, node: &mut Binder) {
+        walk_binder_mut(self, node)
+    }
+
+    fn visit_op_binder_mut(&mut self, node: &mut OpBinder) {
+        walk_op_binder_mut(self, node)
+    }
+
+    fn visit_expr_mut(&mut self, node: &mut Expr) {
+        walk_expr_mut(self, node)
+    }
+
+    fn visit_bin_op_mut(&mut self, node: &mut BinOp) {
+        walk_bin_op_mut(self, node)
+    }
+
+    fn visit_op_mut(&mut self, node: &mut Op) {
+        walk_op_mut(self, node)
+    }
+
+    fn visit_op_kind_mut(&mut self, node: &mut OpKind) {
+        walk_op_kind_mut(self, node)
+    }
+
+    fn visit_term_mut(&mut self, node: &mut Term) {
+        walk_term_mut(self, node)
+    }
+
+    fn visit_tuple_mut(&mut self, node: &mut Tuple) {
+        walk_tuple_mut(self, node)
+    }
+
+    fn visit_block_mut(&mut self, node: &mut Block) {
+        walk_block_mut(self, node)
+    }
+
+    fn visit_unary_mut(&mut self, node: &mut Unary) {
+        walk_unary_mut(self, node)
+    }
+
+    fn visit_app_mut(&mut self, node: &mut App) {
+        walk_app_mut(self, node)
+    }
+
+    fn visit_for_mut(&mut self, node: &mut For) {
+        walk_for_mut(self, node)
+    }
+
+    fn visit_field_mut(&mut self, node: &mut Field) {
+        walk_field_mut(self, node)
+    }
+
+    fn visit_let_in_mut(&mut self, node: &mut LetIn) {
+        walk_let_in_mut(self, node)
+    }
+
+    fn visit_where_mut(&mut self, node: &mut Where) {
+        walk_where_mut(self, node)
+    }
+
+    fn visit_literal_mut(&mut self, node: &mut Literal) {
+        walk_literal_mut(self, node)
+    }
+
+    fn visit_integer_literal_mut(&mut self, _node: &mut IntegerLiteral) {}
+
+    fn visit_string_literal_mut(&mut self, _node: &mut StringLiteral) {}
+
+    fn visit_ident_mut(&mut self, _node: &mut Ident) {}
+
+    fn visit_op_symbol_mut(&mut self, _node: &mut OpSymbol) {}
+
+    fn visit_unary_op_mut(&mut self, _node: &mut UnaryOp) {}
+
+    fn visit_error_node_mut(&mut self, _node: &mut ErrorNode) {}
+
+    fn visit_trivia_mut(&mut self, _node: &mut Trivia) {}
+
+    fn visit_spacing_mut(&mut self, _node: &mut Spacing) {}
+
+    fn visit_token_mut(&mut self, _node: &mut dyn AnyToken) {}
+}
+
+pub fn walk_source_file_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut SourceFile) {
+    v.visit_trivia_mut(&mut node.leading_trivia);
+    v.visit_stmts_mut(&mut node.stmts);
+    v.visit_trivia_mut(&mut node.trailing_trivia);
+}
+
+pub fn walk_stmts_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Stmts) {
+    let (first, lasts) = node.stmts.parts_mut();
+    if let Some(first) = first {
+        v.visit_stmt_mut(first);
+    }
+    for (semi, stmt) in lasts {
+        walk_relaxed_token_mut(v, semi);
+        v.visit_stmt_mut(stmt);
+    }
+    if let Some((trivia, semi)) = &mut node.trailing_semi {
+        v.visit_trivia_mut(trivia);
+        v.visit_token_mut(semi);
+    }
+}
+
+pub fn walk_stmt_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Stmt) {
+    match node {
+        Stmt::Let(n) => v.visit_stmt_let_mut(n),
+        Stmt::Fixity(n) => v.visit_stmt_fixity_mut(n),
+        Stmt::Import(n) => v.visit_stmt_import_mut(n),
+        Stmt::Expr(n) => v.visit_expr_mut(n),
+        Stmt::Error(n) => v.visit_error_node_mut(n),
+    }
+}
+
+pub fn walk_stmt_let_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut StmtLet) {
+    v.visit_token_mut(&mut node.let_token);
+    v.visit_spacing_mut(&mut node.let_spacing);
+    v.visit_binding_mut(&mut node.binding);
+}
+
+pub fn walk_binding_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Binding) {
+    v.visit_binder_mut(&mut node.binder);
+    v.visit_trivia_mut(&mut node.binder_trivia);
+    v.visit_token_mut(&mut node.eq);
+    v.visit_trivia_mut(&mut node.eq_trivia);
+    v.visit_expr_mut(&mut node.expr);
+}
+
+pub fn walk_stmt_fixity_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut StmtFixity) {
+    v.visit_fixity_keyword_mut(&mut node.keyword);
+    v.visit_spacing_mut(&mut node.keyword_spacing);
+    v.visit_integer_literal_mut(&mut node.precedence);
+    v.visit_spacing_mut(&mut node.precedence_spacing);
+    v.visit_op_symbol_mut(&mut node.symbol);
+}
+
+pub fn walk_fixity_keyword_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut FixityKeyword) {
+    match node {
+        FixityKeyword::Infixl(t) => v.visit_token_mut(t),
+        FixityKeyword::Infixr(t) => v.visit_token_mut(t),
+        FixityKeyword::Infix(t) => v.visit_token_mut(t),
+    }
+}
+
+pub fn walk_stmt_import_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut StmtImport) {
+    v.visit_token_mut(&mut node.import_token);
+    v.visit_spacing_mut(&mut node.import_spacing);
+    v.visit_string_literal_mut(&mut node.path);
+    v.visit_spacing_mut(&mut node.path_spacing);
+    v.visit_token_mut(&mut node.as_token);
+    v.visit_spacing_mut(&mut node.as_spacing);
+    v.visit_ident_mut(&mut node.alias);
+}
+
+pub fn walk_binder_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Binder) {
+    match node {
+        Binder::Ident(n) => v.visit_ident_mut(n),
+        Binder::Op(n) => v.visit_op_binder_mut(n),
+    }
+}
+
+pub fn walk_op_binder_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut OpBinder) {
+    v.visit_token_mut(&mut node.lparen);
+    v.visit_op_symbol_mut(&mut node.symbol);
+    v.visit_token_mut(&mut node.rparen);
+}
+
+pub fn walk_expr_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Expr) {
+    match node {
+        Expr::Term(n) => v.visit_term_mut(n),
+        Expr::Bin(n) => v.visit_bin_op_mut(n),
+        Expr::Error(n) => v.visit_error_node_mut(n),
+    }
+}
+
+pub fn walk_bin_op_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut BinOp) {
+    v.visit_expr_mut(&mut node.lhs);
+    v.visit_op_mut(&mut node.op);
+    v.visit_expr_mut(&mut node.rhs);
+}
+
+pub fn walk_op_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Op) {
+    v.visit_spacing_mut(&mut node.leading_spacing);
+    v.visit_op_kind_mut(&mut node.kind);
+    v.visit_spacing_mut(&mut node.trailing_spacing);
+}
+
+pub fn walk_op_kind_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut OpKind) {
+    match node {
+        OpKind::Add(t) => v.visit_token_mut(t),
+        OpKind::Sub(t) => v.visit_token_mut(t),
+        OpKind::Mul(t) => v.visit_token_mut(t),
+        OpKind::Div(t) => v.visit_token_mut(t),
+        OpKind::Rem(t) => v.visit_token_mut(t),
+        OpKind::Pow(t) => v.visit_token_mut(t),
+        OpKind::BitAnd(t) => v.visit_token_mut(t),
+        OpKind::BitOr(t) => v.visit_token_mut(t),
+        OpKind::BitXor(t) => v.visit_token_mut(t),
+        OpKind::Shl(t) => v.visit_token_mut(t),
+        OpKind::Shr(t) => v.visit_token_mut(t),
+        OpKind::Eq(t) => v.visit_token_mut(t),
+        OpKind::Ne(t) => v.visit_token_mut(t),
+        OpKind::Lt(t) => v.visit_token_mut(t),
+        OpKind::Gt(t) => v.visit_token_mut(t),
+        OpKind::Le(t) => v.visit_token_mut(t),
+        OpKind::Ge(t) => v.visit_token_mut(t),
+        OpKind::Range(t) => v.visit_token_mut(t),
+        OpKind::RangeInclusive(t) => v.visit_token_mut(t),
+        OpKind::Custom(s) => v.visit_op_symbol_mut(s),
+    }
+}
+
+pub fn walk_term_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Term) {
+    match node {
+        Term::Tuple(n) => v.visit_tuple_mut(n),
+        Term::Literal(n) => v.visit_literal_mut(n),
+        Term::Ident(n) => v.visit_ident_mut(n),
+        Term::Unary(n) => v.visit_unary_mut(n),
+        Term::Block(n) => v.visit_block_mut(n),
+        Term::App(n) => v.visit_app_mut(n),
+        Term::For(n) => v.visit_for_mut(n),
+        Term::Field(n) => v.visit_field_mut(n),
+        Term::LetIn(n) => v.visit_let_in_mut(n),
+        Term::Where(n) => v.visit_where_mut(n),
+        Term::Error(n) => v.visit_error_node_mut(n),
+    }
+}
+
+pub fn walk_tuple_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Tuple) {
+    v.visit_token_mut(&mut node.lparen);
+    v.visit_trivia_mut(&mut node.ltrivia);
+    let (first, lasts) = node.items.parts_mut();
+    if let Some(first) = first {
+        v.visit_expr_mut(first);
+    }
+    for (comma, item) in lasts {
+        walk_relaxed_token_mut(v, comma);
+        v.visit_expr_mut(item);
+    }
+    v.visit_trivia_mut(&mut node.rtrivia);
+    v.visit_token_mut(&mut node.rparen);
+}
+
+pub fn walk_block_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Block) {
+    v.visit_token_mut(&mut node.lbrace);
+    v.visit_trivia_mut(&mut node.ltrivia);
+    v.visit_stmts_mut(&mut node.stmts);
+    v.visit_trivia_mut(&mut node.rtrivia);
+    v.visit_token_mut(&mut node.rbrace);
+}
+
+pub fn walk_unary_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Unary) {
+    v.visit_unary_op_mut(&mut node.op);
+    v.visit_term_mut(&mut node.term);
+}
+
+pub fn walk_app_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut App) {
+    v.visit_term_mut(&mut node.receiver);
+    v.visit_tuple_mut(&mut node.arg);
+}
+
+pub fn walk_for_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut For) {
+    v.visit_token_mut(&mut node.for_token);
+    v.visit_spacing_mut(&mut node.for_spacing);
+    v.visit_binder_mut(&mut node.binder);
+    v.visit_spacing_mut(&mut node.binder_spacing);
+    v.visit_token_mut(&mut node.in_token);
+    v.visit_spacing_mut(&mut node.in_spacing);
+    v.visit_expr_mut(&mut node.iter);
+    v.visit_trivia_mut(&mut node.iter_trivia);
+    v.visit_block_mut(&mut node.body);
+}
+
+pub fn walk_field_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Field) {
+    v.visit_term_mut(&mut node.receiver);
+    v.visit_token_mut(&mut node.dot);
+    v.visit_ident_mut(&mut node.name);
+}
+
+pub fn walk_let_in_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut LetIn) {
+    v.visit_stmt_let_mut(&mut node.binding);
+    v.visit_trivia_mut(&mut node.in_trivia);
+    v.visit_token_mut(&mut node.in_token);
+    v.visit_trivia_mut(&mut node.body_trivia);
+    v.visit_expr_mut(&mut node.body);
+}
+
+pub fn walk_where_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Where) {
+    v.visit_expr_mut(&mut node.expr);
+    v.visit_trivia_mut(&mut node.where_trivia);
+    v.visit_token_mut(&mut node.where_token);
+    v.visit_trivia_mut(&mut node.bindings_trivia);
+    let (first, lasts) = node.bindings.parts_mut();
+    if let Some(first) = first {
+        v.visit_binding_mut(first);
+    }
+    for (comma, binding) in lasts {
+        walk_relaxed_token_mut(v, comma);
+        v.visit_binding_mut(binding);
+    }
+}
+
+pub fn walk_literal_mut<V: VisitMut + ?Sized>(v: &mut V, node: &mut Literal) {
+    match node {
+        Literal::Integer(n) => v.visit_integer_literal_mut(n),
+        Literal::String(n) => v.visit_string_literal_mut(n),
+    }
+}
+
+fn walk_relaxed_token_mut<V: VisitMut + ?Sized, T: Token + Parse>(
+    v: &mut V,
+    node: &mut Relaxed<T>,
+) {
+    v.visit_trivia_mut(&mut node.leading_trivia);
+    v.visit_token_mut(&mut node.item);
+    v.visit_trivia_mut(&mut node.trailing_trivia);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VisitMut;
+    use crate::{parse_program, Ident, ToSource};
+
+    /// Renames `x` to `value`.
+    struct Rename;
+
+    impl VisitMut for Rename {
+        fn visit_ident_mut(&mut self, node: &mut Ident) {
+            if node.text == "x" {
+                node.text = "value".to_string();
+            }
+        }
+    }
+
+    #[test]
+    fn rewrites_nodes_in_place() {
+        let mut stmts = parse_program("let x = { x + 1 };\nf(x, y)").unwrap();
+        let visitor: &mut dyn VisitMut = &mut Rename;
+        visitor.visit_stmts_mut(&mut stmts);
+
+        assert_eq!(stmts.to_source(), "let value = { value + 1 };\nf(value, y)");
+    }
+}
@@ -1,6 +1,12 @@
+mod chunked;
+mod rope;
+
+pub use chunked::{ChunkAnchor, ChunkRef, ChunkSegments, ChunkedStream};
 use parcom::{
     primitive::Nodes, IntoMeasured, MeasuredStream, Meter, Metrics, ParcomStream, RewindStream,
 };
+pub use rope::{RopeAnchor, RopeSegments, RopeStream};
+use std::future::Ready;
 
 #[derive(Debug, Clone)]
 pub struct StrStream<'me> {
@@ -16,18 +22,15 @@ impl<'me> StrStream<'me> {
 impl<'me> ParcomStream for StrStream<'me> {
     type Segment = str;
     type SegmentStream = Nodes<'me, str>;
-    type Advance = std::future::Ready<Self>;
+    type Advance = Ready<Self>;
 
     fn segments(&self) -> Self::SegmentStream {
         self.str.segments()
     }
 
+    /// Advances by `count` bytes, as parsers measure consumed input in `len_utf8` units.
     fn advance(mut self, count: usize) -> Self::Advance {
-        let mut chars = self.str.chars();
-        for _ in 0..count {
-            chars.next();
-        }
-        self.str = chars.as_str();
+        self.str = &self.str[count.min(self.str.len())..];
         std::future::ready(self)
     }
 }
@@ -53,21 +56,37 @@ impl<'me, M> IntoMeasured<M> for StrStream<'me>
 where
     M: Metrics<str>,
 {
-    type Measured = Measured<'me, M>;
+    type Measured = Measured<Self, M>;
 
     fn into_measured_with(self, meter: M::Meter) -> Self::Measured {
         Measured { meter, base: self }
     }
 }
 
+impl<'me> TextStream for StrStream<'me> {
+    fn fold_prefix<B>(&self, count: usize, init: B, mut f: impl FnMut(B, &str) -> B) -> B {
+        f(init, &self.str[..count.min(self.str.len())])
+    }
+}
+
+/// Text streams that can meter what they are advanced over.
+///
+/// Advancing must complete immediately, as [`Measured`] only meters text the stream already
+/// holds.
+pub trait TextStream: ParcomStream<Segment = str, Advance = Ready<Self>> + Clone {
+    /// Folds `f` over the pieces making up the next `count` bytes, in order.
+    fn fold_prefix<B>(&self, count: usize, init: B, f: impl FnMut(B, &str) -> B) -> B;
+}
+
 #[derive(Debug)]
-pub struct Measured<'me, M: Metrics<str>> {
+pub struct Measured<S, M: Metrics<str>> {
     meter: M::Meter,
-    base: StrStream<'me>,
+    base: S,
 }
 
-impl<'me, M> Clone for Measured<'me, M>
+impl<S, M> Clone for Measured<S, M>
 where
+    S: Clone,
     M::Meter: Clone,
     M: Metrics<str>,
 {
@@ -79,49 +98,55 @@ where
     }
 }
 
-impl<'me, M: Metrics<str>> ParcomStream for Measured<'me, M> {
+impl<S: TextStream, M: Metrics<str>> ParcomStream for Measured<S, M> {
     type Segment = str;
-    type SegmentStream = Nodes<'me, str>;
-    type Advance = std::future::Ready<Self>;
+    type SegmentStream = S::SegmentStream;
+    type Advance = Ready<Self>;
 
     fn segments(&self) -> Self::SegmentStream {
         self.base.segments()
     }
 
     fn advance(mut self, count: usize) -> Self::Advance {
-        let segment = self.base.str;
-        let mut chars = segment.char_indices();
-        chars.nth(count);
-        let offset = chars.offset();
-        self.meter = self.meter.advance(&segment[..offset]);
+        self.meter = self
+            .base
+            .fold_prefix(count, self.meter, |meter, piece| meter.advance(piece));
         self.base = self.base.advance(count).into_inner();
         std::future::ready(self)
     }
 }
 
-impl<'me, M> RewindStream for Measured<'me, M>
+impl<S, M> RewindStream for Measured<S, M>
 where
+    S: TextStream + RewindStream,
     M: Metrics<str>,
     M::Meter: Clone,
 {
-    type Anchor = MeasuredAnchor<'me, M>;
+    type Anchor = MeasuredAnchor<S, M>;
 
     fn anchor(&self) -> Self::Anchor {
         MeasuredAnchor {
-            stream: self.clone(),
+            base: self.base.anchor(),
+            meter: self.meter.clone(),
         }
     }
 
     fn rewind(self, anchor: Self::Anchor) -> Self {
-        anchor.stream
+        Self {
+            meter: anchor.meter,
+            base: self.base.rewind(anchor.base),
+        }
     }
 }
 
-pub struct MeasuredAnchor<'me, M: Metrics<str>> {
-    stream: Measured<'me, M>,
+/// The base stream's own anchor and the meter at that point, so anchoring does not clone the
+/// whole stream.
+pub struct MeasuredAnchor<S: RewindStream, M: Metrics<str>> {
+    base: S::Anchor,
+    meter: M::Meter,
 }
 
-impl<'me, M: Metrics<str>> MeasuredStream for Measured<'me, M> {
+impl<S, M: Metrics<str>> MeasuredStream for Measured<S, M> {
     type Metrics = M;
 
     fn metrics(&self) -> Self::Metrics {
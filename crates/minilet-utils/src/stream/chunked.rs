@@ -0,0 +1,404 @@
+//! Text read incrementally from an [`AsyncRead`].
+//!
+//! Bytes are decoded as they arrive. A character split between two reads is held back until the
+//! rest of it is read, and invalid UTF-8 is replaced with `U+FFFD`, so offsets count the decoded
+//! text rather than the raw input. Every decoded chunk is kept until all streams sharing the
+//! reader are dropped, which is what lets anchors rewind to any earlier point.
+use super::{Measured, TextStream};
+use futures_io::AsyncRead;
+use parcom::{IntoMeasured, Metrics, ParcomSegmentIterator, ParcomStream, RewindStream};
+use std::{
+    cell::RefCell,
+    future::{poll_fn, Ready},
+    io,
+    ops::Deref,
+    pin::Pin,
+    rc::Rc,
+};
+
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+struct Buffer<R> {
+    reader: R,
+    chunk_size: usize,
+    chunks: Vec<Rc<str>>,
+    /// Bytes read but not decoded yet: the start of a character whose rest is still unread.
+    pending: Vec<u8>,
+    eof: bool,
+    error: Option<io::Error>,
+}
+
+impl<R: AsyncRead + Unpin> Buffer<R> {
+    /// Reads until at least one more chunk is decoded. Returns `false` at the end of input.
+    async fn fill(this: &RefCell<Self>) -> bool {
+        let chunk_size = this.borrow().chunk_size;
+        let mut buf = vec![0; chunk_size];
+        loop {
+            if this.borrow().eof {
+                return false;
+            }
+
+            let read =
+                poll_fn(|cx| Pin::new(&mut this.borrow_mut().reader).poll_read(cx, &mut buf)).await;
+
+            let mut this = this.borrow_mut();
+            match read {
+                Ok(0) => {
+                    this.eof = true;
+                    return this.decode(true);
+                }
+                Ok(n) => {
+                    this.pending.extend_from_slice(&buf[..n]);
+                    if this.decode(false) {
+                        return true;
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => {
+                    this.eof = true;
+                    this.error = Some(e);
+                    return this.decode(true);
+                }
+            }
+        }
+    }
+
+    /// Moves the complete characters of `pending` into a new chunk, or all of it when `last`.
+    /// Returns whether a chunk was added.
+    fn decode(&mut self, last: bool) -> bool {
+        let len = if last {
+            self.pending.len()
+        } else {
+            self.pending.len() - incomplete_tail(&self.pending)
+        };
+        if len == 0 {
+            return false;
+        }
+
+        let chunk = String::from_utf8_lossy(&self.pending[..len]).into();
+        self.pending.drain(..len);
+        self.chunks.push(chunk);
+        true
+    }
+}
+
+/// Length of a character at the end of `bytes` that needs more bytes to be complete.
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    for i in 1..=bytes.len().min(3) {
+        let b = bytes[bytes.len() - i];
+        if b & 0xC0 == 0x80 {
+            continue;
+        }
+        let width = match b {
+            0xF0.. => 4,
+            0xE0.. => 3,
+            0xC0.. => 2,
+            _ => 1,
+        };
+        return if width > i { i } else { 0 };
+    }
+    0
+}
+
+/// A stream over text decoded from an [`AsyncRead`] such as a socket or a pipe.
+///
+/// Clones share the reader and the decoded text; each has its own position.
+pub struct ChunkedStream<R> {
+    buffer: Rc<RefCell<Buffer<R>>>,
+    chunk: usize,
+    offset: usize,
+}
+
+impl<R> ChunkedStream<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_chunk_size(reader, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates a stream reading at most `chunk_size` bytes at a time.
+    pub fn with_chunk_size(reader: R, chunk_size: usize) -> Self {
+        let buffer = Buffer {
+            reader,
+            chunk_size: chunk_size.max(1),
+            chunks: Vec::new(),
+            pending: Vec::new(),
+            eof: false,
+            error: None,
+        };
+        Self {
+            buffer: Rc::new(RefCell::new(buffer)),
+            chunk: 0,
+            offset: 0,
+        }
+    }
+
+    /// Takes the error that ended reading, if any. The input appears to end where it occurred.
+    ///
+    /// Keep a clone of the stream to call this after handing the stream to a parser.
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.buffer.borrow_mut().error.take()
+    }
+}
+
+impl<R> Clone for ChunkedStream<R> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer.clone(),
+            chunk: self.chunk,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<R> std::fmt::Debug for ChunkedStream<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChunkedStream")
+            .field("chunk", &self.chunk)
+            .field("offset", &self.offset)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: AsyncRead + Unpin> ParcomStream for ChunkedStream<R> {
+    type Segment = str;
+    type SegmentStream = ChunkSegments<R>;
+    type Advance = Ready<Self>;
+
+    fn segments(&self) -> Self::SegmentStream {
+        ChunkSegments {
+            stream: self.clone(),
+        }
+    }
+
+    /// Advances by `count` bytes. Parsers only advance over segments they have read, so this
+    /// never waits for the reader; advancing past the decoded text stops at its end.
+    fn advance(mut self, count: usize) -> Self::Advance {
+        let buffer = self.buffer.borrow();
+        let mut count = count;
+        while let Some(chunk) = buffer.chunks.get(self.chunk) {
+            let available = chunk.len() - self.offset;
+            if count < available {
+                self.offset += count;
+                break;
+            }
+            count -= available;
+            self.chunk += 1;
+            self.offset = 0;
+        }
+        drop(buffer);
+        std::future::ready(self)
+    }
+}
+
+impl<R: AsyncRead + Unpin> TextStream for ChunkedStream<R> {
+    fn fold_prefix<B>(&self, count: usize, init: B, mut f: impl FnMut(B, &str) -> B) -> B {
+        let buffer = self.buffer.borrow();
+        let mut acc = init;
+        let mut count = count;
+        let mut offset = self.offset;
+        for chunk in &buffer.chunks[self.chunk.min(buffer.chunks.len())..] {
+            if count == 0 {
+                break;
+            }
+            let end = chunk.len().min(offset + count);
+            acc = f(acc, &chunk[offset..end]);
+            count -= end - offset;
+            offset = 0;
+        }
+        acc
+    }
+}
+
+impl<R> RewindStream for ChunkedStream<R> {
+    type Anchor = ChunkAnchor;
+
+    fn anchor(&self) -> Self::Anchor {
+        ChunkAnchor {
+            chunk: self.chunk,
+            offset: self.offset,
+        }
+    }
+
+    fn rewind(mut self, anchor: Self::Anchor) -> Self {
+        self.chunk = anchor.chunk;
+        self.offset = anchor.offset;
+        self
+    }
+}
+
+/// A position in a [`ChunkedStream`]. It is only meaningful for streams sharing the same reader.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkAnchor {
+    chunk: usize,
+    offset: usize,
+}
+
+impl<R, M> IntoMeasured<M> for ChunkedStream<R>
+where
+    R: AsyncRead + Unpin,
+    M: Metrics<str>,
+{
+    type Measured = Measured<Self, M>;
+
+    fn into_measured_with(self, meter: M::Meter) -> Self::Measured {
+        Measured { meter, base: self }
+    }
+}
+
+/// The decoded chunks from a position on, reading more from the reader as they are requested.
+pub struct ChunkSegments<R> {
+    stream: ChunkedStream<R>,
+}
+
+impl<R: AsyncRead + Unpin> ParcomSegmentIterator for ChunkSegments<R> {
+    type Segment = str;
+    type SegmentRef = ChunkRef;
+
+    async fn next(&mut self, _n: usize) -> Option<Self::SegmentRef> {
+        let stream = &mut self.stream;
+        loop {
+            let chunk = stream.buffer.borrow().chunks.get(stream.chunk).cloned();
+            match chunk {
+                Some(chunk) => {
+                    let start = stream.offset;
+                    stream.chunk += 1;
+                    stream.offset = 0;
+                    return Some(ChunkRef { chunk, start });
+                }
+                None if !Buffer::fill(&stream.buffer).await => return None,
+                None => (),
+            }
+        }
+    }
+}
+
+/// The rest of a decoded chunk.
+#[derive(Debug, Clone)]
+pub struct ChunkRef {
+    chunk: Rc<str>,
+    start: usize,
+}
+
+impl Deref for ChunkRef {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.chunk[self.start..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkedStream;
+    use futures_io::AsyncRead;
+    use parcom::{
+        IntoMeasured, MeasuredStream, Meter, Metrics, ParcomSegmentIterator, ParcomStream,
+        RewindStream,
+    };
+    use std::{
+        collections::VecDeque,
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    /// Returns the given pieces one read at a time, yielding once before each.
+    struct Pieces {
+        pieces: VecDeque<Vec<u8>>,
+        ready: bool,
+    }
+
+    impl AsyncRead for Pieces {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let ready = self.ready;
+            self.ready = !ready;
+            if !ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let Some(mut piece) = self.pieces.pop_front() else {
+                return Poll::Ready(Ok(0));
+            };
+            let n = piece.len().min(buf.len());
+            buf[..n].copy_from_slice(&piece[..n]);
+            if n < piece.len() {
+                self.pieces.push_front(piece.split_off(n));
+            }
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    fn stream(pieces: &[&[u8]], chunk_size: usize) -> ChunkedStream<Pieces> {
+        let reader = Pieces {
+            pieces: pieces.iter().map(|p| p.to_vec()).collect(),
+            ready: false,
+        };
+        ChunkedStream::with_chunk_size(reader, chunk_size)
+    }
+
+    async fn rest<R: AsyncRead + Unpin>(stream: &ChunkedStream<R>) -> String {
+        let mut segments = stream.segments();
+        let mut text = String::new();
+        while let Some(segment) = segments.next(0).await {
+            text.push_str(&segment);
+        }
+        text
+    }
+
+    #[test]
+    fn decodes_characters_split_between_reads() {
+        let source = "a\u{e9}\u{3042}\u{1F600}b";
+        for chunk_size in 1..=source.len() {
+            let stream = stream(&[source.as_bytes()], chunk_size);
+            assert_eq!(pollster::block_on(rest(&stream)), source);
+        }
+
+        let stream = stream(&[b"a\xFF", b"\xE3\x81", b"b"], 4);
+        assert_eq!(pollster::block_on(rest(&stream)), "a\u{FFFD}\u{FFFD}b");
+    }
+
+    /// Counts lines, to check that measuring sees every piece of the text advanced over.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Lines(usize);
+
+    impl Metrics<str> for Lines {
+        type Meter = Self;
+    }
+
+    impl Meter<str> for Lines {
+        type Metrics = Self;
+
+        fn advance(self, segment: &str) -> Self {
+            Lines(self.0 + segment.matches('\n').count())
+        }
+
+        fn metrics(&self) -> Self {
+            *self
+        }
+    }
+
+    #[test]
+    fn advances_and_rewinds_across_chunks() {
+        let source = "a\nb\u{e9}\ncd\n";
+        let stream =
+            IntoMeasured::<Lines>::into_measured_with(stream(&[source.as_bytes()], 2), Lines(0));
+        let base = stream.clone();
+        assert_eq!(pollster::block_on(rest(&stream.base)), source);
+
+        let anchor = stream.anchor();
+        let stream = stream.advance(6).into_inner();
+        assert_eq!(stream.metrics(), Lines(2));
+        assert_eq!(pollster::block_on(rest(&stream.base)), "cd\n");
+
+        let stream = stream.rewind(anchor).advance(2).into_inner();
+        assert_eq!(stream.metrics(), Lines(1));
+        assert_eq!(pollster::block_on(rest(&stream.base)), "b\u{e9}\ncd\n");
+
+        let end = base.advance(100).into_inner();
+        assert_eq!(end.metrics(), Lines(3));
+        assert_eq!(pollster::block_on(rest(&end.base)), "");
+    }
+}
@@ -0,0 +1,120 @@
+//! Text held as a sequence of pieces, as editors keep documents in ropes.
+use super::{Measured, TextStream};
+use parcom::{IntoMeasured, Metrics, ParcomSegmentIterator, ParcomStream, RewindStream};
+use std::future::Ready;
+
+/// A stream over text split into pieces, each of which is a segment.
+///
+/// Pieces may be empty. Parsers read across piece boundaries, so how the text is split does not
+/// change what they parse.
+#[derive(Debug, Clone)]
+pub struct RopeStream<'me> {
+    pieces: &'me [&'me str],
+    /// Offset into the first piece.
+    offset: usize,
+}
+
+impl<'me> RopeStream<'me> {
+    pub fn new(pieces: &'me [&'me str]) -> Self {
+        Self { pieces, offset: 0 }
+    }
+}
+
+impl<'me> ParcomStream for RopeStream<'me> {
+    type Segment = str;
+    type SegmentStream = RopeSegments<'me>;
+    type Advance = Ready<Self>;
+
+    fn segments(&self) -> Self::SegmentStream {
+        RopeSegments {
+            pieces: self.pieces,
+            offset: self.offset,
+        }
+    }
+
+    /// Advances by `count` bytes, stopping at the end of the text.
+    fn advance(mut self, count: usize) -> Self::Advance {
+        let mut count = count;
+        while let Some((piece, rest)) = self.pieces.split_first() {
+            let available = piece.len() - self.offset;
+            if count < available {
+                self.offset += count;
+                break;
+            }
+            count -= available;
+            self.pieces = rest;
+            self.offset = 0;
+        }
+        std::future::ready(self)
+    }
+}
+
+impl<'me> TextStream for RopeStream<'me> {
+    fn fold_prefix<B>(&self, count: usize, init: B, mut f: impl FnMut(B, &str) -> B) -> B {
+        let mut acc = init;
+        let mut count = count;
+        let mut segments = self.segments();
+        while let Some(segment) = segments.next_piece() {
+            if count == 0 {
+                break;
+            }
+            let end = segment.len().min(count);
+            acc = f(acc, &segment[..end]);
+            count -= end;
+        }
+        acc
+    }
+}
+
+impl<'me> RewindStream for RopeStream<'me> {
+    type Anchor = RopeAnchor<'me>;
+
+    fn anchor(&self) -> Self::Anchor {
+        RopeAnchor {
+            stream: self.clone(),
+        }
+    }
+
+    fn rewind(self, anchor: Self::Anchor) -> Self {
+        anchor.stream
+    }
+}
+
+pub struct RopeAnchor<'me> {
+    stream: RopeStream<'me>,
+}
+
+impl<'me, M> IntoMeasured<M> for RopeStream<'me>
+where
+    M: Metrics<str>,
+{
+    type Measured = Measured<Self, M>;
+
+    fn into_measured_with(self, meter: M::Meter) -> Self::Measured {
+        Measured { meter, base: self }
+    }
+}
+
+pub struct RopeSegments<'me> {
+    pieces: &'me [&'me str],
+    offset: usize,
+}
+
+impl<'me> RopeSegments<'me> {
+    fn next_piece(&mut self) -> Option<&'me str> {
+        let (piece, rest) = self.pieces.split_first()?;
+        let piece = &piece[self.offset..];
+        self.pieces = rest;
+        self.offset = 0;
+        Some(piece)
+    }
+}
+
+impl<'me> ParcomSegmentIterator for RopeSegments<'me> {
+    type Segment = str;
+    type SegmentRef = &'me str;
+
+    fn next(&mut self, _n: usize) -> impl std::future::Future<Output = Option<&'me str>> {
+        std::future::ready(self.next_piece())
+    }
+}
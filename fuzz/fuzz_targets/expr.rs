@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minilet_fuzz::{check_node, parse_prefix, SpanCheck};
+use minilet_syntax::{parse_expr, visit::Visit, Expr};
+
+fuzz_target!(|source: &str| {
+    if let Some(expr) = parse_prefix::<Expr>(source) {
+        check_node(source, &expr);
+        SpanCheck { source }.visit_expr(&expr);
+    }
+
+    if let Ok(expr) = parse_expr(source) {
+        SpanCheck { source }.visit_expr(&expr);
+    }
+});
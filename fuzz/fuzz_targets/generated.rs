@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minilet_fuzz::{check_recovering, generate::Program};
+use minilet_syntax::format::{format, FormatOptions};
+
+fuzz_target!(|program: Program| {
+    let source = program.to_string();
+    let Some(file) = check_recovering(&source) else {
+        panic!("generated program does not parse:\n{source}");
+    };
+
+    // formatting keeps the program valid, and formatting it again changes nothing.
+    let options = FormatOptions::default();
+    let formatted = format(&file, &options);
+    let Some(file) = check_recovering(&formatted) else {
+        panic!("formatted program does not parse:\n{formatted}");
+    };
+    assert_eq!(format(&file, &options), formatted);
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minilet_fuzz::{check_node, parse_prefix};
+use minilet_syntax::{
+    literal::{IntegerLiteral, StringLiteral},
+    Literal,
+};
+
+fuzz_target!(|source: &str| {
+    if let Some(literal) = parse_prefix::<IntegerLiteral>(source) {
+        check_node(source, &literal);
+    }
+
+    if let Some(literal) = parse_prefix::<StringLiteral>(source) {
+        check_node(source, &literal);
+        assert!(literal.text.len() <= literal.raw_text.len());
+    }
+
+    if let Some(literal) = parse_prefix::<Literal>(source) {
+        check_node(source, &literal);
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minilet_fuzz::{check_node, check_recovering, parse_prefix, SpanCheck};
+use minilet_syntax::{visit::Visit, Stmts};
+
+fuzz_target!(|source: &str| {
+    if let Some(stmts) = parse_prefix::<Stmts>(source) {
+        check_node(source, &stmts);
+        SpanCheck { source }.visit_stmts(&stmts);
+    }
+
+    check_recovering(source);
+});
@@ -0,0 +1,422 @@
+//! Well-formed programs built from fuzzer input, for invariants that only hold for valid source.
+//!
+//! [`Program`] is a small syntax tree of its own rather than the parser's, so that building one
+//! needs no spans. Printing it gives source that must parse without errors.
+use arbitrary::{Arbitrary, Result, Unstructured};
+use std::fmt::{self, Display, Formatter, Write};
+
+/// How deeply generated terms nest, well within [`minilet_syntax::DEFAULT_MAX_DEPTH`] even with
+/// the parentheses added while printing.
+const MAX_DEPTH: usize = 8;
+
+/// Identifiers, some starting with a keyword.
+const NAMES: &[&str] = &["a", "b", "f", "x1", "letter", "form", "index", "asx"];
+const CUSTOM_OPS: &[&str] = &["<+>", "|>", "+++"];
+const CHAIN_OPS: &[&str] = &["+", "-", "*", "/", "%", "**", "&", "|", "^", "<<", ">>"];
+/// Non-associative operators, which cannot be chained.
+const COMPARE_OPS: &[&str] = &["==", "!=", "<", ">", "<=", ">=", "..", "..="];
+const UNARY_OPS: &[&str] = &["-", "+", "~"];
+const FIXITIES: &[&str] = &["infixl", "infixr", "infix"];
+
+#[derive(Debug)]
+pub struct Program {
+    pub stmts: Vec<Stmt>,
+    pub trailing_semi: bool,
+    /// Trivia put, in turn, wherever the grammar allows it.
+    pub gaps: Vec<Gap>,
+}
+
+#[derive(Debug)]
+pub enum Stmt {
+    Let(Binder, Expr),
+    Fixity(&'static str, u8, &'static str),
+    Import(String, &'static str),
+    Expr(Expr),
+}
+
+#[derive(Debug)]
+pub enum Binder {
+    Name(&'static str),
+    Op(&'static str),
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    Term(Term),
+    Chain(Term, Vec<(&'static str, Term)>),
+    Compare(Term, &'static str, Term),
+    Where(Box<Expr>, Vec<(Binder, Expr)>),
+}
+
+#[derive(Debug)]
+pub enum Term {
+    Int(Int),
+    Str(String),
+    Name(&'static str),
+    Tuple(Vec<Expr>),
+    Block(Vec<Stmt>, bool),
+    Unary(&'static str, Box<Term>),
+    App(Box<Term>, Vec<Expr>),
+    Field(Box<Term>, &'static str),
+    LetIn(Binder, Box<Expr>, Box<Expr>),
+    For(&'static str, Box<Expr>, Vec<Stmt>),
+}
+
+#[derive(Debug, Arbitrary)]
+pub enum Int {
+    Dec(u32),
+    Hex(u32),
+    Bin(u16),
+    Oct(u16),
+    /// Decimal with a leading zero.
+    Padded(u16),
+}
+
+#[derive(Debug, Clone, Copy, Arbitrary)]
+pub enum Gap {
+    None,
+    Space,
+    Newline,
+    Comment,
+}
+
+impl<'a> Arbitrary<'a> for Program {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            stmts: stmts(u, 0)?,
+            trailing_semi: u.arbitrary()?,
+            gaps: u.arbitrary()?,
+        })
+    }
+}
+
+fn stmts(u: &mut Unstructured, depth: usize) -> Result<Vec<Stmt>> {
+    let len = u.int_in_range(0..=4)?;
+    (0..len).map(|_| stmt(u, depth)).collect()
+}
+
+fn stmt(u: &mut Unstructured, depth: usize) -> Result<Stmt> {
+    let stmt = match u.int_in_range(0..=5)? {
+        0 | 1 => Stmt::Let(binder(u)?, expr(u, depth)?),
+        2 => Stmt::Fixity(
+            u.choose(FIXITIES)?,
+            u.int_in_range(0..=9)?,
+            u.choose(CUSTOM_OPS)?,
+        ),
+        3 => Stmt::Import(u.arbitrary()?, u.choose(NAMES)?),
+        _ => Stmt::Expr(expr(u, depth)?),
+    };
+    Ok(stmt)
+}
+
+fn binder(u: &mut Unstructured) -> Result<Binder> {
+    if u.ratio(1, 4)? {
+        Ok(Binder::Op(u.choose(CUSTOM_OPS)?))
+    } else {
+        Ok(Binder::Name(u.choose(NAMES)?))
+    }
+}
+
+fn expr(u: &mut Unstructured, depth: usize) -> Result<Expr> {
+    let expr = match u.int_in_range(0..=5)? {
+        0 | 1 => Expr::Term(term(u, depth)?),
+        2 | 3 => {
+            let len = u.int_in_range(1..=3)?;
+            let rest = (0..len)
+                .map(|_| Ok((*u.choose(CHAIN_OPS)?, term(u, depth)?)))
+                .collect::<Result<_>>()?;
+            Expr::Chain(term(u, depth)?, rest)
+        }
+        4 => Expr::Compare(term(u, depth)?, u.choose(COMPARE_OPS)?, term(u, depth)?),
+        _ if depth < MAX_DEPTH => {
+            let len = u.int_in_range(1..=2)?;
+            let bindings = (0..len)
+                .map(|_| Ok((binder(u)?, expr(u, depth + 1)?)))
+                .collect::<Result<_>>()?;
+            Expr::Where(Box::new(expr(u, depth + 1)?), bindings)
+        }
+        _ => Expr::Term(term(u, depth)?),
+    };
+    Ok(expr)
+}
+
+fn exprs(u: &mut Unstructured, depth: usize) -> Result<Vec<Expr>> {
+    let len = u.int_in_range(0..=3)?;
+    (0..len).map(|_| expr(u, depth)).collect()
+}
+
+fn term(u: &mut Unstructured, depth: usize) -> Result<Term> {
+    let leaves = 3;
+    let kinds = if depth < MAX_DEPTH {
+        leaves + 7
+    } else {
+        leaves
+    };
+    let depth = depth + 1;
+    let term = match u.choose_index(kinds)? {
+        0 => Term::Int(u.arbitrary()?),
+        1 => Term::Str(u.arbitrary()?),
+        2 => Term::Name(u.choose(NAMES)?),
+        3 => Term::Tuple(exprs(u, depth)?),
+        4 => Term::Block(stmts(u, depth)?, u.arbitrary()?),
+        5 => Term::Unary(u.choose(UNARY_OPS)?, Box::new(term(u, depth)?)),
+        6 => Term::App(Box::new(term(u, depth)?), exprs(u, depth)?),
+        7 => Term::Field(Box::new(term(u, depth)?), u.choose(NAMES)?),
+        8 => Term::LetIn(
+            binder(u)?,
+            Box::new(expr(u, depth)?),
+            Box::new(expr(u, depth)?),
+        ),
+        _ => Term::For(
+            u.choose(NAMES)?,
+            Box::new(expr(u, depth)?),
+            stmts(u, depth)?,
+        ),
+    };
+    Ok(term)
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut printer = Printer {
+            out: f,
+            gaps: &self.gaps,
+            next: 0,
+        };
+        printer.gap()?;
+        printer.stmts(&self.stmts, self.trailing_semi)?;
+        printer.gap()
+    }
+}
+
+struct Printer<'a, 'f> {
+    out: &'a mut Formatter<'f>,
+    gaps: &'a [Gap],
+    next: usize,
+}
+
+impl Printer<'_, '_> {
+    fn gap(&mut self) -> fmt::Result {
+        let Some(gap) = self.gaps.get(self.next % self.gaps.len().max(1)) else {
+            return Ok(());
+        };
+        self.next += 1;
+        match gap {
+            Gap::None => Ok(()),
+            Gap::Space => self.out.write_char(' '),
+            Gap::Newline => self.out.write_char('\n'),
+            Gap::Comment => self.out.write_str(" // note\n"),
+        }
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt], trailing_semi: bool) -> fmt::Result {
+        for (i, stmt) in stmts.iter().enumerate() {
+            if i > 0 {
+                self.separator(";")?;
+            }
+            self.stmt(stmt)?;
+        }
+        if trailing_semi && !stmts.is_empty() {
+            self.gap()?;
+            self.out.write_char(';')?;
+        }
+        Ok(())
+    }
+
+    fn separator(&mut self, separator: &str) -> fmt::Result {
+        self.gap()?;
+        self.out.write_str(separator)?;
+        self.gap()
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> fmt::Result {
+        match stmt {
+            Stmt::Let(binder, expr) => {
+                self.out.write_str("let ")?;
+                self.binding(binder, expr)
+            }
+            Stmt::Fixity(keyword, precedence, symbol) => {
+                write!(self.out, "{keyword} {precedence} {symbol}")
+            }
+            Stmt::Import(path, alias) => {
+                self.out.write_str("import ")?;
+                self.string(path)?;
+                write!(self.out, " as {alias}")
+            }
+            Stmt::Expr(expr) => self.expr(expr),
+        }
+    }
+
+    fn binding(&mut self, binder: &Binder, expr: &Expr) -> fmt::Result {
+        match binder {
+            Binder::Name(name) => self.out.write_str(name)?,
+            Binder::Op(symbol) => write!(self.out, "({symbol})")?,
+        }
+        self.separator("=")?;
+        self.expr(expr)
+    }
+
+    fn expr(&mut self, expr: &Expr) -> fmt::Result {
+        match expr {
+            Expr::Term(term) => self.term(term),
+            Expr::Chain(first, rest) => {
+                // a `let ... in` body would take the operators after it.
+                self.operand(first, rest.is_empty())?;
+                for (i, (op, term)) in rest.iter().enumerate() {
+                    write!(self.out, " {op} ")?;
+                    self.operand(term, i + 1 == rest.len())?;
+                }
+                Ok(())
+            }
+            Expr::Compare(lhs, op, rhs) => {
+                self.operand(lhs, false)?;
+                write!(self.out, " {op} ")?;
+                self.operand(rhs, true)
+            }
+            Expr::Where(expr, bindings) => {
+                self.expr(expr)?;
+                self.out.write_str(" where ")?;
+                for (i, (binder, expr)) in bindings.iter().enumerate() {
+                    if i > 0 {
+                        self.separator(",")?;
+                    }
+                    self.binding(binder, expr)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn operand(&mut self, term: &Term, last: bool) -> fmt::Result {
+        match term {
+            Term::LetIn(..) if !last => self.parenthesized(term),
+            _ => self.term(term),
+        }
+    }
+
+    fn parenthesized(&mut self, term: &Term) -> fmt::Result {
+        self.out.write_char('(')?;
+        self.gap()?;
+        self.term(term)?;
+        self.gap()?;
+        self.out.write_char(')')
+    }
+
+    fn term(&mut self, term: &Term) -> fmt::Result {
+        match term {
+            Term::Int(int) => self.int(int),
+            Term::Str(text) => self.string(text),
+            Term::Name(name) => self.out.write_str(name),
+            Term::Tuple(items) => self.tuple(items),
+            Term::Block(stmts, trailing_semi) => self.block(stmts, *trailing_semi),
+            Term::Unary(op, term) => {
+                self.out.write_str(op)?;
+                // the operand follows the operator directly, and must not extend it. Prefix
+                // operators are a single character each, so they stack as in `-~x`.
+                match **term {
+                    Term::Int(_)
+                    | Term::Str(_)
+                    | Term::Name(_)
+                    | Term::Tuple(_)
+                    | Term::Unary(..) => self.term(term),
+                    _ => self.parenthesized(term),
+                }
+            }
+            Term::App(receiver, args) => {
+                self.receiver(receiver)?;
+                self.tuple(args)
+            }
+            Term::Field(receiver, name) => {
+                self.receiver(receiver)?;
+                write!(self.out, ".{name}")
+            }
+            Term::LetIn(binder, value, body) => {
+                self.out.write_str("let ")?;
+                self.binding(binder, value)?;
+                self.out.write_str(" in ")?;
+                self.expr(body)
+            }
+            Term::For(name, iter, body) => {
+                write!(self.out, "for {name} in ")?;
+                match **iter {
+                    Expr::Where(..) => {
+                        self.out.write_char('(')?;
+                        self.expr(iter)?;
+                        self.out.write_char(')')?;
+                    }
+                    _ => self.expr(iter)?,
+                }
+                self.gap()?;
+                self.block(body, false)
+            }
+        }
+    }
+
+    fn receiver(&mut self, term: &Term) -> fmt::Result {
+        match term {
+            Term::Name(_) | Term::Tuple(_) | Term::App(..) | Term::Field(..) => self.term(term),
+            _ => self.parenthesized(term),
+        }
+    }
+
+    fn tuple(&mut self, items: &[Expr]) -> fmt::Result {
+        self.out.write_char('(')?;
+        self.gap()?;
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.separator(",")?;
+            }
+            // the bindings of a `where` would take the items after it.
+            if i + 1 < items.len() && ends_with_where(item) {
+                self.out.write_char('(')?;
+                self.expr(item)?;
+                self.out.write_char(')')?;
+            } else {
+                self.expr(item)?;
+            }
+        }
+        self.gap()?;
+        self.out.write_char(')')
+    }
+
+    fn block(&mut self, stmts: &[Stmt], trailing_semi: bool) -> fmt::Result {
+        self.out.write_char('{')?;
+        self.gap()?;
+        self.stmts(stmts, trailing_semi)?;
+        self.gap()?;
+        self.out.write_char('}')
+    }
+
+    fn int(&mut self, int: &Int) -> fmt::Result {
+        match int {
+            Int::Dec(v) => write!(self.out, "{v}"),
+            Int::Hex(v) => write!(self.out, "0x{v:X}"),
+            Int::Bin(v) => write!(self.out, "0b{v:b}"),
+            Int::Oct(v) => write!(self.out, "0o{v:o}"),
+            Int::Padded(v) => write!(self.out, "0{v}"),
+        }
+    }
+
+    fn string(&mut self, text: &str) -> fmt::Result {
+        self.out.write_char('"')?;
+        for c in text.chars() {
+            if c == '"' || c == '\\' {
+                self.out.write_char('\\')?;
+            }
+            self.out.write_char(c)?;
+        }
+        self.out.write_char('"')
+    }
+}
+
+fn ends_with_where(expr: &Expr) -> bool {
+    let last = match expr {
+        Expr::Where(..) => return true,
+        Expr::Term(term) | Expr::Compare(_, _, term) => term,
+        Expr::Chain(first, rest) => rest.last().map_or(first, |(_, term)| term),
+    };
+    match last {
+        Term::LetIn(_, _, body) => ends_with_where(body),
+        _ => false,
+    }
+}
@@ -0,0 +1,123 @@
+//! Invariants checked by the fuzz targets in `fuzz_targets/`.
+//!
+//! Run a target from this directory with `cargo +nightly fuzz run <target>`, where the targets
+//! are `stmts`, `expr`, `literals` and `generated`.
+pub mod generate;
+
+use minilet_syntax::{
+    op::OpSymbol,
+    parse_recovering,
+    recovery::ErrorNode,
+    token::AnyToken,
+    unary_op::UnaryOp,
+    visit::{self, Visit},
+    Expr, Ident, InputStream, Literal, Parse, ParseContext, Position, PositionMeter, SourceFile,
+    Spacing, Span, Spanned, Stmt, Term, ToSource, Trivia,
+};
+use minilet_utils::stream::StrStream;
+use parcom::{prelude::*, IntoMeasured};
+
+pub fn measure(source: &str) -> impl InputStream + '_ {
+    IntoMeasured::<Position>::into_measured_with(StrStream::new(source), PositionMeter::default())
+}
+
+/// Parses a `T` from the start of `source`, returning it if it parsed.
+pub fn parse_prefix<T: Parse>(source: &str) -> Option<T> {
+    let ctx = ParseContext::new();
+    match pollster::block_on(T::parse(measure(source), &ctx)) {
+        Done(v, _) => Some(v),
+        Fail(_, _) | Fatal(_, _) => None,
+    }
+}
+
+/// Parses the whole of `source`, checking the tree against it, and returns the tree if it has no
+/// errors.
+pub fn check_recovering(source: &str) -> Option<SourceFile> {
+    let ctx = ParseContext::new();
+    let recovered = pollster::block_on(parse_recovering(measure(source), &ctx));
+    let file = recovered.value;
+
+    assert_eq!(
+        file.to_source(),
+        source,
+        "printing the tree changed the input"
+    );
+    check_node(source, &file);
+    SpanCheck { source }.visit_source_file(&file);
+
+    recovered.errors.is_empty().then_some(file)
+}
+
+/// Checks that `node` covers the text it was parsed from.
+pub fn check_node<T: Spanned + ToSource>(source: &str, node: &T) {
+    let span = node.span();
+    check_span(source, &span);
+    assert_eq!(node.to_source(), source[span.bytes()], "{span:?}");
+}
+
+/// Checks that `span` is within `source` and starts and ends on character boundaries.
+pub fn check_span(source: &str, span: &Span) {
+    let (start, end) = (span.start(), span.end());
+    assert!(start.byte <= end.byte, "{span:?} ends before it starts");
+    assert!(
+        source.get(span.bytes()).is_some(),
+        "{span:?} is not within {} bytes of input",
+        source.len()
+    );
+}
+
+/// Checks the span of every node of a tree with [`check_node`].
+pub struct SpanCheck<'a> {
+    pub source: &'a str,
+}
+
+impl Visit for SpanCheck<'_> {
+    fn visit_stmt(&mut self, node: &Stmt) {
+        check_node(self.source, node);
+        visit::walk_stmt(self, node)
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        check_node(self.source, node);
+        visit::walk_expr(self, node)
+    }
+
+    fn visit_term(&mut self, node: &Term) {
+        check_node(self.source, node);
+        visit::walk_term(self, node)
+    }
+
+    fn visit_literal(&mut self, node: &Literal) {
+        check_node(self.source, node);
+        visit::walk_literal(self, node)
+    }
+
+    fn visit_ident(&mut self, node: &Ident) {
+        check_node(self.source, node);
+    }
+
+    fn visit_op_symbol(&mut self, node: &OpSymbol) {
+        check_node(self.source, node);
+    }
+
+    fn visit_unary_op(&mut self, node: &UnaryOp) {
+        check_node(self.source, node);
+    }
+
+    fn visit_error_node(&mut self, node: &ErrorNode) {
+        check_span(self.source, &node.span);
+        assert_eq!(node.text, self.source[node.span.bytes()]);
+    }
+
+    fn visit_trivia(&mut self, node: &Trivia) {
+        check_node(self.source, node);
+    }
+
+    fn visit_spacing(&mut self, node: &Spacing) {
+        check_node(self.source, node);
+    }
+
+    fn visit_token(&mut self, node: &dyn AnyToken) {
+        check_span(self.source, &node.span());
+    }
+}